@@ -0,0 +1,55 @@
+//! Compares the guards of [`LocalArena`] with the locked guards of [`Arena`].
+//!
+//! Run with `cargo bench --bench local_arena`.
+use std::{hint::black_box, time::Instant};
+
+use generativity::make_guard;
+use niche_collections::{alloc::arena::LocalArena, prelude::*};
+
+const COUNT: u32 = 100_000;
+const ROUNDS: u32 = 20;
+
+fn measure(name: &str, mut run: impl FnMut() -> u64) {
+    let start = Instant::now();
+    let mut sum = 0;
+    for _ in 0..ROUNDS {
+        sum += run();
+    }
+    let per_op = start.elapsed().as_nanos() as f64 / f64::from(ROUNDS * COUNT);
+    println!("{name:<24} {per_op:>8.2} ns/op");
+    black_box(sum);
+}
+
+fn main() {
+    {
+        make_guard!(port);
+        make_guard!(man);
+        let mut arena = Arena::<Typed<u32>, Versioned<true>>::new(port, man, Headless::default());
+        let handles: Vec<_> = (0..COUNT).map(|i| arena.alloc().insert(i).unwrap()).collect();
+        measure("locked read", || {
+            handles.iter().map(|&handle| u64::from(*arena.read().get(handle).unwrap())).sum()
+        });
+        measure("locked write", || {
+            handles.iter().map(|&handle| u64::from(*arena.write().get_mut(handle).unwrap())).sum()
+        });
+        measure("locked alloc", || {
+            (0..COUNT).map(|i| arena.alloc().insert(i).map(drop).unwrap()).count() as u64
+        });
+    }
+    {
+        make_guard!(port);
+        make_guard!(man);
+        let mut arena =
+            LocalArena::<Typed<u32>, Versioned<true>>::new(port, man, Headless::default());
+        let handles: Vec<_> = (0..COUNT).map(|i| arena.alloc().insert(i).unwrap()).collect();
+        measure("local read", || {
+            handles.iter().map(|&handle| u64::from(*arena.read().get(handle).unwrap())).sum()
+        });
+        measure("local write", || {
+            handles.iter().map(|&handle| u64::from(*arena.write().get_mut(handle).unwrap())).sum()
+        });
+        measure("local alloc", || {
+            (0..COUNT).map(|i| arena.alloc().insert(i).map(drop).unwrap()).count() as u64
+        });
+    }
+}
@@ -0,0 +1,412 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    Data, DeriveInput, Error, Fields, GenericParam, Ident, Index, Lifetime, LifetimeParam, Member,
+    Type, parse_macro_input, spanned::Spanned, visit_mut::VisitMut,
+};
+
+/// Implements `Columns` and `EnumColumns` for an enum.
+///
+/// The enum is stored as a column of variant indices followed by one column per field position,
+/// which is shared by all variants. Alongside, `{Name}Ref` and `{Name}Mut` are generated,
+/// which mirror the variants with borrowed fields.
+#[proc_macro_derive(Columns)]
+pub fn derive_columns(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive_enum_columns(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Implements `MappableHandle` for a struct with a single lifetime parameter, the brand of its handles.
+///
+/// Every field whose type mentions the lifetime is mapped, all of them have to share the same `Data`.
+/// Fields with a different `Data` are rejected with an error pointing at the field.
+/// Other fields are moved as they are.
+#[proc_macro_derive(MappableHandle)]
+pub fn derive_mappable_handle(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive_mappable(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+struct Variant {
+    ident:  Ident,
+    fields: Fields,
+    types:  Vec<Type>,
+}
+
+fn derive_enum_columns(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(&input.ident, "Columns can only be derived for enums"));
+    };
+    if let Some(lifetime) = input.generics.lifetimes().next() {
+        return Err(Error::new_spanned(lifetime, "Columns can't be derived for borrowed data"));
+    }
+    let krate = quote!(::niche_collections::alloc);
+    let store = quote!(#krate::store);
+    let name = &input.ident;
+    let vis = &input.vis;
+    let ref_name = format_ident!("{}Ref", name);
+    let mut_name = format_ident!("{}Mut", name);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut ref_generics = input.generics.clone();
+    let lifetime = Lifetime::new("'a", Span::call_site());
+    ref_generics.params.insert(0, GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())));
+    let (ref_impl_generics, ref_ty_generics, _) = ref_generics.split_for_impl();
+
+    let variants: Vec<_> = data
+        .variants
+        .iter()
+        .map(|variant| Variant {
+            ident:  variant.ident.clone(),
+            fields: variant.fields.clone(),
+            types:  variant.fields.iter().map(|field| field.ty.clone()).collect(),
+        })
+        .collect();
+    let positions = variants.iter().map(|variant| variant.types.len()).max().unwrap_or(0);
+    let count = positions + 1;
+    // layout of each column after the variant column
+    let layouts: Vec<_> = (0..positions)
+        .map(|i| {
+            let types = variants.iter().filter_map(|variant| variant.types.get(i));
+            quote!(const { #store::union_layout(&[#(::std::alloc::Layout::new::<#types>()),*]) })
+        })
+        .collect();
+    let layouts = quote! {
+        #[allow(unused_variables)]
+        let layouts: [::std::alloc::Layout; #positions] = [#(#layouts),*];
+    };
+    let field_names = |variant: &Variant| -> Vec<Ident> {
+        (0..variant.types.len()).map(|i| format_ident!("field{}", i)).collect()
+    };
+    // builds `Path::Variant` with `values` in the shape of the variant
+    let construct = |path: TokenStream2, variant: &Variant, values: &[TokenStream2]| {
+        let ident = &variant.ident;
+        match &variant.fields {
+            Fields::Named(fields) => {
+                let names = fields.named.iter().map(|field| &field.ident);
+                quote!(#path::#ident { #(#names: #values),* })
+            },
+            Fields::Unnamed(_) => quote!(#path::#ident(#(#values),*)),
+            Fields::Unit => quote!(#path::#ident),
+        }
+    };
+    let slot = |i: usize, ty: &Type| {
+        let column = i + 1;
+        quote!(#store::slot(columns[#column], layouts[#i], index).cast::<#ty>())
+    };
+    let element = |i: usize, ty: &Type| {
+        let column = i + 1;
+        quote!(rows.element(#column, index, layouts[#i].size()).cast::<#ty>())
+    };
+
+    let view_variants = |reference: TokenStream2| -> Vec<TokenStream2> {
+        variants
+            .iter()
+            .map(|variant| {
+                let ident = &variant.ident;
+                let types = variant.types.iter().map(|ty| quote!(#reference #ty));
+                match &variant.fields {
+                    Fields::Named(fields) => {
+                        let names = fields.named.iter().map(|field| &field.ident);
+                        quote!(#ident { #(#names: #types),* })
+                    },
+                    Fields::Unnamed(_) => quote!(#ident(#(#types),*)),
+                    Fields::Unit => quote!(#ident),
+                }
+            })
+            .collect()
+    };
+    let ref_variants = view_variants(quote!(&#lifetime));
+    let mut_variants = view_variants(quote!(&#lifetime mut));
+
+    let mut move_arms = Vec::new();
+    let mut take_arms = Vec::new();
+    let mut ref_arms = Vec::new();
+    let mut mut_arms = Vec::new();
+    for (v, variant) in variants.iter().enumerate() {
+        let v = v as u32;
+        let names = field_names(variant);
+        let bindings: Vec<_> = names.iter().map(|name| quote!(#name)).collect();
+        let pattern = construct(quote!(Self), variant, &bindings);
+        let writes = variant.types.iter().enumerate().map(|(i, ty)| {
+            let slot = slot(i, ty);
+            let name = &names[i];
+            quote!(#slot.write(#name);)
+        });
+        move_arms.push(quote!(#pattern => {
+            #store::write_variant(columns, index, #v);
+            #(#writes)*
+        }));
+        let reads: Vec<_> = variant
+            .types
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| {
+                let slot = slot(i, ty);
+                quote!(#slot.read())
+            })
+            .collect();
+        let value = construct(quote!(Self), variant, &reads);
+        take_arms.push(quote!(#v => #value,));
+        let refs: Vec<_> = variant
+            .types
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| {
+                let slot = element(i, ty);
+                quote!(#slot.as_ref())
+            })
+            .collect();
+        let value = construct(quote!(#ref_name), variant, &refs);
+        ref_arms.push(quote!(#v => #value,));
+        let muts: Vec<_> = variant
+            .types
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| {
+                let slot = element(i, ty);
+                quote!(#slot.as_mut())
+            })
+            .collect();
+        let marks = (0..variant.types.len()).map(|i| {
+            let column = i + 1;
+            quote!(rows.mark(#column, index);)
+        });
+        let value = construct(quote!(#mut_name), variant, &muts);
+        mut_arms.push(quote!(#v => {
+            #(#marks)*
+            #value
+        }));
+    }
+
+    Ok(quote! {
+        #[allow(dead_code, reason = "not every variant has to be accessed")]
+        #vis enum #ref_name #ref_impl_generics #where_clause {
+            #(#ref_variants),*
+        }
+        #[allow(dead_code, reason = "not every variant has to be accessed")]
+        #vis enum #mut_name #ref_impl_generics #where_clause {
+            #(#mut_variants),*
+        }
+        // SAFETY: every column is registered with the layouts that are used to access it
+        unsafe impl #impl_generics #store::Columns for #name #ty_generics #where_clause {
+            type Schema = ();
+
+            type Ref<'a, I> = #store::EnumRef<'a, Self, I>
+            where
+                I: #store::IntoIndex + 'a,
+                Self: 'a;
+            type Mut<'a, I> = #store::EnumMut<'a, Self, I>
+            where
+                I: #store::IntoIndex + 'a,
+                Self: 'a;
+
+            fn count(_: &()) -> usize {
+                #count
+            }
+            fn register_layout(
+                _: &(),
+                rows: #krate::Length,
+                register: &mut impl FnMut(::std::alloc::Layout),
+            ) -> Result<(), ::std::alloc::LayoutError> {
+                #layouts
+                register(#store::column_layout(#store::variant_layout(), rows)?);
+                for layout in layouts {
+                    register(#store::column_layout(layout, rows)?);
+                }
+                Ok(())
+            }
+            fn move_into(
+                self,
+                _: &(),
+                index: #krate::Index,
+                columns: &[::std::ptr::NonNull<u8>],
+            ) {
+                #layouts
+                // SAFETY: the columns were registered with these layouts
+                unsafe {
+                    match self {
+                        #(#move_arms)*
+                    }
+                }
+            }
+            fn take(_: &(), index: #krate::Index, columns: &[::std::ptr::NonNull<u8>]) -> Self {
+                #layouts
+                // SAFETY: the columns were registered with these layouts and hold the variant
+                unsafe {
+                    match #store::read_variant(columns, index) {
+                        #(#take_arms)*
+                        _ => unreachable!("invalid variant"),
+                    }
+                }
+            }
+            fn as_freelist_entry<'a>(
+                _: &(),
+                index: #krate::Index,
+                columns: &'a [::std::ptr::NonNull<u8>],
+            ) -> &'a mut Option<#krate::Index> {
+                // SAFETY: column 0 was registered as variant column
+                unsafe { #store::variant_freelist_entry(columns, index) }
+            }
+            unsafe fn make_ref<'a, I>(_: &'a (), rows: #store::RawRows<'a>) -> Self::Ref<'a, I>
+            where
+                I: #store::IntoIndex + 'a,
+                Self: 'a,
+            {
+                // SAFETY: guarantied by caller
+                unsafe { #store::EnumRef::new(rows) }
+            }
+            unsafe fn make_mut<'a, I>(_: &'a (), rows: #store::RawRows<'a>) -> Self::Mut<'a, I>
+            where
+                I: #store::IntoIndex + 'a,
+                Self: 'a,
+            {
+                // SAFETY: guarantied by caller
+                unsafe { #store::EnumMut::new(rows) }
+            }
+        }
+        // SAFETY: column layouts match the ones used by register_layout
+        unsafe impl #impl_generics #store::EnumColumns for #name #ty_generics #where_clause {
+            type VariantRef<'a> = #ref_name #ref_ty_generics where Self: 'a;
+            type VariantMut<'a> = #mut_name #ref_ty_generics where Self: 'a;
+
+            unsafe fn variant_ref<'a>(
+                rows: #store::RawRows<'a>,
+                index: #krate::Index,
+            ) -> Self::VariantRef<'a> {
+                #layouts
+                // SAFETY: index is occupied and holds the variant
+                unsafe {
+                    match #store::read_row_variant(&rows, index) {
+                        #(#ref_arms)*
+                        _ => unreachable!("invalid variant"),
+                    }
+                }
+            }
+            unsafe fn variant_mut<'a>(
+                rows: #store::RawRows<'a>,
+                index: #krate::Index,
+            ) -> Self::VariantMut<'a> {
+                #layouts
+                // SAFETY: index is occupied, holds the variant and is not borrowed elsewhere
+                unsafe {
+                    match #store::read_row_variant(&rows, index) {
+                        #(#mut_arms)*
+                        _ => unreachable!("invalid variant"),
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Replaces a lifetime within a type.
+struct ReplaceLifetime<'a> {
+    from: &'a Lifetime,
+    to:   Lifetime,
+    hits: usize,
+}
+impl VisitMut for ReplaceLifetime<'_> {
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == self.from.ident {
+            *lifetime = self.to.clone();
+            self.hits += 1;
+        }
+    }
+}
+fn replace_lifetime(ty: &Type, from: &Lifetime, to: &str) -> (Type, bool) {
+    let mut ty = ty.clone();
+    let mut visitor = ReplaceLifetime { from, to: Lifetime::new(to, Span::call_site()), hits: 0 };
+    visitor.visit_type_mut(&mut ty);
+    (ty, visitor.hits > 0)
+}
+
+fn derive_mappable(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "MappableHandle can only be derived for structs",
+        ));
+    };
+    let mut lifetimes = input.generics.lifetimes();
+    let (Some(brand), None) = (lifetimes.next(), lifetimes.next()) else {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "MappableHandle needs exactly one lifetime parameter for the handles",
+        ));
+    };
+    let brand = brand.lifetime.clone();
+    let krate = quote!(::niche_collections::alloc);
+    let mappable = quote!(#krate::arena::MappableHandle);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // the type with its brand replaced by `to`
+    let with_brand = |to: &str| {
+        let mut generics = input.generics.clone();
+        for param in generics.lifetimes_mut() {
+            param.lifetime = Lifetime::new(to, Span::call_site());
+        }
+        let (_, ty_generics, _) = generics.split_for_impl();
+        quote!(#name #ty_generics)
+    };
+    let container = with_brand("'__id");
+
+    let mut handles = Vec::new();
+    let mut updates = Vec::new();
+    let mut markers = Vec::new();
+    let mut data_ty = None;
+    for (i, field) in data.fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        };
+        let (marker, mapped) = replace_lifetime(&field.ty, &brand, "'static");
+        if !mapped {
+            updates.push(quote!(#member: from.#member));
+            continue;
+        }
+        data_ty.get_or_insert_with(|| quote!(<#marker as #mappable>::Data));
+        markers.push((field.ty.span(), marker.clone()));
+        handles.push(quote!(<#marker as #mappable>::handles(&target.#member, f);));
+        updates.push(quote!(#member: <#marker as #mappable>::update(from.#member, f)));
+    }
+    let Some(data_ty) = data_ty else {
+        return Err(Error::new_spanned(
+            name,
+            "MappableHandle needs at least one field holding handles",
+        ));
+    };
+
+    let checks = markers
+        .iter()
+        .map(|(span, marker)| quote_spanned!(*span=> __same_data::<#data_ty, #marker>();));
+
+    Ok(quote! {
+        const _: () = {
+            fn __same_data<D: ?Sized, M: #mappable<Data: #krate::arena::SameHandleData<D>>>() {}
+            #[allow(dead_code)]
+            fn __check #impl_generics () #where_clause {
+                #(#checks)*
+            }
+        };
+        impl #impl_generics #mappable for #name #ty_generics #where_clause {
+            type Container<'__id> = #container;
+            type Data = #data_ty;
+
+            fn handles<'__id>(
+                target: &Self::Container<'__id>,
+                f: &mut impl FnMut(#krate::manager::VHandle<'__id, Self::Data>),
+            ) {
+                #(#handles)*
+            }
+            fn update<'__from, '__to>(
+                from: Self::Container<'__from>,
+                f: &mut impl FnMut(
+                    #krate::manager::VHandle<'__from, Self::Data>,
+                ) -> #krate::manager::VHandle<'__to, Self::Data>,
+            ) -> Self::Container<'__to> {
+                #name { #(#updates),* }
+            }
+        }
+    })
+}
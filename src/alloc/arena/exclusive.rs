@@ -2,6 +2,8 @@ use std::cell::UnsafeCell;
 
 use generativity::Guard;
 use parking_lot::Mutex;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 use super::*;
 use crate::alloc::{manager::*, store::*};
@@ -122,6 +124,29 @@ where
         manager!(mut self).get_disjoint_mut(handles)
     }
 }
+#[cfg(feature = "rayon")]
+impl<'id, T, const REUSE: bool, V> Arena<'id, 'id, Typed<T>, Exclusive<REUSE, V>>
+where
+    GlobalConfig<Typed<T>, Exclusive<REUSE, V>>: for<'x> Config<
+            Store: Store<T> + ParIter<T>,
+            Manager<'x> = XManager<'x, Typed<T>, Exclusive<REUSE, V>>,
+            Arena<'x, 'x> = XArena<'x, Typed<T>, Exclusive<REUSE, V>>,
+        >,
+{
+    /// This needs exclusive access, because [`XHandle`]s allow mutation through a shared arena.
+    pub fn par_iter(&mut self) -> impl ParallelIterator<Item = &T>
+    where
+        T: Sync,
+    {
+        self.0.manager.get_mut().par_iter()
+    }
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = &mut T>
+    where
+        T: Send,
+    {
+        self.0.manager.get_mut().par_iter_mut()
+    }
+}
 impl<'id, T, V> Arena<'id, 'id, Typed<T>, Exclusive<true, V>>
 where
     GlobalConfig<Typed<T>, Exclusive<true, V>>: for<'x> Config<
@@ -135,13 +160,14 @@ where
         manager!(mut self).remove(handle).map_err(|(handle, err)| (handle, err.into()))
     }
 }
-impl<'id, C, const REUSE: bool, V> Arena<'id, 'id, SoA<C>, Exclusive<REUSE, V>>
+impl<'id, C, L, const REUSE: bool, V> Arena<'id, 'id, SoA<C, L>, Exclusive<REUSE, V>>
 where
     C: Columns,
-    GlobalConfig<SoA<C>, Exclusive<REUSE, V>>: for<'x, 'a> Config<
-            Store: SoAStore<C, &'a XHandle<'x, C>, &'a mut XHandle<'x, C>>,
-            Manager<'x> = XManager<'x, SoA<C>, Exclusive<REUSE, V>>,
-            Arena<'x, 'x> = XArena<'x, SoA<C>, Exclusive<REUSE, V>>,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Exclusive<REUSE, V>>: for<'x, 'a> Config<
+            Store: SoAStore<C, &'a XHandle<'x, C>> + SoAStore<C, &'a mut XHandle<'x, C>>,
+            Manager<'x> = XManager<'x, SoA<C, L>, Exclusive<REUSE, V>>,
+            Arena<'x, 'x> = XArena<'x, SoA<C, L>, Exclusive<REUSE, V>>,
         >,
 {
     pub fn view(&self) -> C::Ref<'_, &'_ XHandle<'id, C>> {
@@ -170,13 +196,15 @@ where
         }
     }
 }
-impl<'id, C, V> Arena<'id, 'id, SoA<C>, Exclusive<true, V>>
+impl<'id, C, L, V> Arena<'id, 'id, SoA<C, L>, Exclusive<true, V>>
 where
     C: Columns,
-    GlobalConfig<SoA<C>, Exclusive<true, V>>: for<'a, 'x> Config<
-            Store: ReusableSoAStore<C, &'a XHandle<'x, C>, &'a mut XHandle<'x, C>>,
-            Manager<'x> = XManager<'x, SoA<C>, Exclusive<true, V>>,
-            Arena<'x, 'x> = XArena<'x, SoA<C>, Exclusive<true, V>>,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Exclusive<true, V>>: for<'a, 'x> Config<
+            Store: ReusableSoAStore<C, &'a XHandle<'x, C>>
+                       + ReusableSoAStore<C, &'a mut XHandle<'x, C>>,
+            Manager<'x> = XManager<'x, SoA<C, L>, Exclusive<true, V>>,
+            Arena<'x, 'x> = XArena<'x, SoA<C, L>, Exclusive<true, V>>,
         >,
 {
     pub fn remove(&self, handle: XHandle<'id, C>) -> Result<C, (XHandle<'id, C>, ArenaError)> {
@@ -0,0 +1,400 @@
+use std::{iter::once, vec};
+
+use super::*;
+use crate::alloc::store::*;
+
+/// Handles of a batch migration that stopped early.
+#[derive(Debug)]
+pub struct PartialMove<T, F> {
+    /// Handles of the elements that were moved, in order.
+    pub moved:     Vec<T>,
+    /// Handles of the elements that are still in the source arena, in order.
+    pub remaining: Vec<F>,
+    pub error:     ArenaError,
+}
+pub type MoveResult<T, F> = Result<Vec<T>, PartialMove<T, F>>;
+
+fn stop<T, F>(
+    moved: Vec<T>,
+    handle: F,
+    rest: vec::IntoIter<F>,
+    error: ArenaError,
+) -> PartialMove<T, F> {
+    PartialMove { moved, remaining: once(handle).chain(rest).collect(), error }
+}
+/// Moves every element of `handles` from one arena to another,
+/// both are accessed through `state` by the other arguments.
+///
+/// Elements are removed and inserted one at a time.
+/// When an insert fails, space for the element and all remaining ones is reserved at once,
+/// `reserve` gets their count.
+/// If that reservation fails, the element is put back into its source with `restore`.
+fn migrate_each<S, F, D, T>(
+    state: &mut S,
+    handles: Vec<F>,
+    remove: impl Fn(&mut S, F) -> Result<D, (F, ArenaError)>,
+    insert: impl Fn(&mut S, D) -> Result<T, D>,
+    reserve: impl Fn(&mut S, Length) -> AResult<()>,
+    restore: impl Fn(&mut S, D) -> F,
+) -> MoveResult<T, F> {
+    let mut moved = Vec::with_capacity(handles.len());
+    let mut handles = handles.into_iter();
+    while let Some(handle) = handles.next() {
+        let data = match remove(state, handle) {
+            Ok(data) => data,
+            Err((handle, error)) => return Err(stop(moved, handle, handles, error)),
+        };
+        let data = match insert(state, data) {
+            Ok(handle) => {
+                moved.push(handle);
+                continue;
+            },
+            Err(data) => data,
+        };
+        let count = (handles.len() as Length).saturating_add(1);
+        if let Err(error) = reserve(state, count) {
+            let handle = restore(state, data);
+            return Err(stop(moved, handle, handles, error));
+        }
+        let Ok(handle) = insert(state, data) else {
+            unreachable!("insert after reserve should always be successful")
+        };
+        moved.push(handle);
+    }
+    Ok(moved)
+}
+
+macro_rules! restored {
+    ($insert:expr) => {
+        $insert.unwrap_or_else(|_| unreachable!("removed elements always fit into their old place"))
+    };
+}
+/// Migration of a single element through `migrate_all`.
+fn migrate_one<T, F>(result: MoveResult<T, F>) -> Result<T, (F, ArenaError)> {
+    match result {
+        Ok(mut moved) => Ok(moved.pop().expect("one element was moved")),
+        Err(mut partial) =>
+            Err((partial.remaining.pop().expect("one element remains"), partial.error)),
+    }
+}
+
+impl<'id, T, V> Arena<'id, 'id, Typed<T>, Exclusive<true, V>>
+where
+    GlobalConfig<Typed<T>, Exclusive<true, V>>: for<'x> Config<
+            Store: ReusableStore<T>,
+            Manager<'x> = XManager<'x, Typed<T>, Exclusive<true, V>>,
+            Arena<'x, 'x> = XArena<'x, Typed<T>, Exclusive<true, V>>,
+        >,
+{
+    /// Moves the element of `handle` into `to`, which can belong to a different manager.
+    /// On failure the element stays in this arena, under the returned handle.
+    pub fn migrate<'to>(
+        &self,
+        to: &mut Arena<'to, 'to, Typed<T>, Exclusive<true, V>>,
+        handle: XHandle<'id, T>,
+    ) -> Result<XHandle<'to, T>, (XHandle<'id, T>, ArenaError)> {
+        migrate_one(self.migrate_all(to, vec![handle]))
+    }
+    /// Moves the elements of `handles` into `to` in order, the new handles are returned in the same order.
+    pub fn migrate_all<'to>(
+        &self,
+        to: &mut Arena<'to, 'to, Typed<T>, Exclusive<true, V>>,
+        handles: Vec<XHandle<'id, T>>,
+    ) -> MoveResult<XHandle<'to, T>, XHandle<'id, T>> {
+        migrate_each(
+            &mut (self, to),
+            handles,
+            |(from, _), handle| from.remove(handle),
+            |(_, to), data| to.insert_within_capacity(data),
+            |(_, to), units| to.reserve(units),
+            |(from, _), data| restored!(from.insert_within_capacity(data)),
+        )
+    }
+}
+impl<'id, C, L, V> Arena<'id, 'id, SoA<C, L>, Exclusive<true, V>>
+where
+    C: Columns,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Exclusive<true, V>>: for<'a, 'x> Config<
+            Store: ReusableSoAStore<C, &'a XHandle<'x, C>>
+                       + ReusableSoAStore<C, &'a mut XHandle<'x, C>>,
+            Manager<'x> = XManager<'x, SoA<C, L>, Exclusive<true, V>>,
+            Arena<'x, 'x> = XArena<'x, SoA<C, L>, Exclusive<true, V>>,
+        >,
+{
+    /// Moves the row of `handle` into `to`, which can belong to a different manager.
+    /// On failure the row stays in this arena, under the returned handle.
+    pub fn migrate<'to>(
+        &self,
+        to: &mut Arena<'to, 'to, SoA<C, L>, Exclusive<true, V>>,
+        handle: XHandle<'id, C>,
+    ) -> Result<XHandle<'to, C>, (XHandle<'id, C>, ArenaError)> {
+        migrate_one(self.migrate_all(to, vec![handle]))
+    }
+    /// Moves the rows of `handles` into `to` in order, the new handles are returned in the same order.
+    pub fn migrate_all<'to>(
+        &self,
+        to: &mut Arena<'to, 'to, SoA<C, L>, Exclusive<true, V>>,
+        handles: Vec<XHandle<'id, C>>,
+    ) -> MoveResult<XHandle<'to, C>, XHandle<'id, C>> {
+        migrate_each(
+            &mut (self, to),
+            handles,
+            |(from, _), handle| from.remove(handle),
+            |(_, to), data| to.insert_within_capacity(data),
+            |(_, to), units| to.reserve(units),
+            |(from, _), data| restored!(from.insert_within_capacity(data)),
+        )
+    }
+}
+
+impl<'id, 'man, T, H, V> VArenaAllocGuard<'_, 'id, 'man, Typed<T>, Versioned<true, H, V>, H>
+where
+    H: Header,
+    GlobalConfig<Typed<T>, Versioned<true, H, V>>: for<'x> Config<
+            Store: ReusableStore<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<true, H, V>>,
+        >,
+{
+    /// Moves the element of `handle` into the arena of `to`, which can belong to a different manager.
+    /// On failure the element stays in this arena, under the returned handle.
+    pub fn migrate<'to>(
+        &mut self,
+        to: &mut VArenaAllocGuard<'_, 'to, '_, Typed<T>, Versioned<true, H, V>, H>,
+        handle: VHandle<'id, T>,
+    ) -> Result<VHandle<'to, T>, (VHandle<'id, T>, ArenaError)> {
+        migrate_one(self.migrate_all(to, vec![handle]))
+    }
+    /// Moves the elements of `handles` into the arena of `to` in order,
+    /// the new handles are returned in the same order.
+    pub fn migrate_all<'to>(
+        &mut self,
+        to: &mut VArenaAllocGuard<'_, 'to, '_, Typed<T>, Versioned<true, H, V>, H>,
+        handles: Vec<VHandle<'id, T>>,
+    ) -> MoveResult<VHandle<'to, T>, VHandle<'id, T>> {
+        migrate_each(
+            &mut (self, to),
+            handles,
+            |(from, _), handle| from.remove(handle).map_err(|err| (handle, err)),
+            |(_, to), data| to.insert_within_capacity(data),
+            |(_, to), units| to.reserve(units),
+            |(from, _), data| restored!(from.insert_within_capacity(data)),
+        )
+    }
+}
+impl<'id, 'man, C, L, H, V> VArenaAllocGuard<'_, 'id, 'man, SoA<C, L>, Versioned<true, H, V>, H>
+where
+    C: Columns,
+    L: SoALayout,
+    H: Header,
+    GlobalConfig<SoA<C, L>, Versioned<true, H, V>>: for<'x> Config<
+            Store: ReusableSoAStore<Prefix<Version, C>, VHandle<'x, C>>,
+            Manager<'x> = VManager<'x, SoA<C, L>, Versioned<true, H, V>>,
+        >,
+{
+    /// Moves the row of `handle` into the arena of `to`, which can belong to a different manager.
+    /// On failure the row stays in this arena, under the returned handle.
+    pub fn migrate<'to>(
+        &mut self,
+        to: &mut VArenaAllocGuard<'_, 'to, '_, SoA<C, L>, Versioned<true, H, V>, H>,
+        handle: VHandle<'id, C>,
+    ) -> Result<VHandle<'to, C>, (VHandle<'id, C>, ArenaError)> {
+        migrate_one(self.migrate_all(to, vec![handle]))
+    }
+    /// Moves the rows of `handles` into the arena of `to` in order,
+    /// the new handles are returned in the same order.
+    pub fn migrate_all<'to>(
+        &mut self,
+        to: &mut VArenaAllocGuard<'_, 'to, '_, SoA<C, L>, Versioned<true, H, V>, H>,
+        handles: Vec<VHandle<'id, C>>,
+    ) -> MoveResult<VHandle<'to, C>, VHandle<'id, C>> {
+        migrate_each(
+            &mut (self, to),
+            handles,
+            |(from, _), handle| from.remove(handle).map_err(|err| (handle, err)),
+            |(_, to), data| to.insert_within_capacity(data),
+            |(_, to), units| to.reserve(units),
+            |(from, _), data| restored!(from.insert_within_capacity(data)),
+        )
+    }
+}
+
+impl<'id, U, V> Arena<'id, 'id, Slices<U>, Exclusive<true, V>>
+where
+    U: RawBytes,
+    GlobalConfig<Slices<U>, Exclusive<true, V>>: for<'x> Config<
+            Store: ReusableMultiStore<U>,
+            Manager<'x> = XManager<'x, Slices<U>, Exclusive<true, V>>,
+            Arena<'x, 'x> = XArena<'x, Slices<U>, Exclusive<true, V>>,
+        >,
+{
+    /// Moves the slice of `handle` into `to`, which can belong to a different manager.
+    /// On failure the slice stays in this arena, under the returned handle.
+    #[expect(clippy::type_complexity)]
+    pub fn migrate<'to, T: Copy>(
+        &self,
+        to: &mut Arena<'to, 'to, Slices<U>, Exclusive<true, V>>,
+        handle: XHandle<'id, [T]>,
+    ) -> Result<XHandle<'to, [T]>, (XHandle<'id, [T]>, ArenaError)> {
+        migrate_one(self.migrate_all(to, vec![handle]))
+    }
+    /// Moves the slices of `handles` into `to` in order, the new handles are returned in the same order.
+    ///
+    /// Slices are copied before they are removed here, so a failed insert leaves them in place.
+    pub fn migrate_all<'to, T: Copy>(
+        &self,
+        to: &mut Arena<'to, 'to, Slices<U>, Exclusive<true, V>>,
+        handles: Vec<XHandle<'id, [T]>>,
+    ) -> MoveResult<XHandle<'to, [T]>, XHandle<'id, [T]>> {
+        let mut moved = Vec::with_capacity(handles.len());
+        let mut handles = handles.into_iter();
+        while let Some(handle) = handles.next() {
+            match self.get(&handle).and_then(|data| to.insert(data)) {
+                Ok(copy) => moved.push(copy),
+                Err(error) => return Err(stop(moved, handle, handles, error)),
+            }
+            let Ok(_) = self.remove(handle) else {
+                unreachable!("copied slices can always be removed")
+            };
+        }
+        Ok(moved)
+    }
+}
+impl<'id, U, V> Arena<'id, 'id, Mixed<U>, Exclusive<true, V>>
+where
+    U: RawBytes,
+    GlobalConfig<Mixed<U>, Exclusive<true, V>>: for<'x> Config<
+            Store: ReusableMultiStore<U>,
+            Manager<'x> = XManager<'x, Mixed<U>, Exclusive<true, V>>,
+            Arena<'x, 'x> = XArena<'x, Mixed<U>, Exclusive<true, V>>,
+        >,
+{
+    /// Moves the element of `handle` into `to`, which can belong to a different manager.
+    /// On failure the element stays in this arena, under the returned handle.
+    pub fn migrate<'to, T>(
+        &self,
+        to: &mut Arena<'to, 'to, Mixed<U>, Exclusive<true, V>>,
+        handle: XHandle<'id, T>,
+    ) -> Result<XHandle<'to, T>, (XHandle<'id, T>, ArenaError)> {
+        migrate_one(self.migrate_all(to, vec![handle]))
+    }
+    /// Moves the elements of `handles` into `to` in order, the new handles are returned in the same order.
+    pub fn migrate_all<'to, T>(
+        &self,
+        to: &mut Arena<'to, 'to, Mixed<U>, Exclusive<true, V>>,
+        handles: Vec<XHandle<'id, T>>,
+    ) -> MoveResult<XHandle<'to, T>, XHandle<'id, T>> {
+        migrate_each(
+            &mut (self, to),
+            handles,
+            |(from, _), handle| from.remove(handle),
+            |(_, to), data| to.insert_within_capacity(data),
+            |(_, to), count| to.reserve(count.saturating_mul(Mixed::<U>::size_of::<T>())),
+            |(from, _), data| restored!(from.insert_within_capacity(data)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+
+    #[test]
+    fn migrates_between_exclusive_arenas() {
+        make_guard!(a);
+        make_guard!(b);
+        let mut a = Arena::<Typed<u32>, Exclusive<true>>::new(a);
+        let mut b = Arena::<Typed<u32>, Exclusive<true>>::new(b);
+        let handles = (0..5).map(|i| a.insert(i).unwrap_or_else(|_| panic!())).collect();
+        let Ok(moved) = a.migrate_all(&mut b, handles) else { panic!("all elements should move") };
+        let values: Vec<_> = moved.iter().map(|handle| *b.get(handle).unwrap()).collect();
+        assert_eq!(vec![0, 1, 2, 3, 4], values);
+    }
+
+    #[test]
+    fn stops_at_stale_versioned_handles() {
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(man_a);
+        make_guard!(man_b);
+        let mut a = Arena::<Typed<u32>, Versioned<true>>::new(a, man_a, Headless::default());
+        let mut b = Arena::<Typed<u32>, Versioned<true>>::new(b, man_b, Headless::default());
+        let (mut from, mut to) = (a.alloc(), b.alloc());
+        let first = from.insert(1).unwrap();
+        let second = from.insert(2).unwrap();
+        from.remove(first).unwrap();
+        let Err(partial) = from.migrate_all(&mut to, vec![second, first]) else {
+            panic!("stale handle should stop the move")
+        };
+        assert_eq!(vec![first], partial.remaining);
+        assert_eq!(Ok(&2), to.get(partial.moved[0]));
+        assert!(from.get(second).is_err());
+    }
+
+    #[test]
+    fn migrates_soa_rows_between_exclusive_arenas() {
+        make_guard!(a);
+        make_guard!(b);
+        let mut a = Arena::<SoA<(u32, f32)>, Exclusive<true>>::new(a);
+        let mut b = Arena::<SoA<(u32, f32)>, Exclusive<true>>::new(b);
+        let handles =
+            (0..3).map(|i| a.insert((i, i as f32)).unwrap_or_else(|_| panic!())).collect();
+        let Ok(moved) = a.migrate_all(&mut b, handles) else { panic!("all rows should move") };
+        let values: Vec<_> = moved.iter().map(|handle| *b.view().col1(handle).unwrap()).collect();
+        assert_eq!(vec![0.0, 1.0, 2.0], values);
+        let handle = b.migrate(&mut a, moved.into_iter().next().unwrap()).unwrap();
+        assert_eq!(Ok(&0), a.view().col0(&handle));
+    }
+
+    #[test]
+    fn migrates_soa_rows_between_versioned_arenas() {
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(man_a);
+        make_guard!(man_b);
+        let mut a = Arena::<SoA<(u32, f32)>, Versioned<true>>::new(a, man_a, Headless::default());
+        let mut b = Arena::<SoA<(u32, f32)>, Versioned<true>>::new(b, man_b, Headless::default());
+        let (mut from, mut to) = (a.alloc(), b.alloc());
+        let first = from.insert((1, 1.0)).unwrap();
+        let second = from.insert((2, 2.0)).unwrap();
+        let moved = from.migrate(&mut to, second).unwrap();
+        assert_eq!(Ok(&2), to.view().col0(moved));
+        assert!(from.view().col0(second).is_err(), "moved rows should be removed");
+        from.remove(first).unwrap();
+        let Err((stale, _)) = from.migrate(&mut to, first) else {
+            panic!("stale handle should not move")
+        };
+        assert_eq!(first, stale);
+    }
+
+    #[test]
+    fn migrates_slices_between_exclusive_arenas() {
+        make_guard!(a);
+        make_guard!(b);
+        let mut a = Arena::<Slices<u32>, Exclusive<true>>::new(a);
+        let mut b = Arena::<Slices<u32>, Exclusive<true>>::new(b);
+        let handles = vec![a.insert(&[1u16, 2, 3]).unwrap(), a.insert(&[4u16]).unwrap()];
+        let Ok(moved) = a.migrate_all(&mut b, handles) else { panic!("all slices should move") };
+        assert_eq!(Ok(&[1, 2, 3][..]), b.get(&moved[0]));
+        assert_eq!(Ok(&[4][..]), b.get(&moved[1]));
+        let reused =
+            a.insert_within_capacity(&[5u16, 6, 7]).expect("moved slices should free their space");
+        assert_eq!(Ok(&[5, 6, 7][..]), a.get(&reused));
+    }
+
+    #[test]
+    fn migrates_mixed_between_exclusive_arenas() {
+        make_guard!(a);
+        make_guard!(b);
+        let mut a = Arena::<Mixed<u64>, Exclusive<true>>::new(a);
+        let mut b = Arena::<Mixed<u64>, Exclusive<true>>::new(b);
+        let handles = (0..4).map(|i| a.insert((i, i as u8)).unwrap_or_else(|_| panic!())).collect();
+        let Ok(moved) = a.migrate_all(&mut b, handles) else { panic!("all entries should move") };
+        let values: Vec<_> = moved.iter().map(|handle| *b.get(handle).unwrap()).collect();
+        assert_eq!(vec![(0, 0), (1, 1), (2, 2), (3, 3)], values);
+        let handle = b.migrate(&mut a, moved.into_iter().next().unwrap()).unwrap();
+        assert_eq!(Ok(&(0, 0)), a.get(&handle));
+    }
+}
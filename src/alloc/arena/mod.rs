@@ -1,6 +1,9 @@
 mod exclusive;
 pub use exclusive::*;
 
+mod migrate;
+pub use migrate::*;
+
 mod version;
 use thiserror::Error;
 pub use version::*;
@@ -11,6 +14,12 @@ use super::{manager::*, *};
 pub enum ArenaError {
     #[error("manager error: {0}")]
     ManagerError(#[from] ManagerError),
+    #[error("cannot join ports: {0}")]
+    BadJoin(&'static str),
+    #[error("handle belongs to shard {0}")]
+    WrongShard(usize),
+    #[error("manager is shared with other ports")]
+    SharedManager,
 }
 pub type AResult<T> = Result<T, ArenaError>;
 pub struct Arena<'id, 'man, K, C>(<GlobalConfig<K, C> as Config>::Arena<'id, 'man>)
@@ -0,0 +1,396 @@
+use std::{
+    fmt::{self, Debug},
+    future::poll_fn,
+    mem::{ManuallyDrop, take, transmute},
+    ops::{Deref, DerefMut},
+    task::{Context, Poll, Waker},
+};
+
+use parking_lot::Mutex;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::*;
+
+#[derive(Default)]
+struct LockState {
+    readers: usize,
+    writer:  bool,
+    /// Writers that wait for the lock, new readers are not admitted while there are any.
+    queued:  usize,
+    waiters: Vec<Waker>,
+}
+
+/// Read-write lock that parks tasks instead of threads.
+///
+/// Every release wakes all waiting tasks, which then race for the lock again.
+/// Waiting writers keep new readers out, so readers can't starve them,
+/// which means a task must not wait for a second read guard of a lock it already reads.
+pub(super) struct AsyncLock<T> {
+    state: Mutex<LockState>,
+    value: UnsafeCell<T>,
+}
+// SAFETY: the same as for `RwLock`
+unsafe impl<T: Send> Send for AsyncLock<T> {}
+// SAFETY: the same as for `RwLock`
+unsafe impl<T: Send + Sync> Sync for AsyncLock<T> {}
+impl<T> AsyncLock<T> {
+    fn new(value: T) -> Self {
+        Self { state: Mutex::default(), value: UnsafeCell::new(value) }
+    }
+    fn poll_acquire(
+        &self,
+        cx: &mut Context<'_>,
+        acquire: impl FnOnce(&mut LockState) -> bool,
+    ) -> Poll<()> {
+        let mut state = self.state.lock();
+        if acquire(&mut state) {
+            return Poll::Ready(());
+        }
+        if !state.waiters.iter().any(|waiter| waiter.will_wake(cx.waker())) {
+            state.waiters.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+    fn release(&self, release: impl FnOnce(&mut LockState)) {
+        let waiters = {
+            let mut state = self.state.lock();
+            release(&mut state);
+            take(&mut state.waiters)
+        };
+        waiters.into_iter().for_each(Waker::wake);
+    }
+    async fn read(&self) -> AsyncReadGuard<'_, T> {
+        poll_fn(|cx| {
+            self.poll_acquire(cx, |state| {
+                let free = !state.writer && state.queued == 0;
+                state.readers += free as usize;
+                free
+            })
+        })
+        .await;
+        AsyncReadGuard { lock: self }
+    }
+    async fn write(&self) -> AsyncWriteGuard<'_, T> {
+        let mut queue = WriterQueue { lock: self, queued: false };
+        poll_fn(|cx| {
+            self.poll_acquire(cx, |state| {
+                let free = !state.writer && state.readers == 0;
+                state.writer |= free;
+                // enter the queue on the first failed attempt, leave it once acquired
+                if free && queue.queued {
+                    state.queued -= 1;
+                } else if !free && !queue.queued {
+                    state.queued += 1;
+                }
+                queue.queued = !free;
+                free
+            })
+        })
+        .await;
+        AsyncWriteGuard { lock: self }
+    }
+}
+
+/// Removes a writer from the queue if its future is dropped while waiting.
+struct WriterQueue<'a, T> {
+    lock:   &'a AsyncLock<T>,
+    queued: bool,
+}
+impl<T> Drop for WriterQueue<'_, T> {
+    fn drop(&mut self) {
+        if self.queued {
+            // readers that were kept out by this writer have to be woken
+            self.lock.release(|state| state.queued -= 1);
+        }
+    }
+}
+
+pub struct AsyncReadGuard<'a, T> {
+    lock: &'a AsyncLock<T>,
+}
+impl<T> Deref for AsyncReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: there is no writer while this guard exists
+        unsafe { &*self.lock.value.get() }
+    }
+}
+impl<T> Drop for AsyncReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.release(|state| state.readers -= 1);
+    }
+}
+impl<T: Debug> Debug for AsyncReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+pub struct AsyncWriteGuard<'a, T> {
+    lock: &'a AsyncLock<T>,
+}
+impl<'a, T> AsyncWriteGuard<'a, T> {
+    /// Calls `f` with exclusive access, which this guard already has.
+    pub fn with_upgraded<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(self)
+    }
+    pub fn downgrade(s: Self) -> AsyncReadGuard<'a, T> {
+        let lock = ManuallyDrop::new(s).lock;
+        lock.release(|state| {
+            state.writer = false;
+            state.readers += 1;
+        });
+        AsyncReadGuard { lock }
+    }
+}
+impl<T> Deref for AsyncWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: this is the only guard of the lock
+        unsafe { &*self.lock.value.get() }
+    }
+}
+impl<T> DerefMut for AsyncWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: this is the only guard of the lock
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+impl<T> Drop for AsyncWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.release(|state| state.writer = false);
+    }
+}
+impl<T: Debug> Debug for AsyncWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+type ArcAsyncLock<T> = Arc<AsyncLock<T>>;
+
+/// [`VArena`] whose guards are acquired by awaiting, which parks the task instead of the thread.
+///
+/// This does not depend on any runtime, tasks are resumed through their [`Waker`].
+/// The guards have the same API as the guards of [`VArena`].
+///
+/// Like [`VArena`] the arena and its guards are [`Send`] and [`Sync`] if the manager is,
+/// so the futures can be run by multithreaded executors.
+pub struct AsyncArena<'id, 'man, K, C, H: Header = Headless>
+where
+    GlobalConfig<K, C>: Config,
+{
+    manager: ArcAsyncLock<ManagerCell<'man, K, C>>,
+    port:    ArcAsyncLock<(Id<'id>, H)>,
+}
+impl<'id, 'man, K, const REUSE: bool, H, V> AsyncArena<'id, 'man, K, Versioned<REUSE, H, V>, H>
+where
+    H: Header,
+    V: Tracking,
+    GlobalConfig<K, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: Default + Resizable,
+            Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>,
+        >,
+{
+    pub fn new(guard: Guard<'id>, manager_guard: Guard<'man>, header: H) -> Self {
+        Self::with_manager(guard, Manager::new(manager_guard), header)
+    }
+}
+impl<'id, 'man, K, const REUSE: bool, H, V> AsyncArena<'id, 'man, K, Versioned<REUSE, H, V>, H>
+where
+    H: Header,
+    GlobalConfig<K, Versioned<REUSE, H, V>>:
+        for<'x> Config<Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
+{
+    /// Creates an arena around an empty manager, see [`Arena::with_manager`].
+    pub fn with_manager(
+        guard: Guard<'id>,
+        manager: Manager<'man, K, Versioned<REUSE, H, V>>,
+        header: H,
+    ) -> Self {
+        Self {
+            manager: Arc::new(AsyncLock::new(ManagerCell::new(manager))),
+            port:    Arc::new(AsyncLock::new((guard.into(), header))),
+        }
+    }
+    pub fn split<'new>(
+        &self,
+        guard: Guard<'new>,
+    ) -> AsyncArena<'new, 'man, K, Versioned<REUSE, H, V>, H> {
+        AsyncArena {
+            manager: self.manager.clone(),
+            port:    Arc::new(AsyncLock::new((guard.into(), H::default()))),
+        }
+    }
+    /// Merges the port of `other` into the port of `self`, see [`Arena::join`].
+    pub async fn join<'other>(
+        &self,
+        other: AsyncArena<'other, 'man, K, Versioned<REUSE, H, V>, H>,
+    ) -> Result<
+        HandleMap<'other, 'id>,
+        (AsyncArena<'other, 'man, K, Versioned<REUSE, H, V>, H>, ArenaError),
+    > {
+        let (this, port) = (Arc::as_ptr(&self.port), Arc::as_ptr(&other.port));
+        if let Err(err) = check_join(this, port, Arc::strong_count(&other.port)) {
+            return Err((other, err));
+        }
+        Ok(merge_ports(&mut *self.port.write().await, &mut *other.port.write().await))
+    }
+    pub async fn read(&self) -> AsyncArenaReadGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        AsyncArenaReadGuard { manager: self.manager.read().await, port: self.port.read().await }
+    }
+    pub async fn write(
+        &mut self,
+    ) -> AsyncArenaWriteGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        AsyncArenaWriteGuard {
+            manager: self.manager.read().await,
+            port:    self.port.write().await,
+        }
+    }
+    /// Unlike [`Arena::alloc`] this locks the manager exclusively,
+    /// because growing it could not wait for readers of other ports without blocking the thread.
+    pub async fn alloc(
+        &mut self,
+    ) -> AsyncArenaAllocGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        AsyncArenaAllocGuard {
+            manager: self.manager.write().await,
+            port:    self.port.write().await,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AsyncArenaReadGuard<'a, 'id, 'man, K, C, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    manager: AsyncReadGuard<'a, ManagerCell<'man, K, C>>,
+    port:    AsyncReadGuard<'a, (Id<'id>, H)>,
+}
+#[derive(Debug)]
+pub struct AsyncArenaWriteGuard<'a, 'id, 'man, K, C, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    manager: AsyncReadGuard<'a, ManagerCell<'man, K, C>>,
+    port:    AsyncWriteGuard<'a, (Id<'id>, H)>,
+}
+#[derive(Debug)]
+pub struct AsyncArenaAllocGuard<'a, 'id, 'man, K, C, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    manager: AsyncWriteGuard<'a, ManagerCell<'man, K, C>>,
+    port:    AsyncWriteGuard<'a, (Id<'id>, H)>,
+}
+impl_read!(AsyncArenaReadGuard<'_>);
+impl_read!(AsyncArenaWriteGuard<'_>);
+impl_read!(AsyncArenaAllocGuard<'_>);
+impl_write!(AsyncArenaWriteGuard<'_>);
+impl_write!(AsyncArenaAllocGuard<'_>);
+impl_alloc!(AsyncArenaAllocGuard<'_>);
+impl<'a, 'id, 'man, K, const REUSE: bool, H, V>
+    AsyncArenaAllocGuard<'a, 'id, 'man, K, Versioned<REUSE, H, V>, H>
+where
+    H: Header,
+    GlobalConfig<K, Versioned<REUSE, H, V>>:
+        for<'x> Config<Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
+{
+    pub fn downgrade(self) -> AsyncArenaWriteGuard<'a, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        AsyncArenaWriteGuard {
+            manager: AsyncWriteGuard::downgrade(self.manager),
+            port:    self.port,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        pin::pin,
+        sync::atomic::{AtomicUsize, Ordering},
+        task::Wake,
+    };
+
+    use generativity::make_guard;
+
+    use super::*;
+
+    struct CountWakes(AtomicUsize);
+    impl Wake for CountWakes {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn ready<F: Future>(future: F) -> F::Output {
+        let Poll::Ready(output) = pin!(future).poll(&mut Context::from_waker(Waker::noop())) else {
+            panic!("future should be ready")
+        };
+        output
+    }
+
+    #[test]
+    fn parks_tasks_until_the_lock_is_released() {
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(man);
+        let mut a = AsyncArena::<Typed<u32>, Versioned<true>>::new(a, man, Headless::default());
+        let b = a.split(b);
+        let mut alloc = ready(a.alloc());
+        let handle = alloc.insert(1).unwrap();
+
+        let wakes = Arc::new(CountWakes(AtomicUsize::new(0)));
+        let waker = Waker::from(wakes.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut read = pin!(b.read());
+        assert!(read.as_mut().poll(&mut cx).is_pending());
+        let write = alloc.downgrade();
+        assert_eq!(1, wakes.0.load(Ordering::Relaxed));
+        let Poll::Ready(_read) = read.as_mut().poll(&mut cx) else {
+            panic!("reading should not wait for writers of other ports")
+        };
+        assert_eq!(Ok(&1), write.get(handle));
+    }
+
+    #[test]
+    fn waiting_writers_keep_new_readers_out() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        type Kind = Typed<u32>;
+        type Config = Versioned<true>;
+        assert_send_sync::<AsyncArena<'static, 'static, Kind, Config>>();
+        assert_send_sync::<AsyncArenaAllocGuard<'static, 'static, 'static, Kind, Config, Headless>>(
+        );
+
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(c);
+        make_guard!(man);
+        let a = AsyncArena::<Kind, Config>::new(a, man, Headless::default());
+        let mut b = a.split(b);
+        let c = a.split(c);
+        let read = ready(a.read());
+
+        let wakes = Arc::new(CountWakes(AtomicUsize::new(0)));
+        let waker = Waker::from(wakes.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut alloc = pin!(b.alloc());
+        assert!(alloc.as_mut().poll(&mut cx).is_pending());
+        assert!(alloc.as_mut().poll(&mut cx).is_pending());
+        let mut late = pin!(c.read());
+        assert!(late.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(1, read.manager.lock.state.lock().waiters.len(), "wakers should not repeat");
+
+        drop(read);
+        assert_eq!(1, wakes.0.load(Ordering::Relaxed));
+        assert!(late.as_mut().poll(&mut cx).is_pending());
+        let Poll::Ready(alloc) = alloc.as_mut().poll(&mut cx) else {
+            panic!("the writer should get the lock once the readers are gone")
+        };
+        drop(alloc);
+        assert!(late.as_mut().poll(&mut cx).is_ready());
+    }
+}
@@ -1,6 +1,11 @@
-use std::mem::transmute;
+use std::{fmt::Debug, mem::transmute};
 
-use parking_lot::{RwLockReadGuard, RwLockUpgradableReadGuard, RwLockWriteGuard};
+use parking_lot::{
+    ArcRwLockReadGuard, ArcRwLockUpgradableReadGuard, ArcRwLockWriteGuard, RawRwLock,
+    RwLockReadGuard, RwLockUpgradableReadGuard, RwLockWriteGuard,
+};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 use super::*;
 
@@ -12,6 +17,15 @@ where
     pub(super) manager: RwLockReadGuard<'a, ManagerCell<'man, K, C>>,
     pub(super) port:    RwLockReadGuard<'a, (Id<'id>, H)>,
 }
+/// Read guard that locks the whole manager, see [`Arena::read_exclusive`].
+#[derive(Debug)]
+pub struct VArenaExclusiveReadGuard<'a, 'id, 'man, K, C, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    pub(super) manager: RwLockWriteGuard<'a, ManagerCell<'man, K, C>>,
+    pub(super) port:    RwLockReadGuard<'a, (Id<'id>, H)>,
+}
 #[derive(Debug)]
 pub struct VArenaWriteGuard<'a, 'id, 'man, K, C, H: Header>
 where
@@ -28,6 +42,40 @@ where
     pub(super) manager: RwLockUpgradableReadGuard<'a, ManagerCell<'man, K, C>>,
     pub(super) port:    RwLockWriteGuard<'a, (Id<'id>, H)>,
 }
+/// Owned version of [`VArenaReadGuard`], see [`Arena::read_arc`].
+#[derive(Debug)]
+pub struct ArcVArenaReadGuard<'id, 'man, K, C, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    pub(super) manager: ArcRwLockReadGuard<RawRwLock, ManagerCell<'man, K, C>>,
+    pub(super) port:    ArcRwLockReadGuard<RawRwLock, (Id<'id>, H)>,
+}
+/// Owned version of [`VArenaWriteGuard`], see [`Arena::write_arc`].
+#[derive(Debug)]
+pub struct ArcVArenaWriteGuard<'id, 'man, K, C, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    pub(super) manager: ArcRwLockReadGuard<RawRwLock, ManagerCell<'man, K, C>>,
+    pub(super) port:    ArcRwLockWriteGuard<RawRwLock, (Id<'id>, H)>,
+}
+/// Owned version of [`VArenaAllocGuard`], see [`Arena::alloc_arc`].
+pub struct ArcVArenaAllocGuard<'id, 'man, K, C, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    pub(super) manager: ArcRwLockUpgradableReadGuard<RawRwLock, ManagerCell<'man, K, C>>,
+    pub(super) port:    ArcRwLockWriteGuard<RawRwLock, (Id<'id>, H)>,
+}
+impl<K, C, H: Header + Debug> Debug for ArcVArenaAllocGuard<'_, '_, K, C, H>
+where
+    GlobalConfig<K, C>: Config,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArcVArenaAllocGuard").field("port", &*self.port).finish_non_exhaustive()
+    }
+}
 macro_rules! manager {
     (ref $this:ident) => {
         // SAFETY: manager always holds a valid value
@@ -44,9 +92,10 @@ macro_rules! manager {
         })
     };
 }
+pub(super) use manager;
 macro_rules! impl_read {
-    ($type:ident) => {
-        impl<'id, 'man, K, C, H> $type<'_, 'id, 'man, K, C, H>
+    ($type:ident $(<$lt:lifetime>)?) => {
+        impl<'id, 'man, K, C, H> $type<$($lt,)? 'id, 'man, K, C, H>
         where
             GlobalConfig<K, C>: Config,
             H: Header,
@@ -55,7 +104,7 @@ macro_rules! impl_read {
                 &self.port.1
             }
         }
-        impl<'id, 'man, T, const REUSE: bool, H, V> $type<'_, 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
+        impl<'id, 'man, T, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
         where
             H: Header,
             GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
@@ -67,13 +116,14 @@ macro_rules! impl_read {
                 Ok(manager!(ref self).get(map_handle!(handle<T> 'id -> 'man))?)
             }
         }
-        impl<'id, 'man, C, const REUSE: bool, H, V> $type<'_, 'id, 'man, SoA<C>, Versioned<REUSE, H, V>, H>
+        impl<'id, 'man, C, L, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, SoA<C, L>, Versioned<REUSE, H, V>, H>
         where
             C: Columns,
+            L: SoALayout,
             H: Header,
-            GlobalConfig<SoA<C>, Versioned<REUSE, H, V>>: for<'x> Config<
+            GlobalConfig<SoA<C, L>, Versioned<REUSE, H, V>>: for<'x> Config<
                     Store: SoAStore<Prefix<Version, C>, VHandle<'x, C>>,
-                    Manager<'x> = VManager<'x, SoA<C>, Versioned<REUSE, H, V>>,
+                    Manager<'x> = VManager<'x, SoA<C, L>, Versioned<REUSE, H, V>>,
                 >,
         {
             pub fn view(&self) -> C::Ref<'_, VHandle<'id, C>> {
@@ -82,7 +132,47 @@ macro_rules! impl_read {
                 unsafe { transmute::<C::Ref<'_, VHandle<'man, C>>, C::Ref<'_, VHandle<'id, C>>>(view) }
             }
         }
-        impl<'id, 'man, U, const REUSE: bool, H, V> $type<'_, 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, H>
+        #[cfg(feature = "rayon")]
+        impl<'id, 'man, T, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
+        where
+            T: Sync,
+            H: Header,
+            GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: Store<(Version, T)> + Sync,
+                    Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            /// Looks up all `handles` from a thread pool.
+            ///
+            /// Only handles of this port can be visited,
+            /// because other ports might write to their entries at the same time.
+            pub fn par_get<'a>(
+                &'a self,
+                handles: &'a [VHandle<'id, T>],
+            ) -> impl IndexedParallelIterator<Item = AResult<&'a T>> {
+                let manager = manager!(ref self);
+                handles.par_iter().map(move |&handle| {
+                    Ok(manager.get(map_handle!(handle<T> 'id -> 'man))?)
+                })
+            }
+        }
+        #[cfg(feature = "arrow")]
+        impl<'id, 'man, C, L, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, SoA<C, L>, Versioned<REUSE, H, V>, H>
+        where
+            C: ArrowColumns,
+            L: SoALayout,
+            H: Header,
+            GlobalConfig<SoA<C, L>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: SoAStore<Prefix<Version, C>, VHandle<'x, C>>,
+                    Manager<'x> = VManager<'x, SoA<C, L>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            /// Exports all columns of the shared manager, see [`Manager::export_arrow`].
+            pub fn export_arrow(&self, names: &[&str]) -> AResult<ArrowExport<'_>> {
+                Ok(manager!(ref self).export_arrow(names)?)
+            }
+        }
+        impl<'id, 'man, U, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, H>
         where
             U: RawBytes,
             H: Header,
@@ -95,7 +185,7 @@ macro_rules! impl_read {
                 Ok(manager!(ref self).get(map_handle!(handle<[T]> 'id -> 'man))?)
             }
         }
-        impl<'id, 'man, U, const REUSE: bool, H, V> $type<'_, 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, H>
+        impl<'id, 'man, U, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, H>
         where
             U: RawBytes,
             H: Header,
@@ -110,12 +200,37 @@ macro_rules! impl_read {
         }
     };
 }
-impl_read!(VArenaReadGuard);
-impl_read!(VArenaWriteGuard);
-impl_read!(VArenaAllocGuard);
+pub(super) use impl_read;
+impl_read!(VArenaReadGuard<'_>);
+impl_read!(VArenaExclusiveReadGuard<'_>);
+impl_read!(VArenaWriteGuard<'_>);
+impl_read!(VArenaAllocGuard<'_>);
+impl_read!(ArcVArenaReadGuard);
+impl_read!(ArcVArenaWriteGuard);
+impl_read!(ArcVArenaAllocGuard);
+#[cfg(feature = "rayon")]
+impl<'id, 'man, T, const REUSE: bool, H, V>
+    VArenaExclusiveReadGuard<'_, 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
+where
+    H: Header,
+    GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: Store<(Version, T)> + ParIter<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
+        >,
+{
+    /// Visits every occupied entry from a thread pool, including entries of other ports.
+    ///
+    /// Only values are returned, handles of other ports must not be used by this port.
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = &T>
+    where
+        T: Sync,
+    {
+        manager!(ref self).par_iter().map(|(_, data)| data)
+    }
+}
 macro_rules! impl_write {
-    ($type:ident) => {
-        impl<'id, 'man, K, C, H> $type<'_, 'id, 'man, K, C, H>
+    ($type:ident $(<$lt:lifetime>)?) => {
+        impl<'id, 'man, K, C, H> $type<$($lt,)? 'id, 'man, K, C, H>
         where
             GlobalConfig<K, C>: Config,
             H: Header,
@@ -124,7 +239,7 @@ macro_rules! impl_write {
                 &mut self.port.1
             }
         }
-        impl<'id, 'man, T, const REUSE: bool, H, V> $type<'_, 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
+        impl<'id, 'man, T, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
         where
             H: Header,
             GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
@@ -143,12 +258,18 @@ macro_rules! impl_write {
             where
                 M: MappableHandle<Data = T>
             {
-                let handle = M::handle(&target);
-                let handle = manager!(mut self).bump_version(map_handle!(handle<T> 'id -> 'man))?;
-                Ok(M::update(target, map_handle!(handle<T> 'man -> 'to)))
+                move_handles::<M, _>(
+                    manager!(mut self),
+                    target,
+                    |manager, handle| manager.get(map_handle!(handle<T> 'id -> 'man)).map(drop),
+                    |manager, handle| {
+                        let handle = manager.bump_version(map_handle!(handle<T> 'id -> 'man))?;
+                        Ok(map_handle!(handle<T> 'man -> 'id))
+                    },
+                )
             }
         }
-        impl<'id, 'man, T, const REUSE: bool, H, V> $type<'_, 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
+        impl<'id, 'man, T, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
         where
             H: Header,
             GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
@@ -164,13 +285,14 @@ macro_rules! impl_write {
                     .get_disjoint_mut(handles.map(|handle| map_handle!(handle<T> 'id -> 'man)))?)
             }
         }
-        impl<'id, 'man, C, const REUSE: bool, H, V> $type<'_, 'id, 'man, SoA<C>, Versioned<REUSE, H, V>, H>
+        impl<'id, 'man, C, L, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, SoA<C, L>, Versioned<REUSE, H, V>, H>
         where
             C: Columns,
+            L: SoALayout,
             H: Header,
-            GlobalConfig<SoA<C>, Versioned<REUSE, H, V>>: for<'x> Config<
+            GlobalConfig<SoA<C, L>, Versioned<REUSE, H, V>>: for<'x> Config<
                     Store: SoAStore<Prefix<Version, C>, VHandle<'x, C>>,
-                    Manager<'x> = VManager<'x, SoA<C>, Versioned<REUSE, H, V>>,
+                    Manager<'x> = VManager<'x, SoA<C, L>, Versioned<REUSE, H, V>>,
                 >,
         {
             pub fn view_mut(&mut self) -> C::Mut<'_, VHandle<'id, C>> {
@@ -180,18 +302,24 @@ macro_rules! impl_write {
             }
             pub fn move_to<'to, M>(
                 &mut self,
-                _to: &mut Arena<'to, 'man, SoA<C>, Versioned<REUSE, H, V>>,
+                _to: &mut Arena<'to, 'man, SoA<C, L>, Versioned<REUSE, H, V>>,
                 target: M::Container<'id>
             ) -> AResult<M::Container<'to>>
             where
                 M: MappableHandle<Data = C>
             {
-                let handle = M::handle(&target);
-                let handle = manager!(mut self).bump_version(map_handle!(handle<C> 'id -> 'man))?;
-                Ok(M::update(target, map_handle!(handle<C> 'man -> 'to)))
+                move_handles::<M, _>(
+                    manager!(mut self),
+                    target,
+                    |manager, handle| manager.validate(&[map_handle!(handle<C> 'id -> 'man)]),
+                    |manager, handle| {
+                        let handle = manager.bump_version(map_handle!(handle<C> 'id -> 'man))?;
+                        Ok(map_handle!(handle<C> 'man -> 'id))
+                    },
+                )
             }
         }
-        impl<'id, 'man, U, const REUSE: bool, H, V> $type<'_, 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, H>
+        impl<'id, 'man, U, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, H>
         where
             U: RawBytes,
             H: Header,
@@ -211,12 +339,18 @@ macro_rules! impl_write {
             where
                 M: MappableHandle<Data = [T]>
             {
-                let handle = M::handle(&target);
-                let handle = manager!(mut self).bump_version(map_handle!(handle<[T]> 'id -> 'man))?;
-                Ok(M::update(target, map_handle!(handle<[T]> 'man -> 'to)))
+                move_handles::<M, _>(
+                    manager!(mut self),
+                    target,
+                    |manager, handle| manager.get(map_handle!(handle<[T]> 'id -> 'man)).map(drop),
+                    |manager, handle| {
+                        let handle = manager.bump_version(map_handle!(handle<[T]> 'id -> 'man))?;
+                        Ok(map_handle!(handle<[T]> 'man -> 'id))
+                    },
+                )
             }
         }
-        impl<'id, 'man, U, const REUSE: bool, H, V> $type<'_, 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, H>
+        impl<'id, 'man, U, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, H>
         where
             U: RawBytes,
             H: Header,
@@ -233,7 +367,7 @@ macro_rules! impl_write {
                     .get_disjoint_mut(handles.map(|handle| map_handle!(handle<[T]> 'id -> 'man)))?)
             }
         }
-        impl<'id, 'man, U, const REUSE: bool, H, V> $type<'_, 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, H>
+        impl<'id, 'man, U, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, H>
         where
             U: RawBytes,
             H: Header,
@@ -253,12 +387,18 @@ macro_rules! impl_write {
             where
                 M: MappableHandle<Data = T>
             {
-                let handle = M::handle(&target);
-                let handle = manager!(mut self).bump_version(map_handle!(handle<T> 'id -> 'man))?;
-                Ok(M::update(target, map_handle!(handle<T> 'man -> 'to)))
+                move_handles::<M, _>(
+                    manager!(mut self),
+                    target,
+                    |manager, handle| manager.get(map_handle!(handle<T> 'id -> 'man)).map(drop),
+                    |manager, handle| {
+                        let handle = manager.bump_version(map_handle!(handle<T> 'id -> 'man))?;
+                        Ok(map_handle!(handle<T> 'man -> 'id))
+                    },
+                )
             }
         }
-        impl<'id, 'man, U, const REUSE: bool, H, V> $type<'_, 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, H>
+        impl<'id, 'man, U, const REUSE: bool, H, V> $type<$($lt,)? 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, H>
         where
             U: RawBytes,
             H: Header,
@@ -277,19 +417,227 @@ macro_rules! impl_write {
         }
     };
 }
-impl_write!(VArenaWriteGuard);
-impl_write!(VArenaAllocGuard);
+pub(super) use impl_write;
+impl_write!(VArenaWriteGuard<'_>);
+impl_write!(VArenaAllocGuard<'_>);
+impl_write!(ArcVArenaWriteGuard);
+impl_write!(ArcVArenaAllocGuard);
+macro_rules! impl_alloc {
+    ($type:ident $(<$lt:lifetime>)?) => {
+        impl<'id, 'man, K, const REUSE: bool, H, V>
+            $type<$($lt,)? 'id, 'man, K, Versioned<REUSE, H, V>, H>
+        where
+            H: Header,
+            GlobalConfig<K, Versioned<REUSE, H, V>>:
+                for<'x> Config<Store: Resizable, Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
+        {
+            #[rustfmt::skip]
+            pub fn reserve(&mut self, additional: Length) -> AResult<()> {
+                manager!(lock self |manager| Ok(manager.reserve(additional)?))
+            }
+        }
+        impl<'id, 'man, K, H, V> $type<$($lt,)? 'id, 'man, K, Versioned<false, H, V>, H>
+        where
+            H: Header,
+            GlobalConfig<K, Versioned<false, H, V>>:
+                for<'x> Config<Store: Truncate, Manager<'x> = VManager<'x, K, Versioned<false, H, V>>>,
+        {
+            pub fn checkpoint(&self) -> Checkpoint<'id> {
+                let Checkpoint { len, generation, .. } = manager!(ref self).checkpoint();
+                Checkpoint { len, generation, id: self.port.0 }
+            }
+            /// Drops everything that was inserted after `checkpoint` was created, this includes items inserted through other ports.
+            /// Handles to dropped items will never be valid again.
+            #[rustfmt::skip]
+            pub fn rollback_to(&mut self, checkpoint: Checkpoint<'id>) -> AResult<()> {
+                let Checkpoint { len, generation, .. } = checkpoint;
+                manager!(lock self |manager| Ok(manager.rollback_to_checkpoint(len, generation)?))
+            }
+        }
+        impl<'id, 'man, T, const REUSE: bool, H, V>
+            $type<$($lt,)? 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
+        where
+            H: Header,
+            GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: Store<(Version, T)>,
+                    Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn insert_within_capacity(&mut self, data: T) -> Result<VHandle<'id, T>, T> {
+                let handle = manager!(mut self).insert_within_capacity(data)?;
+                Ok(map_handle!(handle<T> 'man -> 'id))
+            }
+            pub fn insert(&mut self, data: T) -> Result<VHandle<'id, T>, (T, ArenaError)> {
+                match self.insert_within_capacity(data) {
+                    Ok(handle) => Ok(handle),
+                    Err(data) => {
+                        if let Err(err) = self.reserve(1) {
+                            return Err((data, err));
+                        }
+                        let Ok(handle) = self.insert_within_capacity(data) else {
+                            unreachable!("insert after reserve should always be successful")
+                        };
+                        Ok(handle)
+                    },
+                }
+            }
+        }
+        impl<'id, 'man, T, H, V> $type<$($lt,)? 'id, 'man, Typed<T>, Versioned<true, H, V>, H>
+        where
+            H: Header,
+            GlobalConfig<Typed<T>, Versioned<true, H, V>>: for<'x> Config<
+                    Store: ReusableStore<(Version, T)>,
+                    Manager<'x> = VManager<'x, Typed<T>, Versioned<true, H, V>>,
+                >,
+        {
+            pub fn remove(&mut self, handle: VHandle<'id, T>) -> AResult<T> {
+                Ok(manager!(mut self).remove(map_handle!(handle<T> 'id -> 'man))?)
+            }
+        }
+        impl<'id, 'man, C, L, const REUSE: bool, H, V>
+            $type<$($lt,)? 'id, 'man, SoA<C, L>, Versioned<REUSE, H, V>, H>
+        where
+            C: Columns,
+            L: SoALayout,
+            H: Header,
+            GlobalConfig<SoA<C, L>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: SoAStore<Prefix<Version, C>, VHandle<'x, C>>,
+                    Manager<'x> = VManager<'x, SoA<C, L>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn insert_within_capacity(&mut self, data: C) -> Result<VHandle<'id, C>, C> {
+                let handle = manager!(mut self).insert_within_capacity(data)?;
+                Ok(map_handle!(handle<C> 'man -> 'id))
+            }
+            pub fn insert(&mut self, data: C) -> Result<VHandle<'id, C>, (C, ArenaError)> {
+                match self.insert_within_capacity(data) {
+                    Ok(handle) => Ok(handle),
+                    Err(data) => {
+                        if let Err(err) = self.reserve(1) {
+                            return Err((data, err));
+                        }
+                        let Ok(handle) = self.insert_within_capacity(data) else {
+                            unreachable!("insert after reserve should always be successful")
+                        };
+                        Ok(handle)
+                    },
+                }
+            }
+        }
+        impl<'id, 'man, C, L, H, V> $type<$($lt,)? 'id, 'man, SoA<C, L>, Versioned<true, H, V>, H>
+        where
+            C: Columns,
+            L: SoALayout,
+            H: Header,
+            GlobalConfig<SoA<C, L>, Versioned<true, H, V>>: for<'x> Config<
+                    Store: ReusableSoAStore<Prefix<Version, C>, VHandle<'x, C>>,
+                    Manager<'x> = VManager<'x, SoA<C, L>, Versioned<true, H, V>>,
+                >,
+        {
+            pub fn remove(&mut self, handle: VHandle<'id, C>) -> AResult<C> {
+                Ok(manager!(mut self).remove(map_handle!(handle<C> 'id -> 'man))?)
+            }
+        }
+        impl<'id, 'man, U, const REUSE: bool, H, V>
+            $type<$($lt,)? 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, H>
+        where
+            U: RawBytes,
+            H: Header,
+            GlobalConfig<Slices<U>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: MultiStore<U>,
+                    Manager<'x> = VManager<'x, Slices<U>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn insert_within_capacity<T: Copy>(&mut self, data: &[T]) -> Option<VHandle<'id, [T]>> {
+                let handle = manager!(mut self).insert_within_capacity(data)?;
+                Some(map_handle!(handle<[T]> 'man -> 'id))
+            }
+            pub fn insert<T: Copy>(&mut self, data: &[T]) -> AResult<VHandle<'id, [T]>> {
+                match self.insert_within_capacity(data) {
+                    Some(handle) => Ok(handle),
+                    None => {
+                        self.reserve(
+                            Slices::<U>::header_size::<Version>()
+                                + Slices::<U>::size_of::<T>(data.len() as Length),
+                        )?;
+                        let Some(handle) = self.insert_within_capacity(data) else {
+                            unreachable!("insert after reserve should always be successful")
+                        };
+                        Ok(handle)
+                    },
+                }
+            }
+        }
+        impl<'id, 'man, U, H, V> $type<$($lt,)? 'id, 'man, Slices<U>, Versioned<true, H, V>, H>
+        where
+            U: RawBytes,
+            H: Header,
+            GlobalConfig<Slices<U>, Versioned<true, H, V>>: for<'x> Config<
+                    Store: ReusableMultiStore<U>,
+                    Manager<'x> = VManager<'x, Slices<U>, Versioned<true, H, V>>,
+                >,
+        {
+            pub fn remove<T: Copy>(
+                &mut self,
+                handle: VHandle<'id, [T]>,
+            ) -> AResult<RemoveSliceGuard<'_, U, Versioned<true, H, V>>> {
+                Ok(manager!(mut self).remove(map_handle!(handle<[T]> 'id -> 'man))?)
+            }
+        }
+        impl<'id, 'man, U, const REUSE: bool, H, V>
+            $type<$($lt,)? 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, H>
+        where
+            U: RawBytes,
+            H: Header,
+            GlobalConfig<Mixed<U>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: MultiStore<U>,
+                    Manager<'x> = VManager<'x, Mixed<U>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn insert_within_capacity<T>(&mut self, data: T) -> Result<VHandle<'id, T>, T> {
+                let handle = manager!(mut self).insert_within_capacity(data)?;
+                Ok(map_handle!(handle<T> 'man -> 'id))
+            }
+            pub fn insert<T>(&mut self, data: T) -> Result<VHandle<'id, T>, (T, ArenaError)> {
+                match self.insert_within_capacity(data) {
+                    Ok(handle) => Ok(handle),
+                    Err(data) => {
+                        if let Err(err) = self.reserve(Mixed::<U>::size_of::<(Version, T)>()) {
+                            return Err((data, err));
+                        }
+                        let Ok(handle) = self.insert_within_capacity(data) else {
+                            unreachable!("insert after reserve should always be successful")
+                        };
+                        Ok(handle)
+                    },
+                }
+            }
+        }
+        impl<'id, 'man, U, H, V> $type<$($lt,)? 'id, 'man, Mixed<U>, Versioned<true, H, V>, H>
+        where
+            U: RawBytes,
+            H: Header,
+            GlobalConfig<Mixed<U>, Versioned<true, H, V>>: for<'x> Config<
+                    Store: ReusableMultiStore<U>,
+                    Manager<'x> = VManager<'x, Mixed<U>, Versioned<true, H, V>>,
+                >,
+        {
+            pub fn remove<T>(&mut self, handle: VHandle<'id, T>) -> AResult<T> {
+                Ok(manager!(mut self).remove(map_handle!(handle<T> 'id -> 'man))?)
+            }
+        }
+    };
+}
+pub(super) use impl_alloc;
+impl_alloc!(VArenaAllocGuard<'_>);
+impl_alloc!(ArcVArenaAllocGuard);
 impl<'a, 'id, 'man, K, const REUSE: bool, H, V>
     VArenaAllocGuard<'a, 'id, 'man, K, Versioned<REUSE, H, V>, H>
 where
     H: Header,
     GlobalConfig<K, Versioned<REUSE, H, V>>:
-        for<'x> Config<Store: Resizable, Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
+        for<'x> Config<Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
 {
-    #[rustfmt::skip]
-    pub fn reserve(&mut self, additional: Length) -> AResult<()> {
-        manager!(lock self |manager| Ok(manager.reserve(additional)?))
-    }
     pub fn downgrade(self) -> VArenaWriteGuard<'a, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
         VArenaWriteGuard {
             manager: RwLockUpgradableReadGuard::downgrade(self.manager),
@@ -297,173 +645,97 @@ where
         }
     }
 }
-impl<'id, 'man, T, const REUSE: bool, H, V>
-    VArenaAllocGuard<'_, 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
-where
-    H: Header,
-    GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
-            Store: Store<(Version, T)>,
-            Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
-        >,
-{
-    pub fn insert_within_capacity(&mut self, data: T) -> Result<VHandle<'id, T>, T> {
-        let handle = manager!(mut self).insert_within_capacity(data)?;
-        Ok(map_handle!(handle<T> 'man -> 'id))
-    }
-    pub fn insert(&mut self, data: T) -> Result<VHandle<'id, T>, (T, ArenaError)> {
-        match self.insert_within_capacity(data) {
-            Ok(handle) => Ok(handle),
-            Err(data) => {
-                if let Err(err) = self.reserve(1) {
-                    return Err((data, err));
-                }
-                let Ok(handle) = self.insert_within_capacity(data) else {
-                    unreachable!("insert after reserve should always be successful")
-                };
-                Ok(handle)
-            },
-        }
-    }
-}
-impl<'id, 'man, T, H, V> VArenaAllocGuard<'_, 'id, 'man, Typed<T>, Versioned<true, H, V>, H>
+impl<'a, 'id, 'man, K, const REUSE: bool, H, V>
+    VArenaWriteGuard<'a, 'id, 'man, K, Versioned<REUSE, H, V>, H>
 where
     H: Header,
-    GlobalConfig<Typed<T>, Versioned<true, H, V>>: for<'x> Config<
-            Store: ReusableStore<(Version, T)>,
-            Manager<'x> = VManager<'x, Typed<T>, Versioned<true, H, V>>,
-        >,
+    GlobalConfig<K, Versioned<REUSE, H, V>>:
+        for<'x> Config<Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
 {
-    pub fn remove(&mut self, handle: VHandle<'id, T>) -> AResult<T> {
-        Ok(manager!(mut self).remove(map_handle!(handle<T> 'id -> 'man))?)
+    /// Tries to turn this into an alloc guard, fails when another port is allocating.
+    pub fn try_upgrade(
+        self,
+    ) -> Result<VArenaAllocGuard<'a, 'id, 'man, K, Versioned<REUSE, H, V>, H>, Self> {
+        let Some(manager) = RwLockReadGuard::rwlock(&self.manager).try_upgradable_read() else {
+            return Err(self);
+        };
+        Ok(VArenaAllocGuard { manager, port: self.port })
     }
 }
-impl<'id, 'man, C, const REUSE: bool, H, V>
-    VArenaAllocGuard<'_, 'id, 'man, SoA<C>, Versioned<REUSE, H, V>, H>
+impl<'id, 'man, K, const REUSE: bool, H, V>
+    ArcVArenaAllocGuard<'id, 'man, K, Versioned<REUSE, H, V>, H>
 where
-    C: Columns,
     H: Header,
-    GlobalConfig<SoA<C>, Versioned<REUSE, H, V>>: for<'x> Config<
-            Store: SoAStore<Prefix<Version, C>, VHandle<'x, C>>,
-            Manager<'x> = VManager<'x, SoA<C>, Versioned<REUSE, H, V>>,
-        >,
+    GlobalConfig<K, Versioned<REUSE, H, V>>:
+        for<'x> Config<Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
 {
-    pub fn insert_within_capacity(&mut self, data: C) -> Result<VHandle<'id, C>, C> {
-        let handle = manager!(mut self).insert_within_capacity(data)?;
-        Ok(map_handle!(handle<C> 'man -> 'id))
-    }
-    pub fn insert(&mut self, data: C) -> Result<VHandle<'id, C>, (C, ArenaError)> {
-        match self.insert_within_capacity(data) {
-            Ok(handle) => Ok(handle),
-            Err(data) => {
-                if let Err(err) = self.reserve(1) {
-                    return Err((data, err));
-                }
-                let Ok(handle) = self.insert_within_capacity(data) else {
-                    unreachable!("insert after reserve should always be successful")
-                };
-                Ok(handle)
-            },
+    pub fn downgrade(self) -> ArcVArenaWriteGuard<'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        ArcVArenaWriteGuard {
+            manager: ArcRwLockUpgradableReadGuard::downgrade(self.manager),
+            port:    self.port,
         }
     }
 }
-impl<'id, 'man, C, H, V> VArenaAllocGuard<'_, 'id, 'man, SoA<C>, Versioned<true, H, V>, H>
+impl<'id, 'man, T, H, V> Reversible<'id, T>
+    for VArenaAllocGuard<'_, 'id, 'man, Typed<T>, Versioned<true, H, V>, H>
 where
-    C: Columns,
     H: Header,
-    GlobalConfig<SoA<C>, Versioned<true, H, V>>: for<'x> Config<
-            Store: ReusableSoAStore<Prefix<Version, C>, VHandle<'x, C>>,
-            Manager<'x> = VManager<'x, SoA<C>, Versioned<true, H, V>>,
+    GlobalConfig<Typed<T>, Versioned<true, H, V>>: for<'x> Config<
+            Store: ReusableStore<(Version, T)> + Restore<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<true, H, V>>,
         >,
 {
-    pub fn remove(&mut self, handle: VHandle<'id, C>) -> AResult<C> {
-        Ok(manager!(mut self).remove(map_handle!(handle<C> 'id -> 'man))?)
+    type Error = ArenaError;
+
+    fn insert(&mut self, data: T) -> Result<VHandle<'id, T>, (T, ArenaError)> {
+        VArenaAllocGuard::insert(self, data)
     }
-}
-impl<'id, 'man, U, const REUSE: bool, H, V>
-    VArenaAllocGuard<'_, 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, H>
-where
-    U: RawBytes,
-    H: Header,
-    GlobalConfig<Slices<U>, Versioned<REUSE, H, V>>: for<'x> Config<
-            Store: MultiStore<U>,
-            Manager<'x> = VManager<'x, Slices<U>, Versioned<REUSE, H, V>>,
-        >,
-{
-    pub fn insert_within_capacity<T: Copy>(&mut self, data: &[T]) -> Option<VHandle<'id, [T]>> {
-        let handle = manager!(mut self).insert_within_capacity(data)?;
-        Some(map_handle!(handle<[T]> 'man -> 'id))
+
+    fn remove(&mut self, handle: VHandle<'id, T>) -> AResult<T> {
+        VArenaAllocGuard::remove(self, handle)
     }
-    pub fn insert<T: Copy>(&mut self, data: &[T]) -> AResult<VHandle<'id, [T]>> {
-        match self.insert_within_capacity(data) {
-            Some(handle) => Ok(handle),
-            None => {
-                self.reserve(
-                    Slices::<U>::header_size::<Version>()
-                        + Slices::<U>::size_of::<T>(data.len() as Length),
-                )?;
-                let Some(handle) = self.insert_within_capacity(data) else {
-                    unreachable!("insert after reserve should always be successful")
-                };
-                Ok(handle)
-            },
-        }
+
+    fn restore(&mut self, handle: VHandle<'id, T>, data: T) -> Result<(), (T, ArenaError)> {
+        let handle = map_handle!(handle<T> 'id -> 'man);
+        manager!(mut self).restore(handle, data).map_err(|(data, err)| (data, err.into()))
     }
-}
-impl<'id, 'man, U, H, V> VArenaAllocGuard<'_, 'id, 'man, Slices<U>, Versioned<true, H, V>, H>
-where
-    U: RawBytes,
-    H: Header,
-    GlobalConfig<Slices<U>, Versioned<true, H, V>>: for<'x> Config<
-            Store: ReusableMultiStore<U>,
-            Manager<'x> = VManager<'x, Slices<U>, Versioned<true, H, V>>,
-        >,
-{
-    pub fn remove<T: Copy>(
-        &mut self,
-        handle: VHandle<'id, [T]>,
-    ) -> AResult<RemoveSliceGuard<'_, U, Versioned<true, H, V>>> {
-        Ok(manager!(mut self).remove(map_handle!(handle<[T]> 'id -> 'man))?)
+
+    fn replace(&mut self, handle: VHandle<'id, T>, data: T) -> Result<T, (T, ArenaError)> {
+        let handle = map_handle!(handle<T> 'id -> 'man);
+        Reversible::replace(manager!(mut self), handle, data)
+            .map_err(|(data, err)| (data, err.into()))
     }
 }
-impl<'id, 'man, U, const REUSE: bool, H, V>
-    VArenaAllocGuard<'_, 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, H>
+impl<'id, 'man, C, L, H, V> Reversible<'id, C>
+    for VArenaAllocGuard<'_, 'id, 'man, SoA<C, L>, Versioned<true, H, V>, H>
 where
-    U: RawBytes,
+    C: Columns,
+    L: SoALayout,
     H: Header,
-    GlobalConfig<Mixed<U>, Versioned<REUSE, H, V>>: for<'x> Config<
-            Store: MultiStore<U>,
-            Manager<'x> = VManager<'x, Mixed<U>, Versioned<REUSE, H, V>>,
+    GlobalConfig<SoA<C, L>, Versioned<true, H, V>>: for<'x> Config<
+            Store: ReusableSoAStore<Prefix<Version, C>, VHandle<'x, C>>
+                       + Restore<Prefix<Version, C>>,
+            Manager<'x> = VManager<'x, SoA<C, L>, Versioned<true, H, V>>,
         >,
 {
-    pub fn insert_within_capacity<T>(&mut self, data: T) -> Result<VHandle<'id, T>, T> {
-        let handle = manager!(mut self).insert_within_capacity(data)?;
-        Ok(map_handle!(handle<T> 'man -> 'id))
+    type Error = ArenaError;
+
+    fn insert(&mut self, data: C) -> Result<VHandle<'id, C>, (C, ArenaError)> {
+        VArenaAllocGuard::insert(self, data)
     }
-    pub fn insert<T>(&mut self, data: T) -> Result<VHandle<'id, T>, (T, ArenaError)> {
-        match self.insert_within_capacity(data) {
-            Ok(handle) => Ok(handle),
-            Err(data) => {
-                if let Err(err) = self.reserve(Mixed::<U>::size_of::<(Version, T)>()) {
-                    return Err((data, err));
-                }
-                let Ok(handle) = self.insert_within_capacity(data) else {
-                    unreachable!("insert after reserve should always be successful")
-                };
-                Ok(handle)
-            },
-        }
+
+    fn remove(&mut self, handle: VHandle<'id, C>) -> AResult<C> {
+        VArenaAllocGuard::remove(self, handle)
     }
-}
-impl<'id, 'man, U, H, V> VArenaAllocGuard<'_, 'id, 'man, Mixed<U>, Versioned<true, H, V>, H>
-where
-    U: RawBytes,
-    H: Header,
-    GlobalConfig<Mixed<U>, Versioned<true, H, V>>: for<'x> Config<
-            Store: ReusableMultiStore<U>,
-            Manager<'x> = VManager<'x, Mixed<U>, Versioned<true, H, V>>,
-        >,
-{
-    pub fn remove<T>(&mut self, handle: VHandle<'id, T>) -> AResult<T> {
-        Ok(manager!(mut self).remove(map_handle!(handle<T> 'id -> 'man))?)
+
+    fn restore(&mut self, handle: VHandle<'id, C>, data: C) -> Result<(), (C, ArenaError)> {
+        let handle = map_handle!(handle<C> 'id -> 'man);
+        manager!(mut self).restore(handle, data).map_err(|(data, err)| (data, err.into()))
+    }
+
+    fn replace(&mut self, handle: VHandle<'id, C>, data: C) -> Result<C, (C, ArenaError)> {
+        let handle = map_handle!(handle<C> 'id -> 'man);
+        Reversible::replace(manager!(mut self), handle, data)
+            .map_err(|(data, err)| (data, err.into()))
     }
 }
@@ -1,3 +1,12 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    hash::{BuildHasher, Hash},
+};
+
+#[cfg(feature = "derive")]
+pub use niche_collections_derive::MappableHandle;
+use variadics_please::all_tuples_enumerated;
+
 use super::*;
 
 #[macro_export]
@@ -19,36 +28,229 @@ impl<'from, 'to> HandleMap<'from, 'to> {
     where
         M: MappableHandle,
     {
-        let handle = M::handle(&target);
-        M::update(target, map_handle!(handle<M::Data> 'from -> 'to))
+        M::update(target, &mut |handle| map_handle!(handle<M::Data> 'from -> 'to))
     }
     pub fn chain<'next>(self, other: HandleMap<'to, 'next>) -> HandleMap<'from, 'next> {
         HandleMap { _from: self._from, _to: other._to }
     }
 }
+/// Types holding handles, which can be moved to another port as a whole.
+///
+/// The implementing type only acts as a marker for [`MappableHandle::Container`],
+/// e.g. `Vec<VHandle<'static, T>>` for `Vec<VHandle<'id, T>>`.
+/// With the `derive` feature this can be derived for structs with a single lifetime parameter,
+/// where every field mentioning the lifetime has to be mappable with the same `Data`.
+///
+/// A container can only hold handles of a single type, the same applies to tuples.
+/// Handles of different types have to be moved with separate calls.
+/// ```compile_fail
+/// # use niche_collections::alloc::{arena::*, manager::VHandle};
+/// #[derive(MappableHandle)]
+/// struct Mixed<'id> {
+///     a: VHandle<'id, u32>,
+///     b: VHandle<'id, f32>,
+/// }
+/// ```
 pub trait MappableHandle {
     type Container<'id>;
     type Data: ?Sized;
 
-    fn handle<'id>(target: &Self::Container<'id>) -> VHandle<'id, Self::Data>;
-
+    /// Calls `f` with every handle held by `target`.
+    fn handles<'id>(target: &Self::Container<'id>, f: &mut impl FnMut(VHandle<'id, Self::Data>));
+    /// Replaces every handle held by `from` with the result of `f`.
     fn update<'from, 'to>(
         from: Self::Container<'from>,
-        to: VHandle<'to, Self::Data>,
+        f: &mut impl FnMut(VHandle<'from, Self::Data>) -> VHandle<'to, Self::Data>,
     ) -> Self::Container<'to>;
 }
-impl<T> MappableHandle for VHandle<'_, T> {
+impl<T: ?Sized> MappableHandle for VHandle<'_, T> {
     type Container<'id> = VHandle<'id, T>;
     type Data = T;
 
-    fn handle<'id>(target: &Self::Container<'id>) -> VHandle<'id, Self::Data> {
-        *target
+    fn handles<'id>(target: &Self::Container<'id>, f: &mut impl FnMut(VHandle<'id, T>)) {
+        f(*target)
+    }
+    fn update<'from, 'to>(
+        from: Self::Container<'from>,
+        f: &mut impl FnMut(VHandle<'from, T>) -> VHandle<'to, T>,
+    ) -> Self::Container<'to> {
+        f(from)
     }
+}
+impl<M: MappableHandle> MappableHandle for Option<M> {
+    type Container<'id> = Option<M::Container<'id>>;
+    type Data = M::Data;
 
+    fn handles<'id>(target: &Self::Container<'id>, f: &mut impl FnMut(VHandle<'id, M::Data>)) {
+        if let Some(target) = target {
+            M::handles(target, f);
+        }
+    }
     fn update<'from, 'to>(
-        _from: Self::Container<'from>,
-        to: VHandle<'to, Self::Data>,
+        from: Self::Container<'from>,
+        f: &mut impl FnMut(VHandle<'from, M::Data>) -> VHandle<'to, M::Data>,
     ) -> Self::Container<'to> {
-        to
+        from.map(|from| M::update(from, f))
+    }
+}
+impl<M: MappableHandle> MappableHandle for Box<M> {
+    type Container<'id> = Box<M::Container<'id>>;
+    type Data = M::Data;
+
+    fn handles<'id>(target: &Self::Container<'id>, f: &mut impl FnMut(VHandle<'id, M::Data>)) {
+        M::handles(target, f);
+    }
+    fn update<'from, 'to>(
+        from: Self::Container<'from>,
+        f: &mut impl FnMut(VHandle<'from, M::Data>) -> VHandle<'to, M::Data>,
+    ) -> Self::Container<'to> {
+        Box::new(M::update(*from, f))
+    }
+}
+impl<M: MappableHandle, const N: usize> MappableHandle for [M; N] {
+    type Container<'id> = [M::Container<'id>; N];
+    type Data = M::Data;
+
+    fn handles<'id>(target: &Self::Container<'id>, f: &mut impl FnMut(VHandle<'id, M::Data>)) {
+        target.iter().for_each(|target| M::handles(target, f));
+    }
+    fn update<'from, 'to>(
+        from: Self::Container<'from>,
+        f: &mut impl FnMut(VHandle<'from, M::Data>) -> VHandle<'to, M::Data>,
+    ) -> Self::Container<'to> {
+        from.map(|from| M::update(from, f))
+    }
+}
+impl<M: MappableHandle> MappableHandle for Vec<M> {
+    type Container<'id> = Vec<M::Container<'id>>;
+    type Data = M::Data;
+
+    fn handles<'id>(target: &Self::Container<'id>, f: &mut impl FnMut(VHandle<'id, M::Data>)) {
+        target.iter().for_each(|target| M::handles(target, f));
+    }
+    fn update<'from, 'to>(
+        from: Self::Container<'from>,
+        f: &mut impl FnMut(VHandle<'from, M::Data>) -> VHandle<'to, M::Data>,
+    ) -> Self::Container<'to> {
+        from.into_iter().map(|from| M::update(from, f)).collect()
+    }
+}
+impl<K, M, S> MappableHandle for HashMap<K, M, S>
+where
+    K: Eq + Hash,
+    M: MappableHandle,
+    S: BuildHasher + Default,
+{
+    type Container<'id> = HashMap<K, M::Container<'id>, S>;
+    type Data = M::Data;
+
+    fn handles<'id>(target: &Self::Container<'id>, f: &mut impl FnMut(VHandle<'id, M::Data>)) {
+        target.values().for_each(|target| M::handles(target, f));
+    }
+    fn update<'from, 'to>(
+        from: Self::Container<'from>,
+        f: &mut impl FnMut(VHandle<'from, M::Data>) -> VHandle<'to, M::Data>,
+    ) -> Self::Container<'to> {
+        from.into_iter().map(|(key, from)| (key, M::update(from, f))).collect()
+    }
+}
+/// Used by the derive of [`MappableHandle`] to reject fields with different handle types.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "every field mapped by `MappableHandle` has to hold `VHandle<'_, {D}>`",
+    label = "this field holds `VHandle<'_, {Self}>`"
+)]
+pub trait SameHandleData<D: ?Sized> {}
+impl<D: ?Sized> SameHandleData<D> for D {}
+macro_rules! impl_mappable_tuple {
+    ($(($i:tt, $T:ident)),*) => {
+        impl<D: ?Sized, $($T: MappableHandle<Data = D>),*> MappableHandle for ($($T,)*) {
+            type Container<'id> = ($($T::Container<'id>,)*);
+            type Data = D;
+
+            fn handles<'id>(target: &Self::Container<'id>, f: &mut impl FnMut(VHandle<'id, D>)) {
+                $($T::handles(&target.$i, f);)*
+            }
+            fn update<'from, 'to>(
+                from: Self::Container<'from>,
+                f: &mut impl FnMut(VHandle<'from, D>) -> VHandle<'to, D>,
+            ) -> Self::Container<'to> {
+                ($($T::update(from.$i, f),)*)
+            }
+        }
+    };
+}
+all_tuples_enumerated!(impl_mappable_tuple, 1, 16, T);
+
+/// Moves every handle in `target` to a new version using `bump`, after checking all of them with `check`.
+/// Each distinct handle is moved once, so nothing is changed when a handle fails the check.
+pub(super) fn move_handles<'from, 'to, M, S>(
+    state: &mut S,
+    target: M::Container<'from>,
+    check: impl Fn(&S, VHandle<'from, M::Data>) -> MResult<()>,
+    bump: impl Fn(&mut S, VHandle<'from, M::Data>) -> MResult<VHandle<'from, M::Data>>,
+) -> AResult<M::Container<'to>>
+where
+    M: MappableHandle,
+{
+    let mut handles = Vec::new();
+    M::handles(&target, &mut |handle| handles.push(handle));
+    for handle in &handles {
+        check(state, *handle)?;
+    }
+    let mut moved = HashMap::with_capacity(handles.len());
+    for handle in handles {
+        if let Entry::Vacant(entry) = moved.entry(handle.into_index()) {
+            entry.insert(bump(state, handle)?);
+        }
+    }
+    Ok(M::update(target, &mut |handle| {
+        let handle = moved[&handle.into_index()];
+        map_handle!(handle<M::Data> 'from -> 'to)
+    }))
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+
+    #[derive(MappableHandle)]
+    struct Node<'id> {
+        parent:   Option<VHandle<'id, u32>>,
+        children: Vec<VHandle<'id, u32>>,
+        weight:   f32,
+    }
+
+    #[test]
+    fn moves_containers_between_ports() {
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(man);
+        let mut a = Arena::<Typed<u32>, Versioned<true>>::new(a, man, Headless::default());
+        let mut b = a.split(b);
+        let mut guard = b.alloc();
+        let parent = guard.insert(1).unwrap();
+        let child = guard.insert(2).unwrap();
+        let node = Node { parent: Some(parent), children: vec![child, child], weight: 0.5 };
+        let node = guard.move_to::<Node>(&mut a, node).unwrap();
+        assert!(guard.get(parent).is_err(), "moved handles should be invalid in the old port");
+        assert_eq!(0.5, node.weight);
+        drop(guard);
+        let guard = a.read();
+        assert_eq!(Ok(&1), guard.get(node.parent.unwrap()));
+        assert!(node.children.iter().all(|child| guard.get(*child) == Ok(&2)));
+        drop(guard);
+
+        let mut guard = b.alloc();
+        let stale = (guard.insert(3).unwrap(), parent);
+        assert!(guard.move_to::<(VHandle<u32>, VHandle<u32>)>(&mut a, stale).is_err());
+        assert_eq!(Ok(&3), guard.get(stale.0), "nothing is moved when a handle is invalid");
+        drop(guard);
+        let map = a.join(b).map_err(|(_, err)| err).unwrap();
+        let moved =
+            map.apply::<HashMap<&str, [VHandle<u32>; 1]>>(HashMap::from([("x", [stale.0])]));
+        assert_eq!(Ok(&3), a.read().get(moved["x"][0]));
     }
 }
@@ -0,0 +1,306 @@
+use std::{
+    cell::Cell,
+    fmt::{self, Debug},
+    mem::{ManuallyDrop, transmute},
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::*;
+
+/// Single threaded counterpart of [`RwLock`] that panics where it would block.
+pub(super) struct LocalLock<T> {
+    readers:    Cell<usize>,
+    writer:     Cell<bool>,
+    upgradable: Cell<bool>,
+    value:      UnsafeCell<T>,
+}
+impl<T> LocalLock<T> {
+    fn new(value: T) -> Self {
+        Self {
+            readers:    Cell::new(0),
+            writer:     Cell::new(false),
+            upgradable: Cell::new(false),
+            value:      UnsafeCell::new(value),
+        }
+    }
+    fn read(&self) -> LocalReadGuard<'_, T> {
+        assert!(!self.writer.get(), "already locked for writing");
+        self.readers.set(self.readers.get() + 1);
+        LocalReadGuard { lock: self }
+    }
+    fn write(&self) -> LocalWriteGuard<'_, T> {
+        assert!(
+            !self.writer.get() && !self.upgradable.get() && self.readers.get() == 0,
+            "already locked"
+        );
+        self.writer.set(true);
+        LocalWriteGuard { lock: self }
+    }
+    fn upgradable_read(&self) -> LocalUpgradableGuard<'_, T> {
+        assert!(!self.writer.get() && !self.upgradable.get(), "already locked for allocation");
+        self.upgradable.set(true);
+        LocalUpgradableGuard { lock: self }
+    }
+}
+
+pub struct LocalReadGuard<'a, T> {
+    lock: &'a LocalLock<T>,
+}
+impl<T> Deref for LocalReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: there is no writer while this guard exists
+        unsafe { &*self.lock.value.get() }
+    }
+}
+impl<T> Drop for LocalReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.readers.set(self.lock.readers.get() - 1);
+    }
+}
+impl<T: Debug> Debug for LocalReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+pub struct LocalWriteGuard<'a, T> {
+    lock: &'a LocalLock<T>,
+}
+impl<T> Deref for LocalWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: this is the only guard of the lock
+        unsafe { &*self.lock.value.get() }
+    }
+}
+impl<T> DerefMut for LocalWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: this is the only guard of the lock
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+impl<T> Drop for LocalWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.writer.set(false);
+    }
+}
+impl<T: Debug> Debug for LocalWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+pub struct LocalUpgradableGuard<'a, T> {
+    lock: &'a LocalLock<T>,
+}
+impl<'a, T> LocalUpgradableGuard<'a, T> {
+    /// Calls `f` with exclusive access.
+    /// # Panics
+    /// Panics if the lock is read by another guard, where [`RwLock`] would deadlock.
+    pub fn with_upgraded<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R {
+        assert_eq!(0, self.lock.readers.get(), "still locked for reading");
+        self.lock.writer.set(true);
+        struct Downgrade<'a>(&'a Cell<bool>);
+        impl Drop for Downgrade<'_> {
+            fn drop(&mut self) {
+                self.0.set(false);
+            }
+        }
+        let _downgrade = Downgrade(&self.lock.writer);
+        // SAFETY: there are no other guards while the writer flag is set
+        f(unsafe { &mut *self.lock.value.get() })
+    }
+    pub fn downgrade(s: Self) -> LocalReadGuard<'a, T> {
+        let lock = ManuallyDrop::new(s).lock;
+        lock.upgradable.set(false);
+        lock.readers.set(lock.readers.get() + 1);
+        LocalReadGuard { lock }
+    }
+}
+impl<T> Deref for LocalUpgradableGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: there is no writer while this guard exists
+        unsafe { &*self.lock.value.get() }
+    }
+}
+impl<T> Drop for LocalUpgradableGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.upgradable.set(false);
+    }
+}
+impl<T: Debug> Debug for LocalUpgradableGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+type RcLock<T> = Rc<LocalLock<T>>;
+
+/// Single threaded [`VArena`], which shares its manager without atomics.
+///
+/// The guards have the same API as the guards of [`VArena`],
+/// but acquiring a guard panics where [`VArena`] would block.
+pub struct LocalArena<'id, 'man, K, C, H: Header = Headless>
+where
+    GlobalConfig<K, C>: Config,
+{
+    manager: RcLock<ManagerCell<'man, K, C>>,
+    port:    RcLock<(Id<'id>, H)>,
+}
+impl<'id, 'man, K, const REUSE: bool, H, V> LocalArena<'id, 'man, K, Versioned<REUSE, H, V>, H>
+where
+    H: Header,
+    V: Tracking,
+    GlobalConfig<K, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: Default + Resizable,
+            Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>,
+        >,
+{
+    pub fn new(guard: Guard<'id>, manager_guard: Guard<'man>, header: H) -> Self {
+        Self::with_manager(guard, Manager::new(manager_guard), header)
+    }
+}
+impl<'id, 'man, K, const REUSE: bool, H, V> LocalArena<'id, 'man, K, Versioned<REUSE, H, V>, H>
+where
+    H: Header,
+    GlobalConfig<K, Versioned<REUSE, H, V>>:
+        for<'x> Config<Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
+{
+    /// Creates an arena around an empty manager, see [`Arena::with_manager`].
+    pub fn with_manager(
+        guard: Guard<'id>,
+        manager: Manager<'man, K, Versioned<REUSE, H, V>>,
+        header: H,
+    ) -> Self {
+        Self {
+            manager: Rc::new(LocalLock::new(ManagerCell::new(manager))),
+            port:    Rc::new(LocalLock::new((guard.into(), header))),
+        }
+    }
+    pub fn split<'new>(
+        &self,
+        guard: Guard<'new>,
+    ) -> LocalArena<'new, 'man, K, Versioned<REUSE, H, V>, H> {
+        LocalArena {
+            manager: self.manager.clone(),
+            port:    Rc::new(LocalLock::new((guard.into(), H::default()))),
+        }
+    }
+    /// Merges the port of `other` into the port of `self`, see [`Arena::join`].
+    #[expect(clippy::type_complexity)]
+    pub fn join<'other>(
+        &self,
+        other: LocalArena<'other, 'man, K, Versioned<REUSE, H, V>, H>,
+    ) -> Result<
+        HandleMap<'other, 'id>,
+        (LocalArena<'other, 'man, K, Versioned<REUSE, H, V>, H>, ArenaError),
+    > {
+        let (this, port) = (Rc::as_ptr(&self.port), Rc::as_ptr(&other.port));
+        if let Err(err) = check_join(this, port, Rc::strong_count(&other.port)) {
+            return Err((other, err));
+        }
+        Ok(merge_ports(&mut self.port.write(), &mut other.port.write()))
+    }
+    pub fn read(&self) -> LocalArenaReadGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        LocalArenaReadGuard { manager: self.manager.read(), port: self.port.read() }
+    }
+    pub fn write(&mut self) -> LocalArenaWriteGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        LocalArenaWriteGuard { manager: self.manager.read(), port: self.port.write() }
+    }
+    pub fn alloc(&mut self) -> LocalArenaAllocGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        LocalArenaAllocGuard { manager: self.manager.upgradable_read(), port: self.port.write() }
+    }
+}
+
+#[derive(Debug)]
+pub struct LocalArenaReadGuard<'a, 'id, 'man, K, C, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    manager: LocalReadGuard<'a, ManagerCell<'man, K, C>>,
+    port:    LocalReadGuard<'a, (Id<'id>, H)>,
+}
+#[derive(Debug)]
+pub struct LocalArenaWriteGuard<'a, 'id, 'man, K, C, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    manager: LocalReadGuard<'a, ManagerCell<'man, K, C>>,
+    port:    LocalWriteGuard<'a, (Id<'id>, H)>,
+}
+#[derive(Debug)]
+pub struct LocalArenaAllocGuard<'a, 'id, 'man, K, C, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    manager: LocalUpgradableGuard<'a, ManagerCell<'man, K, C>>,
+    port:    LocalWriteGuard<'a, (Id<'id>, H)>,
+}
+impl_read!(LocalArenaReadGuard<'_>);
+impl_read!(LocalArenaWriteGuard<'_>);
+impl_read!(LocalArenaAllocGuard<'_>);
+impl_write!(LocalArenaWriteGuard<'_>);
+impl_write!(LocalArenaAllocGuard<'_>);
+impl_alloc!(LocalArenaAllocGuard<'_>);
+impl<'a, 'id, 'man, K, const REUSE: bool, H, V>
+    LocalArenaAllocGuard<'a, 'id, 'man, K, Versioned<REUSE, H, V>, H>
+where
+    H: Header,
+    GlobalConfig<K, Versioned<REUSE, H, V>>:
+        for<'x> Config<Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
+{
+    pub fn downgrade(self) -> LocalArenaWriteGuard<'a, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        LocalArenaWriteGuard {
+            manager: LocalUpgradableGuard::downgrade(self.manager),
+            port:    self.port,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+
+    #[test]
+    fn shares_the_manager_between_ports() {
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(man);
+        let mut a = LocalArena::<Typed<u32>, Versioned<true>>::new(a, man, Headless::default());
+        let mut b = a.split(b);
+        let first = a.alloc().insert(1).unwrap();
+        let second = b.alloc().insert(2).unwrap();
+        let write = b.write();
+        let mut alloc = a.alloc();
+        *alloc.get_mut(first).unwrap() += 10;
+        assert_eq!(Ok(&2), write.get(second));
+        drop((write, alloc));
+        let Ok(map) = a.join(b) else { panic!("unshared port should be joined") };
+        let second = map.apply::<VHandle<u32>>(second);
+        assert_eq!(Ok(&11), a.read().get(first));
+        assert_eq!(Ok(&2), a.read().get(second));
+    }
+
+    #[test]
+    #[should_panic = "still locked for reading"]
+    fn panics_instead_of_deadlocking() {
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(man);
+        let mut a = LocalArena::<Typed<u32>, Versioned<true>>::new(a, man, Headless::default());
+        let b = a.split(b);
+        let _read = b.read();
+        let _ = a.alloc().reserve(1);
+    }
+}
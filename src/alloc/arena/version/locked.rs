@@ -0,0 +1,123 @@
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use super::*;
+
+/// Element with its own lock, so elements of the same port can be written from multiple threads
+/// while the port is only read locked, see [`VArenaReadGuard::elems`].
+#[derive(Debug, Default)]
+pub struct Locked<T>(RwLock<T>);
+impl<T> Locked<T> {
+    pub fn new(value: T) -> Self {
+        Self(RwLock::new(value))
+    }
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+}
+impl<T> From<T> for Locked<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Shareable access to the element locks of a port.
+///
+/// This is [`Sync`] when the manager is, so it can be handed to a thread pool
+/// to write disjoint elements concurrently.
+pub struct ElemLocks<'a, 'id, 'man, T, C>
+where
+    GlobalConfig<Typed<Locked<T>>, C>: Config,
+{
+    manager: &'a Manager<'man, Typed<Locked<T>>, C>,
+    _id:     Id<'id>,
+}
+impl<T, C> Clone for ElemLocks<'_, '_, '_, T, C>
+where
+    GlobalConfig<Typed<Locked<T>>, C>: Config,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T, C> Copy for ElemLocks<'_, '_, '_, T, C> where GlobalConfig<Typed<Locked<T>>, C>: Config {}
+impl<'a, 'id, 'man, T, const REUSE: bool, H, V> ElemLocks<'a, 'id, 'man, T, Versioned<REUSE, H, V>>
+where
+    H: Header,
+    GlobalConfig<Typed<Locked<T>>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: Store<(Version, Locked<T>)>,
+            Manager<'x> = VManager<'x, Typed<Locked<T>>, Versioned<REUSE, H, V>>,
+        >,
+{
+    fn get(&self, handle: VHandle<'id, Locked<T>>) -> AResult<&'a RwLock<T>> {
+        Ok(&self.manager.get(map_handle!(handle<Locked<T>> 'id -> 'man))?.0)
+    }
+    pub fn read(&self, handle: VHandle<'id, Locked<T>>) -> AResult<RwLockReadGuard<'a, T>> {
+        Ok(self.get(handle)?.read())
+    }
+    pub fn write(&self, handle: VHandle<'id, Locked<T>>) -> AResult<RwLockWriteGuard<'a, T>> {
+        Ok(self.get(handle)?.write())
+    }
+    /// Returns `None` when the element is currently locked.
+    pub fn try_write(
+        &self,
+        handle: VHandle<'id, Locked<T>>,
+    ) -> AResult<Option<RwLockWriteGuard<'a, T>>> {
+        Ok(self.get(handle)?.try_write())
+    }
+}
+
+impl<'id, 'man, T, const REUSE: bool, H, V>
+    VArenaReadGuard<'_, 'id, 'man, Typed<Locked<T>>, Versioned<REUSE, H, V>, H>
+where
+    H: Header,
+    GlobalConfig<Typed<Locked<T>>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: Store<(Version, Locked<T>)>,
+            Manager<'x> = VManager<'x, Typed<Locked<T>>, Versioned<REUSE, H, V>>,
+        >,
+{
+    /// Element level locks of this port, the port stays read locked while they are in use.
+    pub fn elems(&self) -> ElemLocks<'_, 'id, 'man, T, Versioned<REUSE, H, V>> {
+        ElemLocks { manager: manager!(ref self), _id: self.port.0 }
+    }
+    pub fn read_elem(&self, handle: VHandle<'id, Locked<T>>) -> AResult<RwLockReadGuard<'_, T>> {
+        self.elems().read(handle)
+    }
+    /// Write locks a single element, other elements of this port can still be locked.
+    pub fn lock_elem(&self, handle: VHandle<'id, Locked<T>>) -> AResult<RwLockWriteGuard<'_, T>> {
+        self.elems().write(handle)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use generativity::make_guard;
+
+    use super::*;
+
+    #[test]
+    fn writes_disjoint_elements_concurrently() {
+        make_guard!(a);
+        make_guard!(man);
+        let mut a = Arena::<Typed<Locked<u32>>, Versioned<true>>::new(a, man, Headless::default());
+        let handles: Vec<_> = {
+            let mut alloc = a.alloc();
+            (0..8).map(|i| alloc.insert(Locked::new(i)).unwrap()).collect()
+        };
+        let read = a.read();
+        let elems = read.elems();
+        thread::scope(|scope| {
+            for &handle in &handles {
+                scope.spawn(move || *elems.write(handle).unwrap() *= 10);
+            }
+        });
+        let first = read.lock_elem(handles[0]).unwrap();
+        assert!(elems.try_write(handles[0]).unwrap().is_none());
+        assert!(elems.try_write(handles[1]).unwrap().is_some());
+        drop(first);
+        let values: Vec<_> =
+            handles.iter().map(|&handle| *read.read_elem(handle).unwrap()).collect();
+        assert_eq!(vec![0, 10, 20, 30, 40, 50, 60, 70], values);
+    }
+}
@@ -0,0 +1,212 @@
+use std::ops::{Deref, DerefMut};
+
+use parking_lot::{MappedRwLockReadGuard, RwLockReadGuard, RwLockWriteGuard};
+
+use super::*;
+
+/// Read guard of a single element that keeps the locks of its arena, see [`VArenaReadGuard::map`].
+pub struct MappedVArenaReadGuard<'a, 'id, T: ?Sized, H: Header> {
+    value: MappedRwLockReadGuard<'a, T>,
+    _port: RwLockReadGuard<'a, (Id<'id>, H)>,
+}
+impl<'a, 'id, T: ?Sized, H: Header> MappedVArenaReadGuard<'a, 'id, T, H> {
+    fn new<'man, K, C>(
+        manager: RwLockReadGuard<'a, ManagerCell<'man, K, C>>,
+        port: RwLockReadGuard<'a, (Id<'id>, H)>,
+        project: impl for<'x> FnOnce(&'x Manager<'man, K, C>) -> &'x T,
+    ) -> Self
+    where
+        GlobalConfig<K, C>: Config,
+    {
+        let value = RwLockReadGuard::map(manager, |manager| {
+            // SAFETY: manager always holds a valid value
+            project(unsafe { manager.get().as_ref().unwrap_unchecked() })
+        });
+        Self { value, _port: port }
+    }
+}
+impl<T: ?Sized, H: Header> Deref for MappedVArenaReadGuard<'_, '_, T, H> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+/// Write guard of a single element that keeps the locks of its arena, see [`VArenaWriteGuard::map_mut`].
+pub struct MappedVArenaWriteGuard<'a, 'id, T: ?Sized, H: Header> {
+    // the manager is only read locked, write access comes from the port lock
+    value: MappedRwLockReadGuard<'a, UnsafeCell<T>>,
+    _port: RwLockWriteGuard<'a, (Id<'id>, H)>,
+}
+impl<'a, 'id, T: ?Sized, H: Header> MappedVArenaWriteGuard<'a, 'id, T, H> {
+    fn new<'man, K, C>(
+        manager: RwLockReadGuard<'a, ManagerCell<'man, K, C>>,
+        port: RwLockWriteGuard<'a, (Id<'id>, H)>,
+        project: impl for<'x> FnOnce(&'x mut Manager<'man, K, C>) -> &'x mut T,
+    ) -> Self
+    where
+        GlobalConfig<K, C>: Config,
+    {
+        let value = RwLockReadGuard::map(manager, |manager| {
+            // SAFETY: manager always holds a valid value
+            &*UnsafeCell::from_mut(project(unsafe { manager.get().as_mut().unwrap_unchecked() }))
+        });
+        Self { value, _port: port }
+    }
+}
+impl<T: ?Sized, H: Header> Deref for MappedVArenaWriteGuard<'_, '_, T, H> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the element can only be accessed through this guard
+        unsafe { &*self.value.get() }
+    }
+}
+impl<T: ?Sized, H: Header> DerefMut for MappedVArenaWriteGuard<'_, '_, T, H> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the element can only be accessed through this guard
+        unsafe { &mut *self.value.get() }
+    }
+}
+
+fn checked<T>(result: MResult<T>) -> T {
+    result.unwrap_or_else(|_| unreachable!("handle was checked before mapping"))
+}
+
+impl<'a, 'id, 'man, T, const REUSE: bool, H, V>
+    VArenaReadGuard<'a, 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
+where
+    H: Header,
+    GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: Store<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
+        >,
+{
+    /// Turns this into a guard of the element of `handle`.
+    pub fn map(
+        self,
+        handle: VHandle<'id, T>,
+    ) -> Result<MappedVArenaReadGuard<'a, 'id, T, H>, (Self, ArenaError)> {
+        if let Err(err) = self.get(handle).map(drop) {
+            return Err((self, err));
+        }
+        Ok(MappedVArenaReadGuard::new(self.manager, self.port, |manager| {
+            checked(manager.get(map_handle!(handle<T> 'id -> 'man)))
+        }))
+    }
+}
+impl<'a, 'id, 'man, U, const REUSE: bool, H, V>
+    VArenaReadGuard<'a, 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, H>
+where
+    U: RawBytes,
+    H: Header,
+    GlobalConfig<Slices<U>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: MultiStore<U>,
+            Manager<'x> = VManager<'x, Slices<U>, Versioned<REUSE, H, V>>,
+        >,
+{
+    /// Turns this into a guard of the slice of `handle`.
+    pub fn map<T>(
+        self,
+        handle: VHandle<'id, [T]>,
+    ) -> Result<MappedVArenaReadGuard<'a, 'id, [T], H>, (Self, ArenaError)> {
+        if let Err(err) = self.get(handle).map(drop) {
+            return Err((self, err));
+        }
+        Ok(MappedVArenaReadGuard::new(self.manager, self.port, |manager| {
+            checked(manager.get(map_handle!(handle<[T]> 'id -> 'man)))
+        }))
+    }
+}
+impl<'a, 'id, 'man, U, const REUSE: bool, H, V>
+    VArenaReadGuard<'a, 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, H>
+where
+    U: RawBytes,
+    H: Header,
+    GlobalConfig<Mixed<U>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: MultiStore<U>,
+            Manager<'x> = VManager<'x, Mixed<U>, Versioned<REUSE, H, V>>,
+        >,
+{
+    /// Turns this into a guard of the element of `handle`.
+    pub fn map<T>(
+        self,
+        handle: VHandle<'id, T>,
+    ) -> Result<MappedVArenaReadGuard<'a, 'id, T, H>, (Self, ArenaError)> {
+        if let Err(err) = self.get(handle).map(drop) {
+            return Err((self, err));
+        }
+        Ok(MappedVArenaReadGuard::new(self.manager, self.port, |manager| {
+            checked(manager.get(map_handle!(handle<T> 'id -> 'man)))
+        }))
+    }
+}
+impl<'a, 'id, 'man, T, const REUSE: bool, H, V>
+    VArenaWriteGuard<'a, 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
+where
+    H: Header,
+    GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: Store<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
+        >,
+{
+    /// Turns this into a mutable guard of the element of `handle`.
+    pub fn map_mut(
+        self,
+        handle: VHandle<'id, T>,
+    ) -> Result<MappedVArenaWriteGuard<'a, 'id, T, H>, (Self, ArenaError)> {
+        if let Err(err) = self.get(handle).map(drop) {
+            return Err((self, err));
+        }
+        Ok(MappedVArenaWriteGuard::new(self.manager, self.port, |manager| {
+            checked(manager.get_mut(map_handle!(handle<T> 'id -> 'man)))
+        }))
+    }
+}
+impl<'a, 'id, 'man, U, const REUSE: bool, H, V>
+    VArenaWriteGuard<'a, 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, H>
+where
+    U: RawBytes,
+    H: Header,
+    GlobalConfig<Slices<U>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: MultiStore<U>,
+            Manager<'x> = VManager<'x, Slices<U>, Versioned<REUSE, H, V>>,
+        >,
+{
+    /// Turns this into a mutable guard of the slice of `handle`.
+    pub fn map_mut<T>(
+        self,
+        handle: VHandle<'id, [T]>,
+    ) -> Result<MappedVArenaWriteGuard<'a, 'id, [T], H>, (Self, ArenaError)> {
+        if let Err(err) = self.get(handle).map(drop) {
+            return Err((self, err));
+        }
+        Ok(MappedVArenaWriteGuard::new(self.manager, self.port, |manager| {
+            checked(manager.get_mut(map_handle!(handle<[T]> 'id -> 'man)))
+        }))
+    }
+}
+impl<'a, 'id, 'man, U, const REUSE: bool, H, V>
+    VArenaWriteGuard<'a, 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, H>
+where
+    U: RawBytes,
+    H: Header,
+    GlobalConfig<Mixed<U>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: MultiStore<U>,
+            Manager<'x> = VManager<'x, Mixed<U>, Versioned<REUSE, H, V>>,
+        >,
+{
+    /// Turns this into a mutable guard of the element of `handle`.
+    pub fn map_mut<T>(
+        self,
+        handle: VHandle<'id, T>,
+    ) -> Result<MappedVArenaWriteGuard<'a, 'id, T, H>, (Self, ArenaError)> {
+        if let Err(err) = self.get(handle).map(drop) {
+            return Err((self, err));
+        }
+        Ok(MappedVArenaWriteGuard::new(self.manager, self.port, |manager| {
+            checked(manager.get_mut(map_handle!(handle<T> 'id -> 'man)))
+        }))
+    }
+}
@@ -1,18 +1,69 @@
+#[cfg(feature = "async")]
+mod async_arena;
 mod collection;
 mod guard;
 mod handle;
-use std::{cell::UnsafeCell, sync::Arc};
+mod local;
+mod locked;
+mod mapped;
+mod shard;
+mod transaction;
+use std::{
+    cell::UnsafeCell,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+#[cfg(feature = "async")]
+pub use async_arena::*;
 pub use collection::*;
 use generativity::{Guard, Id};
 pub use guard::*;
 pub use handle::*;
+pub use local::*;
+pub use locked::*;
+pub use mapped::*;
 use parking_lot::RwLock;
+pub use shard::*;
+pub use transaction::*;
 
 use super::*;
 use crate::alloc::{manager::*, store::*};
 
-type ManagerCell<'man, K, C> = UnsafeCell<Manager<'man, K, C>>;
+/// Manager shared by all ports of an arena, access to it is synchronized by the locks of the ports.
+struct ManagerCell<'man, K, C>(UnsafeCell<Manager<'man, K, C>>)
+where
+    GlobalConfig<K, C>: Config;
+// SAFETY: entries are only written through handles of a single port while its lock is held,
+// everything else needs the manager lock
+unsafe impl<K, C> Sync for ManagerCell<'_, K, C>
+where
+    GlobalConfig<K, C>: Config,
+    for<'x> Manager<'x, K, C>: Send + Sync,
+{
+}
+impl<'man, K, C> ManagerCell<'man, K, C>
+where
+    GlobalConfig<K, C>: Config,
+{
+    fn new(manager: Manager<'man, K, C>) -> Self {
+        Self(UnsafeCell::new(manager))
+    }
+    fn get(&self) -> *mut Manager<'man, K, C> {
+        self.0.get()
+    }
+    fn get_mut(&mut self) -> &mut Manager<'man, K, C> {
+        self.0.get_mut()
+    }
+}
+impl<K, C> std::fmt::Debug for ManagerCell<'_, K, C>
+where
+    GlobalConfig<K, C>: Config,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManagerCell").finish_non_exhaustive()
+    }
+}
 type ArcLock<T> = Arc<RwLock<T>>;
 
 // TODO: should there be on_insert, on_remove, on_move handlers?
@@ -79,6 +130,25 @@ where
     manager: ArcLock<ManagerCell<'man, K, C>>,
     port:    ArcLock<(Id<'id>, H)>,
 }
+/// Checks that the port at `other`, which has `owners` references, can be joined into `this`.
+fn check_join<T, O>(this: *const T, other: *const O, owners: usize) -> AResult<()> {
+    // ports of different brands have different types, so only their addresses are compared
+    if this.cast::<()>() == other.cast::<()>() {
+        return Err(ArenaError::BadJoin("port can't be joined with itself"));
+    }
+    if owners != 1 {
+        return Err(ArenaError::BadJoin("port is still shared"));
+    }
+    Ok(())
+}
+/// Merges the header of `other` into `this`, the ports have to be checked by [`check_join`].
+fn merge_ports<'id, 'other, H: Header>(
+    this: &mut (Id<'id>, H),
+    other: &mut (Id<'other>, H),
+) -> HandleMap<'other, 'id> {
+    this.1.merge(&mut other.1);
+    HandleMap { _from: other.0, _to: this.0 }
+}
 impl<'id, 'man, K, const REUSE: bool, H, V> Arena<'id, 'man, K, Versioned<REUSE, H, V>>
 where
     H: Header,
@@ -93,42 +163,371 @@ where
             port:    Arc::new(RwLock::new((guard.into(), H::default()))),
         })
     }
+    /// Merges the port of `other` into the port of `self`,
+    /// the returned map converts handles of `other` to handles of `self`.
+    ///
+    /// Fails and returns `other` if it is the port of `self`
+    /// or if the port is still shared, as its handles could be used through the other owner.
+    #[expect(clippy::type_complexity)]
+    pub fn join<'other>(
+        &self,
+        other: Arena<'other, 'man, K, Versioned<REUSE, H, V>>,
+    ) -> Result<HandleMap<'other, 'id>, (Arena<'other, 'man, K, Versioned<REUSE, H, V>>, ArenaError)>
+    {
+        let (this, port) = (Arc::as_ptr(&self.0.port), Arc::as_ptr(&other.0.port));
+        if let Err(err) = check_join(this, port, Arc::strong_count(&other.0.port)) {
+            return Err((other, err));
+        }
+        // SAFETY: `other` owns the only reference to its port, which is dropped here,
+        // so no handle of `other` can be accessed afterwards
+        Ok(unsafe { self.join_unchecked(other) })
+    }
+    /// [`Arena::join`] without checking the ports.
     /// # Safety
     /// After this call accessing `VHandle<'other, _>` causes undefined behaviour.
     /// This can deadlock if `self` is the same as `other`.
-    /// Making this safe requires `H` to carry additional data and thus is not being done by default.
-    pub unsafe fn join<'other>(
+    pub unsafe fn join_unchecked<'other>(
         &self,
-        other: VArena<'other, 'man, K, Versioned<REUSE, H, V>, H>,
+        other: Arena<'other, 'man, K, Versioned<REUSE, H, V>>,
     ) -> HandleMap<'other, 'id> {
-        let mut this_port = self.0.port.write();
-        let mut other_port = other.port.write();
-        this_port.1.merge(&mut other_port.1);
-        HandleMap { _from: other_port.0, _to: this_port.0 }
+        merge_ports(&mut self.0.port.write(), &mut other.0.port.write())
     }
     pub fn read(&self) -> VArenaReadGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
         VArenaReadGuard { manager: self.0.manager.read(), port: self.0.port.read() }
     }
+    /// Locks the manager exclusively, this blocks every other port until the guard is dropped.
+    ///
+    /// Other ports cannot write while this guard is alive,
+    /// so it can visit all entries of the manager, see [`VArenaExclusiveReadGuard::par_iter`].
+    pub fn read_exclusive(
+        &self,
+    ) -> VArenaExclusiveReadGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        VArenaExclusiveReadGuard { manager: self.0.manager.write(), port: self.0.port.read() }
+    }
     pub fn write(&mut self) -> VArenaWriteGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
         VArenaWriteGuard { manager: self.0.manager.read(), port: self.0.port.write() }
     }
     pub fn alloc(&mut self) -> VArenaAllocGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H> {
         VArenaAllocGuard { manager: self.0.manager.upgradable_read(), port: self.0.port.write() }
     }
+    pub fn try_read(&self) -> Option<VArenaReadGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H>> {
+        Some(VArenaReadGuard {
+            manager: self.0.manager.try_read()?,
+            port:    self.0.port.try_read()?,
+        })
+    }
+    pub fn try_read_for(
+        &self,
+        timeout: Duration,
+    ) -> Option<VArenaReadGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H>> {
+        self.try_read_until(Instant::now() + timeout)
+    }
+    /// Waits for both locks until `deadline`, the time spent waiting for the manager lock
+    /// counts towards the port lock.
+    pub fn try_read_until(
+        &self,
+        deadline: Instant,
+    ) -> Option<VArenaReadGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H>> {
+        Some(VArenaReadGuard {
+            manager: self.0.manager.try_read_until(deadline)?,
+            port:    self.0.port.try_read_until(deadline)?,
+        })
+    }
+    pub fn try_write(
+        &mut self,
+    ) -> Option<VArenaWriteGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H>> {
+        Some(VArenaWriteGuard {
+            manager: self.0.manager.try_read()?,
+            port:    self.0.port.try_write()?,
+        })
+    }
+    pub fn try_write_for(
+        &mut self,
+        timeout: Duration,
+    ) -> Option<VArenaWriteGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H>> {
+        self.try_write_until(Instant::now() + timeout)
+    }
+    /// See [`Arena::try_read_until`].
+    pub fn try_write_until(
+        &mut self,
+        deadline: Instant,
+    ) -> Option<VArenaWriteGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H>> {
+        Some(VArenaWriteGuard {
+            manager: self.0.manager.try_read_until(deadline)?,
+            port:    self.0.port.try_write_until(deadline)?,
+        })
+    }
+    pub fn try_alloc(
+        &mut self,
+    ) -> Option<VArenaAllocGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H>> {
+        Some(VArenaAllocGuard {
+            manager: self.0.manager.try_upgradable_read()?,
+            port:    self.0.port.try_write()?,
+        })
+    }
+    pub fn try_alloc_for(
+        &mut self,
+        timeout: Duration,
+    ) -> Option<VArenaAllocGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H>> {
+        self.try_alloc_until(Instant::now() + timeout)
+    }
+    /// See [`Arena::try_read_until`].
+    pub fn try_alloc_until(
+        &mut self,
+        deadline: Instant,
+    ) -> Option<VArenaAllocGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, H>> {
+        Some(VArenaAllocGuard {
+            manager: self.0.manager.try_upgradable_read_until(deadline)?,
+            port:    self.0.port.try_write_until(deadline)?,
+        })
+    }
+    /// Owned version of [`Arena::read`] that does not borrow the arena,
+    /// so it can be held across `.await` points.
+    ///
+    /// The guard is still bound to the `'id` and `'man` brands, so it is never `'static`
+    /// and can only be moved into scoped threads or tasks.
+    /// Moving it to another thread needs the `send-guard` feature,
+    /// which enables `send_guard` of parking_lot for every user of it.
+    ///
+    /// Locking this port again while the guard is alive can deadlock.
+    pub fn read_arc(&self) -> ArcVArenaReadGuard<'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        ArcVArenaReadGuard { manager: self.0.manager.read_arc(), port: self.0.port.read_arc() }
+    }
+    /// Owned version of [`Arena::write`], see [`Arena::read_arc`].
+    pub fn write_arc(&mut self) -> ArcVArenaWriteGuard<'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        ArcVArenaWriteGuard { manager: self.0.manager.read_arc(), port: self.0.port.write_arc() }
+    }
+    /// Owned version of [`Arena::alloc`], see [`Arena::read_arc`].
+    pub fn alloc_arc(&mut self) -> ArcVArenaAllocGuard<'id, 'man, K, Versioned<REUSE, H, V>, H> {
+        ArcVArenaAllocGuard {
+            manager: self.0.manager.upgradable_read_arc(),
+            port:    self.0.port.write_arc(),
+        }
+    }
 }
 impl<'id, 'man, K, const REUSE: bool, H, V> Arena<'id, 'man, K, Versioned<REUSE, H, V>>
 where
     H: Header,
+    V: Tracking,
     GlobalConfig<K, Versioned<REUSE, H, V>>: for<'x, 'y> Config<
-            Store: Default,
+            Store: Default + Resizable,
             Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>,
             Arena<'y, 'x> = VArena<'y, 'x, K, Versioned<REUSE, H, V>, H>,
         >,
 {
     pub fn new(guard: Guard<'id>, manager_guard: Guard<'man>, header: H) -> Self {
+        Self::with_manager(guard, Manager::new(manager_guard), header)
+    }
+}
+impl<'id, 'man, K, const REUSE: bool, H, V> Arena<'id, 'man, K, Versioned<REUSE, H, V>>
+where
+    H: Header,
+    GlobalConfig<K, Versioned<REUSE, H, V>>: for<'x, 'y> Config<
+            Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>,
+            Arena<'y, 'x> = VArena<'y, 'x, K, Versioned<REUSE, H, V>, H>,
+        >,
+{
+    /// Creates an arena around an empty manager,
+    /// e.g. one created by [`Manager::with_store`].
+    pub fn with_manager(
+        guard: Guard<'id>,
+        manager: Manager<'man, K, Versioned<REUSE, H, V>>,
+        header: H,
+    ) -> Self {
         Self(VArena {
-            manager: Arc::new(RwLock::new(UnsafeCell::new(Manager::new(manager_guard)))),
+            manager: Arc::new(RwLock::new(ManagerCell::new(manager))),
             port:    Arc::new(RwLock::new((guard.into(), header))),
         })
     }
 }
+impl<'id, 'man, T, H, V> Arena<'id, 'man, Typed<T>, Versioned<true, H, V>>
+where
+    H: Header,
+    GlobalConfig<Typed<T>, Versioned<true, H, V>>: for<'x, 'y> Config<
+            Store: ReusableStore<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<true, H, V>>,
+            Arena<'y, 'x> = VArena<'y, 'x, Typed<T>, Versioned<true, H, V>, H>,
+        >,
+{
+    /// Collection needs the manager for itself, as handles of other ports can't be traced
+    /// and marking would race with their write guards.
+    fn unshared_manager(&mut self) -> AResult<&mut Manager<'man, Typed<T>, Versioned<true, H, V>>> {
+        Arc::get_mut(&mut self.0.manager)
+            .map(|manager| manager.get_mut().get_mut())
+            .ok_or(ArenaError::SharedManager)
+    }
+    /// Removes all entries that are not reachable from `roots`, see [`Manager::collect`].
+    ///
+    /// Fails if the manager is shared with another port or an owned guard.
+    pub fn collect(&mut self, roots: impl IntoIterator<Item = VHandle<'id, T>>) -> AResult<Length>
+    where
+        T: Trace<'id>,
+    {
+        let manager = self.unshared_manager()?;
+        let collector = manager.begin_collect_with(roots);
+        Ok(manager.finish_collect_with(collector, []))
+    }
+    /// Starts an incremental collection, see [`Collector`].
+    ///
+    /// The arena can be written between the steps, every step fails if the manager got shared.
+    pub fn begin_collect(
+        &mut self,
+        roots: impl IntoIterator<Item = VHandle<'id, T>>,
+    ) -> AResult<Collector<'id, T>> {
+        Ok(self.unshared_manager()?.begin_collect_with(roots))
+    }
+    /// Marks up to `budget` entries, returns `true` when there is no marking work left.
+    pub fn collect_step(
+        &mut self,
+        collector: &mut Collector<'id, T>,
+        budget: usize,
+    ) -> AResult<bool>
+    where
+        T: Trace<'id>,
+    {
+        Ok(self.unshared_manager()?.collect_step_with(collector, budget))
+    }
+    /// See [`Manager::finish_collect`].
+    pub fn finish_collect(
+        &mut self,
+        collector: Collector<'id, T>,
+        roots: impl IntoIterator<Item = VHandle<'id, T>>,
+    ) -> AResult<Length>
+    where
+        T: Trace<'id>,
+    {
+        Ok(self.unshared_manager()?.finish_collect_with(collector, roots))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::ops::Deref;
+
+    use generativity::make_guard;
+
+    use super::*;
+
+    #[test]
+    fn joins_only_unshared_ports() {
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(man);
+        let a = Arena::<Typed<u32>, Versioned<true>>::new(a, man, Headless::default());
+        let mut b = a.split(b);
+        let handle = b.alloc().insert(7).unwrap();
+        let shared = Arena::<Typed<u32>, Versioned<true>>(VArena {
+            manager: b.0.manager.clone(),
+            port:    b.0.port.clone(),
+        });
+        let Err((b, err)) = a.join(b) else { panic!("shared port should not be joined") };
+        assert_eq!(ArenaError::BadJoin("port is still shared"), err);
+        drop(shared);
+        let Ok(map) = a.join(b) else { panic!("unshared port should be joined") };
+        let handle = map.apply::<VHandle<u32>>(handle);
+        assert_eq!(Ok(&7), a.read().get(handle));
+    }
+
+    #[test]
+    fn owned_guards_outlive_the_arena() {
+        make_guard!(a);
+        make_guard!(man);
+        let mut a = Arena::<Typed<u32>, Versioned<true>>::new(a, man, Headless::default());
+        let mut alloc = a.alloc_arc();
+        let handle = alloc.insert(7).unwrap();
+        drop(a);
+        *alloc.get_mut(handle).unwrap() += 1;
+        let write = alloc.downgrade();
+        assert_eq!(Ok(&8), write.get(handle));
+    }
+
+    #[cfg(feature = "send-guard")]
+    #[test]
+    fn owned_guards_are_send() {
+        fn assert_send<T: Send>() {}
+        type Kind = Typed<u32>;
+        type Config = Versioned<true>;
+        assert_send::<ArcVArenaReadGuard<'static, 'static, Kind, Config, Headless>>();
+        assert_send::<ArcVArenaWriteGuard<'static, 'static, Kind, Config, Headless>>();
+        assert_send::<ArcVArenaAllocGuard<'static, 'static, Kind, Config, Headless>>();
+
+        make_guard!(a);
+        make_guard!(man);
+        let mut a = Arena::<Kind, Config>::new(a, man, Headless::default());
+        let mut alloc = a.alloc_arc();
+        let handle = std::thread::scope(|scope| {
+            scope.spawn(move || alloc.insert(5).unwrap()).join().unwrap()
+        });
+        assert_eq!(Ok(&5), a.read().get(handle));
+    }
+
+    #[test]
+    fn try_locks_fail_while_allocating() {
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(man);
+        let mut a = Arena::<Typed<u32>, Versioned<true>>::new(a, man, Headless::default());
+        let mut b = a.split(b);
+        let alloc = a.alloc();
+        assert!(b.try_alloc().is_none());
+        assert!(b.try_alloc_for(Duration::from_millis(1)).is_none());
+        let Some(write) = b.try_write() else { panic!("writing should not be blocked") };
+        let Err(write) = write.try_upgrade() else { panic!("upgrade should be blocked") };
+        drop(alloc);
+        let Ok(mut alloc) = write.try_upgrade() else { panic!("upgrade should not be blocked") };
+        let handle = alloc.insert(3).unwrap();
+        drop(alloc);
+        assert_eq!(Ok(&3), b.try_read().unwrap().get(handle));
+    }
+
+    #[test]
+    fn mapped_guards_keep_the_locks() {
+        fn first<'a, 'id>(
+            arena: &'a Arena<'id, '_, Typed<u32>, Versioned<true>>,
+            handle: VHandle<'id, u32>,
+        ) -> impl Deref<Target = u32> + 'a {
+            arena.read().map(handle).unwrap_or_else(|_| panic!("handle should be valid"))
+        }
+        make_guard!(a);
+        make_guard!(man);
+        let mut a = Arena::<Typed<u32>, Versioned<true>>::new(a, man, Headless::default());
+        let handle = a.alloc().insert(1).unwrap();
+        let Ok(mut value) = a.write().map_mut(handle) else { panic!("handle should be valid") };
+        *value += 1;
+        drop(value);
+        assert_eq!(2, *first(&a, handle));
+        a.alloc().remove(handle).unwrap();
+        assert!(a.read().map(handle).is_err());
+    }
+
+    #[test]
+    fn collects_only_unshared_managers() {
+        #[derive(Debug)]
+        struct Node<'id>(Option<VHandle<'id, Node<'id>>>);
+        impl<'id> Trace<'id> for Node<'id> {
+            fn trace(&self, tracer: &mut Tracer<'_, 'id, Self>) {
+                self.0.into_iter().for_each(|handle| tracer.visit(handle));
+            }
+        }
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(man);
+        let mut a = Arena::<Typed<Node>, Versioned<true>>::new(a, man, Headless::default());
+        let (root, garbage) = {
+            let mut alloc = a.alloc();
+            (alloc.insert(Node(None)).unwrap(), alloc.insert(Node(None)).unwrap())
+        };
+        let b = a.split(b);
+        assert_eq!(Err(ArenaError::SharedManager), a.collect([root]));
+        assert_eq!(Some(ArenaError::SharedManager), a.begin_collect([root]).err());
+        drop(b);
+        let mut collector = a.begin_collect([root]).unwrap();
+        assert_eq!(Ok(true), a.collect_step(&mut collector, 1));
+        let read = a.read_arc();
+        assert_eq!(Err(ArenaError::SharedManager), a.collect_step(&mut collector, 1));
+        drop(read);
+        assert_eq!(Ok(1), a.finish_collect(collector, []));
+        assert!(a.read().get(root).is_ok());
+        assert!(a.read().get(garbage).is_err(), "unreachable entry should be removed");
+    }
+}
@@ -0,0 +1,918 @@
+use std::{
+    array,
+    fmt::Debug,
+    mem::transmute,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use parking_lot::{
+    ArcRwLockReadGuard, ArcRwLockUpgradableReadGuard, ArcRwLockWriteGuard, RawRwLock, RwLock,
+    RwLockReadGuard, RwLockUpgradableReadGuard, RwLockWriteGuard,
+};
+
+use super::*;
+
+/// Handle into a [`ShardedVArena`], which remembers the shard holding its element.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct SHandle<'id, T: ?Sized> {
+    shard:  u32,
+    handle: VHandle<'id, T>,
+}
+impl<T: ?Sized> Clone for SHandle<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized> Copy for SHandle<'_, T> {}
+impl<'id, T: ?Sized> SHandle<'id, T> {
+    pub fn shard(&self) -> usize {
+        self.shard as usize
+    }
+    /// Handle within its shard, e.g. to index the rows of [`ShardedVArenaReadGuard::view`].
+    pub fn handle(&self) -> VHandle<'id, T> {
+        self.handle
+    }
+}
+impl<T: ?Sized> MappableHandle for SHandle<'_, T> {
+    type Container<'id> = SHandle<'id, T>;
+    type Data = T;
+
+    fn handles<'id>(target: &Self::Container<'id>, f: &mut impl FnMut(VHandle<'id, T>)) {
+        f(target.handle)
+    }
+    fn update<'from, 'to>(
+        from: Self::Container<'from>,
+        f: &mut impl FnMut(VHandle<'from, T>) -> VHandle<'to, T>,
+    ) -> Self::Container<'to> {
+        SHandle { shard: from.shard, handle: f(from.handle) }
+    }
+}
+
+/// Counts an allocator towards the contention of its shard until it is dropped.
+#[derive(Debug)]
+struct Claim<const N: usize> {
+    allocators: Arc<[AtomicUsize; N]>,
+    shard:      usize,
+}
+impl<const N: usize> Claim<N> {
+    fn new(allocators: &Arc<[AtomicUsize; N]>, shard: usize) -> Self {
+        allocators[shard].fetch_add(1, Ordering::Relaxed);
+        Self { allocators: allocators.clone(), shard }
+    }
+}
+impl<const N: usize> Drop for Claim<N> {
+    fn drop(&mut self) {
+        self.allocators[self.shard].fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Versioned arena split into `N` independently locked managers.
+///
+/// Ports of different shards can allocate at the same time,
+/// [`ShardedVArena::alloc`] picks the shard with the fewest allocators holding or waiting for it.
+/// All shards share the `'man` brand, handles are kept apart by their shard index instead.
+pub struct ShardedVArena<'id, 'man, K, C, const N: usize, H: Header = Headless>
+where
+    GlobalConfig<K, C>: Config,
+{
+    shards:     [ArcLock<ManagerCell<'man, K, C>>; N],
+    /// Allocators holding or waiting for each shard, shared by all ports.
+    allocators: Arc<[AtomicUsize; N]>,
+    port:       ArcLock<(Id<'id>, H)>,
+    next:       usize,
+}
+impl<'id, 'man, K, const REUSE: bool, H, V, const N: usize>
+    ShardedVArena<'id, 'man, K, Versioned<REUSE, H, V>, N, H>
+where
+    H: Header,
+    V: Tracking,
+    GlobalConfig<K, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: Default + Resizable,
+            Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>,
+        >,
+{
+    pub fn new(guard: Guard<'id>, manager_guard: Guard<'man>, header: H) -> Self {
+        const { assert!(N > 0 && N <= u32::MAX as usize, "shard count has to fit into a handle") };
+        let id = manager_guard.into();
+        Self {
+            shards:     array::from_fn(|_| {
+                // SAFETY: handles remember their shard, so no shard sees handles of another shard
+                let guard = unsafe { Guard::new(id) };
+                Arc::new(RwLock::new(ManagerCell::new(Manager::new(guard))))
+            }),
+            allocators: Arc::new(array::from_fn(|_| AtomicUsize::new(0))),
+            port:       Arc::new(RwLock::new((guard.into(), header))),
+            next:       0,
+        }
+    }
+}
+impl<'id, 'man, K, const REUSE: bool, H, V, const N: usize>
+    ShardedVArena<'id, 'man, K, Versioned<REUSE, H, V>, N, H>
+where
+    H: Header,
+    GlobalConfig<K, Versioned<REUSE, H, V>>:
+        for<'x> Config<Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
+{
+    pub fn split<'new>(
+        &self,
+        guard: Guard<'new>,
+    ) -> ShardedVArena<'new, 'man, K, Versioned<REUSE, H, V>, N, H> {
+        ShardedVArena {
+            shards:     self.shards.clone(),
+            allocators: self.allocators.clone(),
+            port:       Arc::new(RwLock::new((guard.into(), H::default()))),
+            next:       0,
+        }
+    }
+    /// Merges the port of `other` into the port of `self`, see [`Arena::join`].
+    #[expect(clippy::type_complexity)]
+    pub fn join<'other>(
+        &self,
+        other: ShardedVArena<'other, 'man, K, Versioned<REUSE, H, V>, N, H>,
+    ) -> Result<
+        HandleMap<'other, 'id>,
+        (ShardedVArena<'other, 'man, K, Versioned<REUSE, H, V>, N, H>, ArenaError),
+    > {
+        let (this, port) = (Arc::as_ptr(&self.port), Arc::as_ptr(&other.port));
+        if let Err(err) = check_join(this, port, Arc::strong_count(&other.port)) {
+            return Err((other, err));
+        }
+        Ok(merge_ports(&mut self.port.write(), &mut other.port.write()))
+    }
+    /// Shards ordered by the number of allocators holding or waiting for them,
+    /// ties are broken in round-robin order starting after the shard of the last allocation.
+    fn by_contention(&self) -> [usize; N] {
+        let load: [_; N] = array::from_fn(|shard| self.allocators[shard].load(Ordering::Relaxed));
+        let mut order = array::from_fn(|offset| (self.next + offset) % N);
+        order.sort_by_key(|&shard| load[shard]);
+        order
+    }
+    /// Takes read locks on all `N` shards, so it waits for allocations that grow any shard.
+    pub fn read(&self) -> ShardedVArenaReadGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H> {
+        ShardedVArenaReadGuard {
+            managers: array::from_fn(|shard| self.shards[shard].read()),
+            port:     self.port.read(),
+        }
+    }
+    /// Takes read locks on all `N` shards like [`ShardedVArena::read`].
+    pub fn write(
+        &mut self,
+    ) -> ShardedVArenaWriteGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H> {
+        ShardedVArenaWriteGuard {
+            managers: array::from_fn(|shard| self.shards[shard].read()),
+            port:     self.port.write(),
+        }
+    }
+    /// Allocates from the shard with the fewest allocators holding or waiting for it.
+    /// Free shards are preferred, between equally contended shards the choice rotates.
+    /// Blocks on the least contended shard if every shard is allocated from.
+    pub fn alloc(
+        &mut self,
+    ) -> ShardedVArenaAllocGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H> {
+        let port = self.port.write();
+        let order = self.by_contention();
+        let (claim, manager) =
+            try_claim(&self.shards, &self.allocators, &order, |shard| shard.try_upgradable_read())
+                .unwrap_or_else(|| {
+                    let claim = Claim::new(&self.allocators, order[0]);
+                    (claim, self.shards[order[0]].upgradable_read())
+                });
+        self.next = (claim.shard + 1) % N;
+        ShardedVArenaAllocGuard { manager, port, claim }
+    }
+    /// Allocates from a specific shard, e.g. to remove an element of [`SHandle::shard`].
+    /// # Panics
+    /// Panics if `shard` is out of range.
+    pub fn alloc_in(
+        &mut self,
+        shard: usize,
+    ) -> ShardedVArenaAllocGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H> {
+        let port = self.port.write();
+        let claim = Claim::new(&self.allocators, shard);
+        ShardedVArenaAllocGuard { manager: self.shards[shard].upgradable_read(), port, claim }
+    }
+    pub fn try_read(
+        &self,
+    ) -> Option<ShardedVArenaReadGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H>> {
+        Some(ShardedVArenaReadGuard {
+            managers: lock_all(|shard| self.shards[shard].try_read())?,
+            port:     self.port.try_read()?,
+        })
+    }
+    pub fn try_read_for(
+        &self,
+        timeout: Duration,
+    ) -> Option<ShardedVArenaReadGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H>> {
+        self.try_read_until(Instant::now() + timeout)
+    }
+    /// Waits for all locks until `deadline`, see [`Arena::try_read_until`].
+    pub fn try_read_until(
+        &self,
+        deadline: Instant,
+    ) -> Option<ShardedVArenaReadGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H>> {
+        Some(ShardedVArenaReadGuard {
+            managers: lock_all(|shard| self.shards[shard].try_read_until(deadline))?,
+            port:     self.port.try_read_until(deadline)?,
+        })
+    }
+    pub fn try_write(
+        &mut self,
+    ) -> Option<ShardedVArenaWriteGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H>> {
+        Some(ShardedVArenaWriteGuard {
+            managers: lock_all(|shard| self.shards[shard].try_read())?,
+            port:     self.port.try_write()?,
+        })
+    }
+    pub fn try_write_for(
+        &mut self,
+        timeout: Duration,
+    ) -> Option<ShardedVArenaWriteGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H>> {
+        self.try_write_until(Instant::now() + timeout)
+    }
+    /// See [`ShardedVArena::try_read_until`].
+    pub fn try_write_until(
+        &mut self,
+        deadline: Instant,
+    ) -> Option<ShardedVArenaWriteGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H>> {
+        Some(ShardedVArenaWriteGuard {
+            managers: lock_all(|shard| self.shards[shard].try_read_until(deadline))?,
+            port:     self.port.try_write_until(deadline)?,
+        })
+    }
+    /// Allocates from the least contended shard that no one allocates from, see [`ShardedVArena::alloc`].
+    pub fn try_alloc(
+        &mut self,
+    ) -> Option<ShardedVArenaAllocGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H>> {
+        let port = self.port.try_write()?;
+        let order = self.by_contention();
+        let (claim, manager) =
+            try_claim(&self.shards, &self.allocators, &order, |shard| shard.try_upgradable_read())?;
+        self.next = (claim.shard + 1) % N;
+        Some(ShardedVArenaAllocGuard { manager, port, claim })
+    }
+    pub fn try_alloc_for(
+        &mut self,
+        timeout: Duration,
+    ) -> Option<ShardedVArenaAllocGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H>> {
+        self.try_alloc_until(Instant::now() + timeout)
+    }
+    /// Waits on the least contended shard until `deadline` if every shard is allocated from,
+    /// see [`ShardedVArena::try_read_until`].
+    pub fn try_alloc_until(
+        &mut self,
+        deadline: Instant,
+    ) -> Option<ShardedVArenaAllocGuard<'_, 'id, 'man, K, Versioned<REUSE, H, V>, N, H>> {
+        let port = self.port.try_write_until(deadline)?;
+        let order = self.by_contention();
+        let (claim, manager) = match try_claim(&self.shards, &self.allocators, &order, |shard| {
+            shard.try_upgradable_read()
+        }) {
+            Some(claimed) => claimed,
+            None => {
+                let claim = Claim::new(&self.allocators, order[0]);
+                (claim, self.shards[order[0]].try_upgradable_read_until(deadline)?)
+            },
+        };
+        self.next = (claim.shard + 1) % N;
+        Some(ShardedVArenaAllocGuard { manager, port, claim })
+    }
+    /// Owned version of [`ShardedVArena::read`], see [`Arena::read_arc`].
+    pub fn read_arc(
+        &self,
+    ) -> ArcShardedVArenaReadGuard<'id, 'man, K, Versioned<REUSE, H, V>, N, H> {
+        ArcShardedVArenaReadGuard {
+            managers: array::from_fn(|shard| self.shards[shard].read_arc()),
+            port:     self.port.read_arc(),
+        }
+    }
+    /// Owned version of [`ShardedVArena::write`], see [`Arena::read_arc`].
+    pub fn write_arc(
+        &mut self,
+    ) -> ArcShardedVArenaWriteGuard<'id, 'man, K, Versioned<REUSE, H, V>, N, H> {
+        ArcShardedVArenaWriteGuard {
+            managers: array::from_fn(|shard| self.shards[shard].read_arc()),
+            port:     self.port.write_arc(),
+        }
+    }
+    /// Owned version of [`ShardedVArena::alloc`], see [`Arena::read_arc`].
+    pub fn alloc_arc(
+        &mut self,
+    ) -> ArcShardedVArenaAllocGuard<'id, 'man, K, Versioned<REUSE, H, V>, N, H> {
+        let port = self.port.write_arc();
+        let order = self.by_contention();
+        let (claim, manager) = try_claim(&self.shards, &self.allocators, &order, |shard| {
+            shard.try_upgradable_read_arc()
+        })
+        .unwrap_or_else(|| {
+            let claim = Claim::new(&self.allocators, order[0]);
+            (claim, self.shards[order[0]].upgradable_read_arc())
+        });
+        self.next = (claim.shard + 1) % N;
+        ArcShardedVArenaAllocGuard { manager, port, claim }
+    }
+}
+/// Takes the first shard of `order` that no one allocates from.
+fn try_claim<'a, S, G, const N: usize>(
+    shards: &'a [ArcLock<S>; N],
+    allocators: &Arc<[AtomicUsize; N]>,
+    order: &[usize],
+    lock: impl Fn(&'a ArcLock<S>) -> Option<G>,
+) -> Option<(Claim<N>, G)> {
+    order.iter().find_map(|&shard| {
+        let manager = lock(&shards[shard])?;
+        Some((Claim::new(allocators, shard), manager))
+    })
+}
+/// Locks every shard with `lock`, fails if any of them fails.
+fn lock_all<G, const N: usize>(lock: impl FnMut(usize) -> Option<G>) -> Option<[G; N]> {
+    let guards: Vec<_> = (0..N).map(lock).collect::<Option<_>>()?;
+    guards.try_into().ok()
+}
+
+#[derive(Debug)]
+pub struct ShardedVArenaReadGuard<'a, 'id, 'man, K, C, const N: usize, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    managers: [RwLockReadGuard<'a, ManagerCell<'man, K, C>>; N],
+    port:     RwLockReadGuard<'a, (Id<'id>, H)>,
+}
+#[derive(Debug)]
+pub struct ShardedVArenaWriteGuard<'a, 'id, 'man, K, C, const N: usize, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    managers: [RwLockReadGuard<'a, ManagerCell<'man, K, C>>; N],
+    port:     RwLockWriteGuard<'a, (Id<'id>, H)>,
+}
+/// Allocation guard of a single shard, elements of other shards can't be accessed through it.
+#[derive(Debug)]
+pub struct ShardedVArenaAllocGuard<'a, 'id, 'man, K, C, const N: usize, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    manager: RwLockUpgradableReadGuard<'a, ManagerCell<'man, K, C>>,
+    port:    RwLockWriteGuard<'a, (Id<'id>, H)>,
+    claim:   Claim<N>,
+}
+/// Owned version of [`ShardedVArenaReadGuard`], see [`ShardedVArena::read_arc`].
+#[derive(Debug)]
+pub struct ArcShardedVArenaReadGuard<'id, 'man, K, C, const N: usize, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    managers: [ArcRwLockReadGuard<RawRwLock, ManagerCell<'man, K, C>>; N],
+    port:     ArcRwLockReadGuard<RawRwLock, (Id<'id>, H)>,
+}
+/// Owned version of [`ShardedVArenaWriteGuard`], see [`ShardedVArena::write_arc`].
+#[derive(Debug)]
+pub struct ArcShardedVArenaWriteGuard<'id, 'man, K, C, const N: usize, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    managers: [ArcRwLockReadGuard<RawRwLock, ManagerCell<'man, K, C>>; N],
+    port:     ArcRwLockWriteGuard<RawRwLock, (Id<'id>, H)>,
+}
+/// Owned version of [`ShardedVArenaAllocGuard`], see [`ShardedVArena::alloc_arc`].
+pub struct ArcShardedVArenaAllocGuard<'id, 'man, K, C, const N: usize, H: Header>
+where
+    GlobalConfig<K, C>: Config,
+{
+    manager: ArcRwLockUpgradableReadGuard<RawRwLock, ManagerCell<'man, K, C>>,
+    port:    ArcRwLockWriteGuard<RawRwLock, (Id<'id>, H)>,
+    claim:   Claim<N>,
+}
+impl<K, C, const N: usize, H: Header + Debug> Debug
+    for ArcShardedVArenaAllocGuard<'_, '_, K, C, N, H>
+where
+    GlobalConfig<K, C>: Config,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArcShardedVArenaAllocGuard")
+            .field("shard", &self.claim.shard)
+            .field("port", &*self.port)
+            .finish_non_exhaustive()
+    }
+}
+macro_rules! shard {
+    (ref $this:ident[$shard:expr]) => {
+        // SAFETY: manager always holds a valid value
+        unsafe { $this.managers[$shard].get().as_ref().unwrap_unchecked() }
+    };
+    (mut $this:ident[$shard:expr]) => {
+        // SAFETY: manager always holds a valid value
+        unsafe { $this.managers[$shard].get().as_mut().unwrap_unchecked() }
+    };
+}
+macro_rules! impl_sharded_read {
+    ($type:ident $(<$lt:lifetime>)?) => {
+        impl<'id, 'man, K, C, const N: usize, H> $type<$($lt,)? 'id, 'man, K, C, N, H>
+        where
+            GlobalConfig<K, C>: Config,
+            H: Header,
+        {
+            pub fn header(&self) -> &H {
+                &self.port.1
+            }
+        }
+        impl<'id, 'man, T, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, N, H>
+        where
+            H: Header,
+            GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: Store<(Version, T)>,
+                    Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn get(&self, handle: SHandle<'id, T>) -> AResult<&T> {
+                let SHandle { shard, handle } = handle;
+                Ok(shard!(ref self[shard as usize]).get(map_handle!(handle<T> 'id -> 'man))?)
+            }
+        }
+        impl<'id, 'man, C, L, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, SoA<C, L>, Versioned<REUSE, H, V>, N, H>
+        where
+            C: Columns,
+            L: SoALayout,
+            H: Header,
+            GlobalConfig<SoA<C, L>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: SoAStore<Prefix<Version, C>, VHandle<'x, C>>,
+                    Manager<'x> = VManager<'x, SoA<C, L>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            /// Rows of `shard`, indexed by [`SHandle::handle`] of handles of that shard.
+            /// # Panics
+            /// Panics if `shard` is out of range.
+            pub fn view(&self, shard: usize) -> C::Ref<'_, VHandle<'id, C>> {
+                let view = shard!(ref self[shard]).view();
+                // SAFETY: self has the proper locks here
+                unsafe { transmute::<C::Ref<'_, VHandle<'man, C>>, C::Ref<'_, VHandle<'id, C>>>(view) }
+            }
+        }
+        impl<'id, 'man, U, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, N, H>
+        where
+            U: RawBytes,
+            H: Header,
+            GlobalConfig<Slices<U>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: MultiStore<U>,
+                    Manager<'x> = VManager<'x, Slices<U>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn get<T>(&self, handle: SHandle<'id, [T]>) -> AResult<&[T]> {
+                let SHandle { shard, handle } = handle;
+                Ok(shard!(ref self[shard as usize]).get(map_handle!(handle<[T]> 'id -> 'man))?)
+            }
+        }
+        impl<'id, 'man, U, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, N, H>
+        where
+            U: RawBytes,
+            H: Header,
+            GlobalConfig<Mixed<U>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: MultiStore<U>,
+                    Manager<'x> = VManager<'x, Mixed<U>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn get<T>(&self, handle: SHandle<'id, T>) -> AResult<&T> {
+                let SHandle { shard, handle } = handle;
+                Ok(shard!(ref self[shard as usize]).get(map_handle!(handle<T> 'id -> 'man))?)
+            }
+        }
+    };
+}
+impl_sharded_read!(ShardedVArenaReadGuard<'_>);
+impl_sharded_read!(ShardedVArenaWriteGuard<'_>);
+impl_sharded_read!(ArcShardedVArenaReadGuard);
+impl_sharded_read!(ArcShardedVArenaWriteGuard);
+macro_rules! impl_sharded_write {
+    ($type:ident $(<$lt:lifetime>)?) => {
+        impl<'id, 'man, K, C, const N: usize, H> $type<$($lt,)? 'id, 'man, K, C, N, H>
+        where
+            GlobalConfig<K, C>: Config,
+            H: Header,
+        {
+            pub fn header_mut(&mut self) -> &mut H {
+                &mut self.port.1
+            }
+        }
+        impl<'id, 'man, T, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, N, H>
+        where
+            H: Header,
+            GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: Store<(Version, T)>,
+                    Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn get_mut(&mut self, handle: SHandle<'id, T>) -> AResult<&mut T> {
+                let SHandle { shard, handle } = handle;
+                Ok(shard!(mut self[shard as usize]).get_mut(map_handle!(handle<T> 'id -> 'man))?)
+            }
+        }
+        impl<'id, 'man, C, L, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, SoA<C, L>, Versioned<REUSE, H, V>, N, H>
+        where
+            C: Columns,
+            L: SoALayout,
+            H: Header,
+            GlobalConfig<SoA<C, L>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: SoAStore<Prefix<Version, C>, VHandle<'x, C>>,
+                    Manager<'x> = VManager<'x, SoA<C, L>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            /// See [`ShardedVArenaReadGuard::view`].
+            pub fn view_mut(&mut self, shard: usize) -> C::Mut<'_, VHandle<'id, C>> {
+                let view = shard!(mut self[shard]).view_mut();
+                // SAFETY: self has the proper locks here
+                unsafe { transmute::<C::Mut<'_, VHandle<'man, C>>, C::Mut<'_, VHandle<'id, C>>>(view) }
+            }
+        }
+        impl<'id, 'man, U, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, N, H>
+        where
+            U: RawBytes,
+            H: Header,
+            GlobalConfig<Slices<U>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: MultiStore<U>,
+                    Manager<'x> = VManager<'x, Slices<U>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn get_mut<T>(&mut self, handle: SHandle<'id, [T]>) -> AResult<&mut [T]> {
+                let SHandle { shard, handle } = handle;
+                Ok(shard!(mut self[shard as usize]).get_mut(map_handle!(handle<[T]> 'id -> 'man))?)
+            }
+        }
+        impl<'id, 'man, U, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, N, H>
+        where
+            U: RawBytes,
+            H: Header,
+            GlobalConfig<Mixed<U>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: MultiStore<U>,
+                    Manager<'x> = VManager<'x, Mixed<U>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn get_mut<T>(&mut self, handle: SHandle<'id, T>) -> AResult<&mut T> {
+                let SHandle { shard, handle } = handle;
+                Ok(shard!(mut self[shard as usize]).get_mut(map_handle!(handle<T> 'id -> 'man))?)
+            }
+        }
+    };
+}
+impl_sharded_write!(ShardedVArenaWriteGuard<'_>);
+impl_sharded_write!(ArcShardedVArenaWriteGuard);
+macro_rules! impl_sharded_alloc {
+    ($type:ident $(<$lt:lifetime>)?) => {
+        impl<'id, 'man, K, C, const N: usize, H> $type<$($lt,)? 'id, 'man, K, C, N, H>
+        where
+            GlobalConfig<K, C>: Config,
+            H: Header,
+        {
+            /// Shard that is allocated from.
+            pub fn shard(&self) -> usize {
+                self.claim.shard
+            }
+            pub fn header(&self) -> &H {
+                &self.port.1
+            }
+            pub fn header_mut(&mut self) -> &mut H {
+                &mut self.port.1
+            }
+            fn local<T: ?Sized>(&self, handle: SHandle<'id, T>) -> AResult<VHandle<'man, T>> {
+                if handle.shard() != self.shard() {
+                    return Err(ArenaError::WrongShard(handle.shard()));
+                }
+                let SHandle { handle, .. } = handle;
+                Ok(map_handle!(handle<T> 'id -> 'man))
+            }
+            fn shared<T: ?Sized>(&self, handle: VHandle<'man, T>) -> SHandle<'id, T> {
+                SHandle { shard: self.shard() as u32, handle: map_handle!(handle<T> 'man -> 'id) }
+            }
+        }
+        impl<'id, 'man, K, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, K, Versioned<REUSE, H, V>, N, H>
+        where
+            H: Header,
+            GlobalConfig<K, Versioned<REUSE, H, V>>:
+                for<'x> Config<Store: Resizable, Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
+        {
+            #[rustfmt::skip]
+            pub fn reserve(&mut self, additional: Length) -> AResult<()> {
+                manager!(lock self |manager| Ok(manager.reserve(additional)?))
+            }
+        }
+        impl<'id, 'man, T, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, N, H>
+        where
+            H: Header,
+            GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: Store<(Version, T)>,
+                    Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn get(&self, handle: SHandle<'id, T>) -> AResult<&T> {
+                Ok(manager!(ref self).get(self.local(handle)?)?)
+            }
+            pub fn get_mut(&mut self, handle: SHandle<'id, T>) -> AResult<&mut T> {
+                let handle = self.local(handle)?;
+                Ok(manager!(mut self).get_mut(handle)?)
+            }
+            pub fn insert_within_capacity(&mut self, data: T) -> Result<SHandle<'id, T>, T> {
+                let handle = manager!(mut self).insert_within_capacity(data)?;
+                Ok(self.shared(handle))
+            }
+            pub fn insert(&mut self, data: T) -> Result<SHandle<'id, T>, (T, ArenaError)> {
+                match self.insert_within_capacity(data) {
+                    Ok(handle) => Ok(handle),
+                    Err(data) => {
+                        if let Err(err) = self.reserve(1) {
+                            return Err((data, err));
+                        }
+                        let Ok(handle) = self.insert_within_capacity(data) else {
+                            unreachable!("insert after reserve should always be successful")
+                        };
+                        Ok(handle)
+                    },
+                }
+            }
+        }
+        impl<'id, 'man, T, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, Typed<T>, Versioned<true, H, V>, N, H>
+        where
+            H: Header,
+            GlobalConfig<Typed<T>, Versioned<true, H, V>>: for<'x> Config<
+                    Store: ReusableStore<(Version, T)>,
+                    Manager<'x> = VManager<'x, Typed<T>, Versioned<true, H, V>>,
+                >,
+        {
+            /// Fails for handles of other shards, see [`ShardedVArena::alloc_in`].
+            pub fn remove(&mut self, handle: SHandle<'id, T>) -> AResult<T> {
+                let handle = self.local(handle)?;
+                Ok(manager!(mut self).remove(handle)?)
+            }
+        }
+        impl<'id, 'man, C, L, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, SoA<C, L>, Versioned<REUSE, H, V>, N, H>
+        where
+            C: Columns,
+            L: SoALayout,
+            H: Header,
+            GlobalConfig<SoA<C, L>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: SoAStore<Prefix<Version, C>, VHandle<'x, C>>,
+                    Manager<'x> = VManager<'x, SoA<C, L>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            /// Rows of the allocated shard, see [`ShardedVArenaReadGuard::view`].
+            pub fn view(&self) -> C::Ref<'_, VHandle<'id, C>> {
+                let view = manager!(ref self).view();
+                // SAFETY: self has the proper locks here
+                unsafe { transmute::<C::Ref<'_, VHandle<'man, C>>, C::Ref<'_, VHandle<'id, C>>>(view) }
+            }
+            pub fn view_mut(&mut self) -> C::Mut<'_, VHandle<'id, C>> {
+                let view = manager!(mut self).view_mut();
+                // SAFETY: self has the proper locks here
+                unsafe { transmute::<C::Mut<'_, VHandle<'man, C>>, C::Mut<'_, VHandle<'id, C>>>(view) }
+            }
+            pub fn insert_within_capacity(&mut self, data: C) -> Result<SHandle<'id, C>, C> {
+                let handle = manager!(mut self).insert_within_capacity(data)?;
+                Ok(self.shared(handle))
+            }
+            pub fn insert(&mut self, data: C) -> Result<SHandle<'id, C>, (C, ArenaError)> {
+                match self.insert_within_capacity(data) {
+                    Ok(handle) => Ok(handle),
+                    Err(data) => {
+                        if let Err(err) = self.reserve(1) {
+                            return Err((data, err));
+                        }
+                        let Ok(handle) = self.insert_within_capacity(data) else {
+                            unreachable!("insert after reserve should always be successful")
+                        };
+                        Ok(handle)
+                    },
+                }
+            }
+        }
+        impl<'id, 'man, C, L, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, SoA<C, L>, Versioned<true, H, V>, N, H>
+        where
+            C: Columns,
+            L: SoALayout,
+            H: Header,
+            GlobalConfig<SoA<C, L>, Versioned<true, H, V>>: for<'x> Config<
+                    Store: ReusableSoAStore<Prefix<Version, C>, VHandle<'x, C>>,
+                    Manager<'x> = VManager<'x, SoA<C, L>, Versioned<true, H, V>>,
+                >,
+        {
+            /// Fails for handles of other shards, see [`ShardedVArena::alloc_in`].
+            pub fn remove(&mut self, handle: SHandle<'id, C>) -> AResult<C> {
+                let handle = self.local(handle)?;
+                Ok(manager!(mut self).remove(handle)?)
+            }
+        }
+        impl<'id, 'man, U, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, Slices<U>, Versioned<REUSE, H, V>, N, H>
+        where
+            U: RawBytes,
+            H: Header,
+            GlobalConfig<Slices<U>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: MultiStore<U>,
+                    Manager<'x> = VManager<'x, Slices<U>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn get<T>(&self, handle: SHandle<'id, [T]>) -> AResult<&[T]> {
+                Ok(manager!(ref self).get(self.local(handle)?)?)
+            }
+            pub fn get_mut<T>(&mut self, handle: SHandle<'id, [T]>) -> AResult<&mut [T]> {
+                let handle = self.local(handle)?;
+                Ok(manager!(mut self).get_mut(handle)?)
+            }
+            pub fn insert_within_capacity<T: Copy>(&mut self, data: &[T]) -> Option<SHandle<'id, [T]>> {
+                let handle = manager!(mut self).insert_within_capacity(data)?;
+                Some(self.shared(handle))
+            }
+            pub fn insert<T: Copy>(&mut self, data: &[T]) -> AResult<SHandle<'id, [T]>> {
+                match self.insert_within_capacity(data) {
+                    Some(handle) => Ok(handle),
+                    None => {
+                        self.reserve(
+                            Slices::<U>::header_size::<Version>()
+                                + Slices::<U>::size_of::<T>(data.len() as Length),
+                        )?;
+                        let Some(handle) = self.insert_within_capacity(data) else {
+                            unreachable!("insert after reserve should always be successful")
+                        };
+                        Ok(handle)
+                    },
+                }
+            }
+        }
+        impl<'id, 'man, U, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, Slices<U>, Versioned<true, H, V>, N, H>
+        where
+            U: RawBytes,
+            H: Header,
+            GlobalConfig<Slices<U>, Versioned<true, H, V>>: for<'x> Config<
+                    Store: ReusableMultiStore<U>,
+                    Manager<'x> = VManager<'x, Slices<U>, Versioned<true, H, V>>,
+                >,
+        {
+            /// Fails for handles of other shards, see [`ShardedVArena::alloc_in`].
+            pub fn remove<T: Copy>(
+                &mut self,
+                handle: SHandle<'id, [T]>,
+            ) -> AResult<RemoveSliceGuard<'_, U, Versioned<true, H, V>>> {
+                let handle = self.local(handle)?;
+                Ok(manager!(mut self).remove(handle)?)
+            }
+        }
+        impl<'id, 'man, U, const REUSE: bool, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, Mixed<U>, Versioned<REUSE, H, V>, N, H>
+        where
+            U: RawBytes,
+            H: Header,
+            GlobalConfig<Mixed<U>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: MultiStore<U>,
+                    Manager<'x> = VManager<'x, Mixed<U>, Versioned<REUSE, H, V>>,
+                >,
+        {
+            pub fn get<T>(&self, handle: SHandle<'id, T>) -> AResult<&T> {
+                Ok(manager!(ref self).get(self.local(handle)?)?)
+            }
+            pub fn get_mut<T>(&mut self, handle: SHandle<'id, T>) -> AResult<&mut T> {
+                let handle = self.local(handle)?;
+                Ok(manager!(mut self).get_mut(handle)?)
+            }
+            pub fn insert_within_capacity<T>(&mut self, data: T) -> Result<SHandle<'id, T>, T> {
+                let handle = manager!(mut self).insert_within_capacity(data)?;
+                Ok(self.shared(handle))
+            }
+            pub fn insert<T>(&mut self, data: T) -> Result<SHandle<'id, T>, (T, ArenaError)> {
+                match self.insert_within_capacity(data) {
+                    Ok(handle) => Ok(handle),
+                    Err(data) => {
+                        if let Err(err) = self.reserve(Mixed::<U>::size_of::<(Version, T)>()) {
+                            return Err((data, err));
+                        }
+                        let Ok(handle) = self.insert_within_capacity(data) else {
+                            unreachable!("insert after reserve should always be successful")
+                        };
+                        Ok(handle)
+                    },
+                }
+            }
+        }
+        impl<'id, 'man, U, H, V, const N: usize>
+            $type<$($lt,)? 'id, 'man, Mixed<U>, Versioned<true, H, V>, N, H>
+        where
+            U: RawBytes,
+            H: Header,
+            GlobalConfig<Mixed<U>, Versioned<true, H, V>>: for<'x> Config<
+                    Store: ReusableMultiStore<U>,
+                    Manager<'x> = VManager<'x, Mixed<U>, Versioned<true, H, V>>,
+                >,
+        {
+            /// Fails for handles of other shards, see [`ShardedVArena::alloc_in`].
+            pub fn remove<T>(&mut self, handle: SHandle<'id, T>) -> AResult<T> {
+                let handle = self.local(handle)?;
+                Ok(manager!(mut self).remove(handle)?)
+            }
+        }
+    };
+}
+impl_sharded_alloc!(ShardedVArenaAllocGuard<'_>);
+impl_sharded_alloc!(ArcShardedVArenaAllocGuard);
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+
+    #[test]
+    fn allocates_from_free_shards() {
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(man);
+        let mut a =
+            ShardedVArena::<Typed<u32>, Versioned<true>, 2>::new(a, man, Headless::default());
+        let mut b = a.split(b);
+        let mut alloc_a = a.alloc();
+        let mut alloc_b = b.alloc();
+        assert_ne!(alloc_a.shard(), alloc_b.shard());
+        let first = alloc_a.insert(1).unwrap();
+        let second = alloc_b.insert(2).unwrap();
+        drop((alloc_a, alloc_b));
+        let Ok(map) = a.join(b) else { panic!("unshared port should be joined") };
+        let second = map.apply::<SHandle<u32>>(second);
+        assert_eq!(Ok(&2), a.read().get(second));
+        *a.write().get_mut(first).unwrap() += 10;
+        let other = a.alloc_in(1 - first.shard()).get(first).map(drop);
+        assert_eq!(Err(ArenaError::WrongShard(first.shard())), other);
+        assert_eq!(Ok(11), a.alloc_in(first.shard()).remove(first));
+        assert!(a.read().get(first).is_err());
+    }
+
+    #[test]
+    fn prefers_least_contended_shards() {
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(c);
+        make_guard!(d);
+        make_guard!(man);
+        let mut a =
+            ShardedVArena::<Typed<u32>, Versioned<true>, 3>::new(a, man, Headless::default());
+        let (mut b, mut c, mut d) = (a.split(b), a.split(c), a.split(d));
+        let alloc_a = a.alloc();
+        let alloc_b = b.alloc();
+        assert_eq!((0, 1), (alloc_a.shard(), alloc_b.shard()));
+        let waiting = Claim::new(&d.allocators, 0);
+        assert_eq!([2, 1, 0], d.by_contention(), "busy shards should come last");
+        let alloc_c = c.try_alloc().expect("free shard should be taken");
+        assert_eq!(2, alloc_c.shard());
+        assert_eq!([1, 2, 0], d.by_contention());
+        drop(waiting);
+        assert!(d.try_alloc().is_none(), "all shards are allocated from");
+        drop((alloc_a, alloc_b));
+        assert_eq!(0, d.try_alloc_for(Duration::from_millis(10)).unwrap().shard());
+    }
+
+    #[test]
+    fn covers_all_kinds() {
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(man);
+        let mut rows =
+            ShardedVArena::<SoA<(u32, f32)>, Versioned<true>, 2>::new(a, man, Headless::default());
+        let mut alloc = rows.alloc();
+        let row = alloc.insert((1, 1.0)).unwrap();
+        *alloc.view_mut().col0_mut(row.handle()).unwrap() += 1;
+        drop(alloc);
+        assert_eq!(Ok(&2), rows.read().view(row.shard()).col0(row.handle()));
+        assert_eq!(Ok((2, 1.0)), rows.alloc_in(row.shard()).remove(row));
+
+        make_guard!(man);
+        let mut mixed =
+            ShardedVArena::<Mixed<u64>, Versioned<true>, 2>::new(b, man, Headless::default());
+        let mut alloc = mixed.alloc();
+        let entry = alloc.insert((3u32, 4u8)).unwrap_or_else(|_| panic!());
+        drop(alloc);
+        mixed.write().get_mut(entry).unwrap().0 += 1;
+        assert_eq!(Ok(&(4, 4)), mixed.read().get(entry));
+    }
+
+    #[test]
+    fn owned_guards_outlive_the_arena() {
+        make_guard!(a);
+        make_guard!(man);
+        let mut a =
+            ShardedVArena::<Typed<u32>, Versioned<true>, 2>::new(a, man, Headless::default());
+        let mut alloc = a.alloc_arc();
+        let handle = alloc.insert(7).unwrap();
+        drop(alloc);
+        let mut write = a.write_arc();
+        drop(a);
+        *write.get_mut(handle).unwrap() += 1;
+        assert_eq!(Ok(&8), write.get(handle));
+    }
+}
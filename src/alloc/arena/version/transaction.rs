@@ -0,0 +1,361 @@
+use std::collections::{HashMap, hash_map::Entry};
+
+use variadics_please::all_tuples_enumerated;
+
+use super::*;
+
+/// Change staged by a [`VArenaTransaction`], it is applied to the arena on [`Transaction::commit`].
+#[derive(Debug)]
+pub enum Change<T> {
+    Remove,
+    Overwrite(T),
+}
+/// Guards that can apply and undo changes of a [`VArenaTransaction`].
+pub trait Rollback<'id, T> {
+    fn apply(&mut self, handle: VHandle<'id, T>, change: Change<T>);
+    /// Removes an entry that was inserted by the transaction.
+    fn discard(&mut self, handle: VHandle<'id, T>);
+}
+/// Stages removes and overwrites until [`Transaction::commit`] is called,
+/// they are dropped together with this transaction otherwise.
+///
+/// Inserts take their slot right away, as the returned handle has to be usable inside the transaction.
+/// No other handle can reach the new entry and it is removed again if the transaction is not committed.
+/// Only `Typed` arenas are supported, staging works on copies of whole elements.
+#[derive(Debug)]
+pub struct VArenaTransaction<'t, 'id, G, T>
+where
+    G: Rollback<'id, T>,
+{
+    guard:    &'t mut G,
+    inserted: Vec<VHandle<'id, T>>,
+    staged:   HashMap<Index, (VHandle<'id, T>, Change<T>)>,
+}
+impl<'id, G, T> Drop for VArenaTransaction<'_, 'id, G, T>
+where
+    G: Rollback<'id, T>,
+{
+    fn drop(&mut self) {
+        while let Some(handle) = self.inserted.pop() {
+            self.guard.discard(handle);
+        }
+    }
+}
+pub trait Transaction {
+    /// Applies all changes, this will commit either all or none of the transactions in a tuple.
+    fn commit(self);
+}
+impl<'id, G, T> Transaction for VArenaTransaction<'_, 'id, G, T>
+where
+    G: Rollback<'id, T>,
+{
+    fn commit(mut self) {
+        for (handle, change) in self.staged.drain().map(|(_, staged)| staged) {
+            self.guard.apply(handle, change);
+        }
+        self.inserted.clear();
+    }
+}
+macro_rules! impl_transaction_tuple {
+    ($(($i:tt, $T:ident)),*) => {
+        impl<$($T: Transaction),*> Transaction for ($($T,)*) {
+            fn commit(self) {
+                $(self.$i.commit();)*
+            }
+        }
+    };
+}
+all_tuples_enumerated!(impl_transaction_tuple, 1, 16, T);
+
+const STAGED_REMOVE: ArenaError =
+    ArenaError::ManagerError(ManagerError::BadHandle("removed by the transaction"));
+
+macro_rules! impl_apply_overwrite {
+    ($this:ident, $handle:ident, $data:ident) => {{
+        let result = $this.get_mut($handle).map(|old| *old = $data);
+        debug_assert!(result.is_ok(), "staged handles should stay valid");
+    }};
+}
+impl<'id, 'man, T, const REUSE: bool, H, V> Rollback<'id, T>
+    for VArenaWriteGuard<'_, 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>
+where
+    H: Header,
+    GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: Store<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
+        >,
+{
+    fn apply(&mut self, handle: VHandle<'id, T>, change: Change<T>) {
+        match change {
+            Change::Overwrite(data) => impl_apply_overwrite!(self, handle, data),
+            Change::Remove => unreachable!("write guards cannot remove"),
+        }
+    }
+
+    fn discard(&mut self, _: VHandle<'id, T>) {
+        unreachable!("write guards cannot insert")
+    }
+}
+impl<'id, 'man, T, H, V> Rollback<'id, T>
+    for VArenaAllocGuard<'_, 'id, 'man, Typed<T>, Versioned<false, H, V>, H>
+where
+    H: Header,
+    GlobalConfig<Typed<T>, Versioned<false, H, V>>: for<'x> Config<
+            Store: Store<(Version, T)> + Truncate,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<false, H, V>>,
+        >,
+{
+    fn apply(&mut self, handle: VHandle<'id, T>, change: Change<T>) {
+        match change {
+            Change::Overwrite(data) => impl_apply_overwrite!(self, handle, data),
+            Change::Remove => unreachable!("append-only arenas cannot remove"),
+        }
+    }
+
+    fn discard(&mut self, handle: VHandle<'id, T>) {
+        // NOTE: inserts are discarded in reverse order, so this is always the last entry
+        let result = manager!(mut self).rollback_to_len(handle.into_index().get());
+        debug_assert!(result.is_ok(), "inserts should not be rolled back yet");
+    }
+}
+impl<'id, 'man, T, H, V> Rollback<'id, T>
+    for VArenaAllocGuard<'_, 'id, 'man, Typed<T>, Versioned<true, H, V>, H>
+where
+    H: Header,
+    GlobalConfig<Typed<T>, Versioned<true, H, V>>: for<'x> Config<
+            Store: ReusableStore<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<true, H, V>>,
+        >,
+{
+    fn apply(&mut self, handle: VHandle<'id, T>, change: Change<T>) {
+        match change {
+            Change::Overwrite(data) => impl_apply_overwrite!(self, handle, data),
+            Change::Remove => self.discard(handle),
+        }
+    }
+
+    fn discard(&mut self, handle: VHandle<'id, T>) {
+        let result = self.remove(handle);
+        debug_assert!(result.is_ok(), "staged handles should stay valid");
+    }
+}
+
+macro_rules! impl_transaction {
+    ($type:ident) => {
+        impl<'id, 'man, T, C, H> $type<'_, 'id, 'man, Typed<T>, C, H>
+        where
+            GlobalConfig<Typed<T>, C>: Config,
+            H: Header,
+        {
+            /// Starts a transaction, see [`VArenaTransaction`].
+            pub fn transaction(&mut self) -> VArenaTransaction<'_, 'id, Self, T>
+            where
+                Self: Rollback<'id, T>,
+            {
+                VArenaTransaction { guard: self, inserted: Vec::new(), staged: HashMap::new() }
+            }
+        }
+        impl<'a, 'id, 'man, T, const REUSE: bool, H, V>
+            VArenaTransaction<'_, 'id, $type<'a, 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>, T>
+        where
+            H: Header,
+            GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
+                    Store: Store<(Version, T)>,
+                    Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
+                >,
+            $type<'a, 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>: Rollback<'id, T>,
+        {
+            /// Returns the staged value, if the entry was changed by this transaction.
+            pub fn get(&self, handle: VHandle<'id, T>) -> AResult<&T> {
+                let data = self.guard.get(handle)?;
+                match self.staged.get(&handle.into_index()) {
+                    None => Ok(data),
+                    Some((_, Change::Overwrite(data))) => Ok(data),
+                    Some((_, Change::Remove)) => Err(STAGED_REMOVE),
+                }
+            }
+            /// Stages a copy of the current value on the first call for `handle`,
+            /// the copy is written to the arena on commit.
+            pub fn get_mut(&mut self, handle: VHandle<'id, T>) -> AResult<&mut T>
+            where
+                T: Clone,
+            {
+                let data = self.guard.get(handle)?;
+                let (_, change) = self
+                    .staged
+                    .entry(handle.into_index())
+                    .or_insert_with(|| (handle, Change::Overwrite(data.clone())));
+                match change {
+                    Change::Overwrite(data) => Ok(data),
+                    Change::Remove => Err(STAGED_REMOVE),
+                }
+            }
+        }
+    };
+}
+impl_transaction!(VArenaWriteGuard);
+impl_transaction!(VArenaAllocGuard);
+impl<'a, 'id, 'man, T, const REUSE: bool, H, V>
+    VArenaTransaction<
+        '_,
+        'id,
+        VArenaAllocGuard<'a, 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>,
+        T,
+    >
+where
+    H: Header,
+    GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: Store<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
+        >,
+    VArenaAllocGuard<'a, 'id, 'man, Typed<T>, Versioned<REUSE, H, V>, H>: Rollback<'id, T>,
+{
+    pub fn insert_within_capacity(&mut self, data: T) -> Result<VHandle<'id, T>, T> {
+        let handle = self.guard.insert_within_capacity(data)?;
+        self.inserted.push(handle);
+        Ok(handle)
+    }
+    pub fn insert(&mut self, data: T) -> Result<VHandle<'id, T>, (T, ArenaError)> {
+        let handle = self.guard.insert(data)?;
+        self.inserted.push(handle);
+        Ok(handle)
+    }
+}
+impl<'id, 'man, T, H, V>
+    VArenaTransaction<
+        '_,
+        'id,
+        VArenaAllocGuard<'_, 'id, 'man, Typed<T>, Versioned<true, H, V>, H>,
+        T,
+    >
+where
+    H: Header,
+    GlobalConfig<Typed<T>, Versioned<true, H, V>>: for<'x> Config<
+            Store: ReusableStore<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<true, H, V>>,
+        >,
+{
+    /// Stages the removal and returns a copy of the current value,
+    /// the entry stays in the arena until the transaction is committed.
+    pub fn remove(&mut self, handle: VHandle<'id, T>) -> AResult<T>
+    where
+        T: Clone,
+    {
+        let data = self.guard.get(handle)?;
+        match self.staged.entry(handle.into_index()) {
+            Entry::Vacant(entry) => {
+                entry.insert((handle, Change::Remove));
+                Ok(data.clone())
+            },
+            Entry::Occupied(mut entry) => match &mut entry.get_mut().1 {
+                Change::Remove => Err(STAGED_REMOVE),
+                change => match std::mem::replace(change, Change::Remove) {
+                    Change::Overwrite(data) => Ok(data),
+                    Change::Remove => unreachable!("removes are handled above"),
+                },
+            },
+        }
+    }
+}
+
+/// Arenas that can be locked together by [`AllocAll::alloc_all`].
+pub trait OrderedAlloc {
+    type Guard;
+
+    /// Address of the shared manager, locks are always acquired in order of this address.
+    fn lock_address(&self) -> usize;
+    fn alloc(self) -> Self::Guard;
+}
+impl<'a, 'id, 'man, K, const REUSE: bool, H, V> OrderedAlloc
+    for &'a mut Arena<'id, 'man, K, Versioned<REUSE, H, V>>
+where
+    H: Header,
+    GlobalConfig<K, Versioned<REUSE, H, V>>: for<'x, 'y> Config<
+            Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>,
+            Arena<'y, 'x> = VArena<'y, 'x, K, Versioned<REUSE, H, V>, H>,
+        >,
+{
+    type Guard = VArenaAllocGuard<'a, 'id, 'man, K, Versioned<REUSE, H, V>, H>;
+
+    fn lock_address(&self) -> usize {
+        Arc::as_ptr(&self.0.manager) as usize
+    }
+
+    fn alloc(self) -> Self::Guard {
+        Arena::alloc(self)
+    }
+}
+/// Locks multiple arenas at once, this will never deadlock with another call to `alloc_all`.
+pub trait AllocAll {
+    type Guards;
+
+    /// # Panics
+    /// When two of the arenas share the same manager.
+    fn alloc_all(self) -> Self::Guards;
+}
+macro_rules! impl_alloc_all {
+    ($(($i:tt, $T:ident)),*) => {
+        impl<$($T: OrderedAlloc),*> AllocAll for ($($T,)*) {
+            type Guards = ($($T::Guard,)*);
+
+            fn alloc_all(self) -> Self::Guards {
+                let mut order = [$((self.$i.lock_address(), $i)),*];
+                order.sort_unstable();
+                assert!(
+                    order.windows(2).all(|pair| pair[0].0 != pair[1].0),
+                    "arenas sharing a manager cannot be locked at the same time"
+                );
+                let mut arenas = ($(Some(self.$i),)*);
+                let mut guards = ($(None::<$T::Guard>,)*);
+                for (_, i) in order {
+                    match i {
+                        $($i => guards.$i = arenas.$i.take().map($T::alloc),)*
+                        _ => unreachable!("order only contains tuple indices"),
+                    }
+                }
+                ($(guards.$i.expect("every arena should be locked"),)*)
+            }
+        }
+    };
+}
+all_tuples_enumerated!(impl_alloc_all, 2, 16, T);
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+
+    #[test]
+    fn rollback_on_drop_across_arenas() {
+        make_guard!(a);
+        make_guard!(ma);
+        make_guard!(b);
+        make_guard!(mb);
+        let mut a = Arena::<Typed<u32>, Versioned<true>>::new(a, ma, Headless::default());
+        let mut b = Arena::<Typed<u32>, Versioned<true>>::new(b, mb, Headless::default());
+        let (mut a, mut b) = (&mut a, &mut b).alloc_all();
+        let x = a.insert(1).unwrap();
+        let y = b.insert(2).unwrap();
+        let z = {
+            let (mut ta, mut tb) = (a.transaction(), b.transaction());
+            *ta.get_mut(x).unwrap() = 3;
+            assert_eq!(Ok(2), tb.remove(y));
+            ta.insert(4).unwrap()
+        };
+        assert_eq!(Ok(&1), a.get(x), "overwrites should be undone");
+        assert_eq!(Ok(&2), b.get(y), "removed entries should be restored");
+        assert!(a.get(z).is_err(), "inserted entries should be removed");
+        let (mut ta, mut tb) = (a.transaction(), b.transaction());
+        *ta.get_mut(x).unwrap() = 3;
+        *ta.get_mut(x).unwrap() += 1;
+        assert_eq!(Ok(&4), ta.get(x), "edits should be kept across get_mut calls");
+        assert_eq!(Ok(2), tb.remove(y));
+        assert!(tb.get(y).is_err(), "removed entries should be hidden inside the transaction");
+        assert_eq!(Ok(&1), ta.guard.get(x), "overwrites should be staged until commit");
+        assert_eq!(Ok(&2), tb.guard.get(y), "removes should be staged until commit");
+        (ta, tb).commit();
+        assert_eq!(Ok(&4), a.get(x));
+        assert!(b.get(y).is_err(), "committed changes should be kept");
+    }
+}
@@ -0,0 +1,189 @@
+use super::*;
+
+/// Types stored in a garbage collected arena have to report every handle they hold.
+pub trait Trace<'id>: Sized {
+    fn trace(&self, tracer: &mut Tracer<'_, 'id, Self>);
+}
+pub struct Tracer<'a, 'id, T> {
+    stack: &'a mut Vec<VHandle<'id, T>>,
+}
+impl<'id, T> Tracer<'_, 'id, T> {
+    pub fn visit(&mut self, handle: VHandle<'id, T>) {
+        self.stack.push(handle);
+    }
+}
+
+/// State of a running mark and sweep collection.
+///
+/// Entries that get inserted while the collection is running are never collected.
+/// Every handle that gets written into an entry while the collection is running
+/// has to be passed to [`Collector::shade`], otherwise it might be collected.
+#[derive(Debug)]
+pub struct Collector<'id, T> {
+    /// Version of every entry that existed when the collection started and was not marked yet.
+    candidates: Vec<Option<Version>>,
+    stack:      Vec<VHandle<'id, T>>,
+}
+impl<'id, T> Collector<'id, T> {
+    pub fn shade(&mut self, handle: VHandle<'id, T>) {
+        self.stack.push(handle);
+    }
+    /// Returns `true` when there is no marking work left.
+    pub fn is_marked(&self) -> bool {
+        self.stack.is_empty()
+    }
+}
+impl<'id, T, H, V> Manager<'id, Typed<T>, Versioned<true, H, V>>
+where
+    GlobalConfig<Typed<T>, Versioned<true, H, V>>: for<'x> Config<
+            Store: ReusableStore<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<true, H, V>>,
+        >,
+{
+    pub(crate) fn begin_collect_with<'b>(
+        &self,
+        roots: impl IntoIterator<Item = VHandle<'b, T>>,
+    ) -> Collector<'b, T> {
+        let candidates = (0..self.0.store.capacity())
+            .map(|i| Index::new(i).and_then(|index| self.0.store.get(index).ok()).map(|(v, _)| *v))
+            .collect();
+        Collector { candidates, stack: roots.into_iter().collect() }
+    }
+    pub(crate) fn collect_step_with<'b>(
+        &self,
+        collector: &mut Collector<'b, T>,
+        mut budget: usize,
+    ) -> bool
+    where
+        T: Trace<'b>,
+    {
+        while budget > 0 {
+            let Some(handle) = collector.stack.pop() else { break };
+            let Some(candidate) = collector.candidates.get_mut(handle.index.get() as usize) else {
+                continue;
+            };
+            if *candidate != Some(handle.version) {
+                continue;
+            }
+            *candidate = None;
+            budget -= 1;
+            if let Ok((_, data)) = self.0.store.get(handle.index) {
+                data.trace(&mut Tracer { stack: &mut collector.stack });
+            }
+        }
+        collector.is_marked()
+    }
+    pub(crate) fn finish_collect_with<'b>(
+        &mut self,
+        mut collector: Collector<'b, T>,
+        roots: impl IntoIterator<Item = VHandle<'b, T>>,
+    ) -> Length
+    where
+        T: Trace<'b>,
+    {
+        collector.stack.extend(roots);
+        self.collect_step_with(&mut collector, usize::MAX);
+        let mut removed = 0;
+        for (i, candidate) in collector.candidates.into_iter().enumerate() {
+            let Some(version) = candidate else { continue };
+            // SAFETY: candidates are only created for indices within capacity
+            let index = unsafe { Index::new_unchecked(i as u32) };
+            if self.0.store.get(index).is_ok_and(|(v, _)| *v == version)
+                && self.0.store.remove(index).is_ok()
+            {
+                self.0.ticks.mark_removed(index, version);
+                removed += 1;
+            }
+        }
+        if removed > 0 {
+            self.0.dirty = true;
+        }
+        removed
+    }
+
+    /// Removes all entries that are not reachable from `roots`, returns the number of removed entries.
+    /// Removed entries are invalidated the same way [`Manager::remove`] does.
+    pub fn collect(&mut self, roots: impl IntoIterator<Item = VHandle<'id, T>>) -> Length
+    where
+        T: Trace<'id>,
+    {
+        let collector = self.begin_collect_with(roots);
+        self.finish_collect_with(collector, [])
+    }
+    /// Starts an incremental collection, see [`Collector`].
+    pub fn begin_collect(
+        &self,
+        roots: impl IntoIterator<Item = VHandle<'id, T>>,
+    ) -> Collector<'id, T> {
+        self.begin_collect_with(roots)
+    }
+    /// Marks up to `budget` entries, returns `true` when there is no marking work left.
+    pub fn collect_step(&self, collector: &mut Collector<'id, T>, budget: usize) -> bool
+    where
+        T: Trace<'id>,
+    {
+        self.collect_step_with(collector, budget)
+    }
+    /// Marks everything reachable from `roots` and the remaining work of `collector`
+    /// and removes all unmarked entries, returns the number of removed entries.
+    pub fn finish_collect(
+        &mut self,
+        collector: Collector<'id, T>,
+        roots: impl IntoIterator<Item = VHandle<'id, T>>,
+    ) -> Length
+    where
+        T: Trace<'id>,
+    {
+        self.finish_collect_with(collector, roots)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+
+    struct Node<'id> {
+        next: Option<VHandle<'id, Node<'id>>>,
+    }
+    impl<'id> Trace<'id> for Node<'id> {
+        fn trace(&self, tracer: &mut Tracer<'_, 'id, Self>) {
+            if let Some(next) = self.next {
+                tracer.visit(next);
+            }
+        }
+    }
+
+    #[test]
+    fn collects_unreachable_cycles() {
+        make_guard!(guard);
+        let mut manager = Manager::<Typed<Node>, Versioned<true>>::new(guard);
+        assert_eq!(Ok(()), manager.reserve(3));
+        let root = manager.insert_within_capacity(Node { next: None }).ok().unwrap();
+        let a = manager.insert_within_capacity(Node { next: None }).ok().unwrap();
+        let b = manager.insert_within_capacity(Node { next: Some(a) }).ok().unwrap();
+        manager.get_mut(a).unwrap().next = Some(b);
+        assert_eq!(2, manager.collect([root]), "cycle should be collected");
+        assert!(manager.get(root).is_ok(), "root should survive");
+        assert!(manager.get(a).is_err(), "collected handles should be invalid");
+        let c = manager.insert_within_capacity(Node { next: None }).ok().unwrap();
+        assert!(manager.get(b).is_err(), "reused slots should not accept stale handles");
+        assert!(manager.get(c).is_ok());
+    }
+
+    #[test]
+    fn incremental_collection_respects_shaded_handles() {
+        make_guard!(guard);
+        let mut manager = Manager::<Typed<Node>, Versioned<true>>::new(guard);
+        assert_eq!(Ok(()), manager.reserve(3));
+        let root = manager.insert_within_capacity(Node { next: None }).ok().unwrap();
+        let a = manager.insert_within_capacity(Node { next: None }).ok().unwrap();
+        let mut collector = manager.begin_collect([root]);
+        assert!(manager.collect_step(&mut collector, 1));
+        manager.get_mut(root).unwrap().next = Some(a);
+        collector.shade(a);
+        assert_eq!(0, manager.finish_collect(collector, [root]));
+        assert!(manager.get(a).is_ok(), "shaded entry should survive");
+    }
+}
@@ -1,6 +1,8 @@
 use std::marker::PhantomData;
 
 use generativity::{Guard, Id};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 use super::*;
 use crate::alloc::store::*;
@@ -33,9 +35,9 @@ pub struct XManager<'id, K, C>
 where
     GlobalConfig<K, C>: Config,
 {
-    store:   <GlobalConfig<K, C> as Config>::Store,
-    id:      Id<'id>,
-    _marker: PhantomData<K>,
+    pub(super) store: <GlobalConfig<K, C> as Config>::Store,
+    id:               Id<'id>,
+    _marker:          PhantomData<K>,
 }
 impl<'id, K, const REUSE: bool, V> Manager<'id, K, Exclusive<REUSE, V>>
 where
@@ -43,11 +45,21 @@ where
         for<'x> Config<Store: Default, Manager<'x> = XManager<'x, K, Exclusive<REUSE, V>>>,
 {
     pub fn new(guard: Guard<'id>) -> Self {
-        Self(XManager {
-            store:   <GlobalConfig<K, Exclusive<REUSE, V>> as Config>::Store::default(),
-            id:      guard.into(),
-            _marker: PhantomData,
-        })
+        Self::with_store(guard, Default::default())
+    }
+}
+impl<'id, K, const REUSE: bool, V> Manager<'id, K, Exclusive<REUSE, V>>
+where
+    GlobalConfig<K, Exclusive<REUSE, V>>:
+        for<'x> Config<Manager<'x> = XManager<'x, K, Exclusive<REUSE, V>>>,
+{
+    /// Creates a manager around an existing empty store,
+    /// e.g. one holding columns only known at runtime.
+    pub fn with_store(
+        guard: Guard<'id>,
+        store: <GlobalConfig<K, Exclusive<REUSE, V>> as Config>::Store,
+    ) -> Self {
+        Self(XManager { store, id: guard.into(), _marker: PhantomData })
     }
 }
 impl<K, const REUSE: bool, V> Manager<'_, K, Exclusive<REUSE, V>>
@@ -93,6 +105,27 @@ where
         })
     }
 }
+#[cfg(feature = "rayon")]
+impl<'id, T, const REUSE: bool, V> Manager<'id, Typed<T>, Exclusive<REUSE, V>>
+where
+    GlobalConfig<Typed<T>, Exclusive<REUSE, V>>: for<'x> Config<
+            Store: Store<T> + ParIter<T>,
+            Manager<'x> = XManager<'x, Typed<T>, Exclusive<REUSE, V>>,
+        >,
+{
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = &T>
+    where
+        T: Sync,
+    {
+        self.0.store.par_iter().map(|(_, data)| data)
+    }
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = &mut T>
+    where
+        T: Send,
+    {
+        self.0.store.par_iter_mut().map(|(_, data)| data)
+    }
+}
 impl<'id, T, const REUSE: bool, V> Manager<'id, Typed<T>, Exclusive<REUSE, V>>
 where
     GlobalConfig<Typed<T>, Exclusive<REUSE, V>>: for<'x> Config<
@@ -122,19 +155,20 @@ where
         self.0.store.remove(handle.index).map_err(|err| (handle, err.into()))
     }
 }
-impl<'id, C, const REUSE: bool, V> Manager<'id, SoA<C>, Exclusive<REUSE, V>>
+impl<'id, C, L, const REUSE: bool, V> Manager<'id, SoA<C, L>, Exclusive<REUSE, V>>
 where
     C: Columns,
-    GlobalConfig<SoA<C>, Exclusive<REUSE, V>>: for<'a, 'x> Config<
-            Store: SoAStore<C, &'a XHandle<'x, C>, &'a mut XHandle<'x, C>>,
-            Manager<'x> = XManager<'x, SoA<C>, Exclusive<REUSE, V>>,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Exclusive<REUSE, V>>: for<'a, 'x> Config<
+            Store: SoAStore<C, &'a XHandle<'x, C>> + SoAStore<C, &'a mut XHandle<'x, C>>,
+            Manager<'x> = XManager<'x, SoA<C, L>, Exclusive<REUSE, V>>,
         >,
 {
     pub fn view(&self) -> C::Ref<'_, &'_ XHandle<'id, C>> {
-        self.0.store.view()
+        View::<Rows<C, &XHandle<'id, C>>>::view(&self.0.store)
     }
     pub fn view_mut(&mut self) -> C::Mut<'_, &'_ mut XHandle<'id, C>> {
-        self.0.store.view_mut()
+        View::<Rows<C, &mut XHandle<'id, C>>>::view_mut(&mut self.0.store)
     }
     pub fn insert_within_capacity(&mut self, data: C) -> Result<XHandle<'id, C>, C> {
         self.0.store.insert_within_capacity(data).map(|index| XHandle {
@@ -144,12 +178,14 @@ where
         })
     }
 }
-impl<'id, C, V> Manager<'id, SoA<C>, Exclusive<true, V>>
+impl<'id, C, L, V> Manager<'id, SoA<C, L>, Exclusive<true, V>>
 where
     C: Columns,
-    GlobalConfig<SoA<C>, Exclusive<true, V>>: for<'a, 'x> Config<
-            Store: ReusableSoAStore<C, &'a XHandle<'x, C>, &'a mut XHandle<'x, C>>,
-            Manager<'x> = XManager<'x, SoA<C>, Exclusive<true, V>>,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Exclusive<true, V>>: for<'a, 'x> Config<
+            Store: ReusableSoAStore<C, &'a XHandle<'x, C>>
+                       + ReusableSoAStore<C, &'a mut XHandle<'x, C>>,
+            Manager<'x> = XManager<'x, SoA<C, L>, Exclusive<true, V>>,
         >,
 {
     pub fn remove(
@@ -224,6 +260,7 @@ where
             Manager<'x> = XManager<'x, Slices<U>, Exclusive<true, V>>,
         >,
 {
+    /// The guard holds the header of the slice followed by its data.
     #[expect(clippy::type_complexity)]
     pub fn remove<T: Copy>(
         &mut self,
@@ -237,7 +274,7 @@ where
                 Slices::<U>::delete_slice::<
                     T,
                     <GlobalConfig<Slices<U>, Exclusive<true, V>> as Config>::Store,
-                >(&mut self.0.store, index, len)
+                >(&mut self.0.store, handle.index, index, len)
                 .map_err(|err| (handle, err.into()))
             },
             Err(err) => Err((handle, err.into())),
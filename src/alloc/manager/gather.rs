@@ -0,0 +1,192 @@
+use super::*;
+use crate::alloc::store::*;
+
+impl<'id, C, L, const REUSE: bool, H, V> Manager<'id, SoA<C, L>, Versioned<REUSE, H, V>>
+where
+    C: Columns,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: SoAStore<Prefix<Version, C>, VHandle<'id, C>>,
+            Manager<'x> = VManager<'x, SoA<C, L>, Versioned<REUSE, H, V>>,
+        >,
+{
+    /// Checks that every handle points to an occupied row with a matching version.
+    pub fn validate(&self, handles: &[VHandle<'id, C>]) -> MResult<()> {
+        let versions = self.0.store.view().part0();
+        for handle in handles {
+            if *versions.col0(*handle)? != handle.version {
+                return Err(ManagerError::BadHandle("version mismatch"));
+            }
+        }
+        Ok(())
+    }
+    /// Copies `columns` of the rows at `handles` into `buffers`, one value per handle.
+    ///
+    /// Columns are selected by [`Col`] or a tuple of them, e.g. `(Col::<0>, Col::<2>)`,
+    /// with a matching tuple of `Vec`s as buffers.
+    /// All handles are validated before anything is copied.
+    pub fn gather<S: ColumnSet<C>>(
+        &self,
+        handles: &[VHandle<'id, C>],
+        columns: S,
+        buffers: &mut S::Buffers,
+    ) -> MResult<()> {
+        self.validate(handles)?;
+        // SAFETY: handles were validated
+        unsafe { self.gather_unchecked(handles, columns, buffers) };
+        Ok(())
+    }
+    /// [`Manager::gather`] without validation.
+    /// # Safety
+    /// `handles` have to pass [`Manager::validate`] and no row was removed since.
+    pub unsafe fn gather_unchecked<S: ColumnSet<C>>(
+        &self,
+        handles: &[VHandle<'id, C>],
+        _columns: S,
+        buffers: &mut S::Buffers,
+    ) {
+        let count = C::count(&self.0.store.schema().1);
+        // column 0 is the version
+        // SAFETY: gathering only reads the rows
+        let rows = unsafe { self.0.store.raw_rows() }.slice(1..count + 1);
+        // SAFETY: guarantied by caller
+        unsafe { S::gather(&rows, handles.iter().map(|handle| handle.index), buffers) };
+    }
+    /// Moves the values in `buffers` back into `columns` of the rows at `handles`,
+    /// leaving the buffers empty for reuse. When a handle appears more than once, the last value is kept.
+    ///
+    /// All handles and buffer lengths are validated before anything is written,
+    /// every buffer has to hold exactly one value per handle.
+    /// Indexes are marked as stale, see [`Manager::refresh_indexes`].
+    pub fn scatter<S: ColumnSet<C>>(
+        &mut self,
+        handles: &[VHandle<'id, C>],
+        columns: S,
+        buffers: &mut S::Buffers,
+    ) -> MResult<()> {
+        if !S::has_len(buffers, handles.len()) {
+            return Err(ManagerError::BadBuffer("length differs from the number of handles"));
+        }
+        self.validate(handles)?;
+        // SAFETY: handles were validated
+        unsafe { self.scatter_unchecked(handles, columns, buffers) };
+        Ok(())
+    }
+    /// [`Manager::scatter`] without validation.
+    /// # Safety
+    /// `handles` have to pass [`Manager::validate`] and no row was removed since.
+    /// # Panics
+    /// Every buffer has to hold exactly one value per handle.
+    pub unsafe fn scatter_unchecked<S: ColumnSet<C>>(
+        &mut self,
+        handles: &[VHandle<'id, C>],
+        _columns: S,
+        buffers: &mut S::Buffers,
+    ) {
+        self.0.indexes.mark_stale();
+        let ticks = self.0.ticks.sink(self.0.store.capacity());
+        let (schema, rows) = self.0.store.raw_rows_mut();
+        let count = C::count(&schema.1);
+        let rows = match ticks {
+            Some(ticks) => rows.with_ticks(ticks),
+            None => rows,
+        };
+        // column 0 is the version
+        let rows = rows.slice(1..count + 1);
+        // SAFETY: guarantied by caller and self is borrowed mutably
+        unsafe { S::scatter(&rows, handles.iter().map(|handle| handle.index), buffers) };
+    }
+}
+impl<'id, C, L, const REUSE: bool, V> Manager<'id, SoA<C, L>, Exclusive<REUSE, V>>
+where
+    C: Columns,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Exclusive<REUSE, V>>: for<'a, 'x> Config<
+            Store: SoAStore<C, &'a XHandle<'x, C>> + SoAStore<C, &'a mut XHandle<'x, C>>,
+            Manager<'x> = XManager<'x, SoA<C, L>, Exclusive<REUSE, V>>,
+        >,
+{
+    /// Copies `columns` of the rows at `handles` into `buffers`, one value per handle.
+    ///
+    /// Exclusive handles are always valid, so there is nothing to validate.
+    pub fn gather<S: ColumnSet<C>>(
+        &self,
+        handles: &[XHandle<'id, C>],
+        _columns: S,
+        buffers: &mut S::Buffers,
+    ) {
+        // SAFETY: gathering only reads the rows
+        let rows = unsafe { self.0.store.raw_rows() };
+        // SAFETY: exclusive handles always point to occupied rows
+        unsafe { S::gather(&rows, handles.iter().map(IntoIndex::into_index), buffers) };
+    }
+    /// Moves the values in `buffers` back into `columns` of the rows at `handles`,
+    /// leaving the buffers empty.
+    ///
+    /// Nothing is written unless every buffer holds exactly one value per handle.
+    pub fn scatter<S: ColumnSet<C>>(
+        &mut self,
+        handles: &mut [XHandle<'id, C>],
+        _columns: S,
+        buffers: &mut S::Buffers,
+    ) -> MResult<()> {
+        if !S::has_len(buffers, handles.len()) {
+            return Err(ManagerError::BadBuffer("length differs from the number of handles"));
+        }
+        let (_, rows) = self.0.store.raw_rows_mut();
+        // SAFETY: exclusive handles always point to occupied rows and are borrowed mutably
+        unsafe { S::scatter(&rows, handles.iter().map(IntoIndex::into_index), buffers) };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+    use crate::alloc::arena::Headless;
+
+    #[test]
+    fn gathers_and_scatters_columns() {
+        make_guard!(guard);
+        let mut manager =
+            Manager::<SoA<(u32, f32, String)>, Versioned<true, Headless, Tracked>>::new(guard);
+        manager.reserve(4).unwrap();
+        let handles: Vec<_> = (0..4)
+            .map(|i| manager.insert_within_capacity((i, i as f32, i.to_string())).unwrap())
+            .collect();
+        let batch = [handles[3], handles[1]];
+        let mut buffers = (Vec::new(), Vec::new());
+        manager.gather(&batch, (Col::<0>, Col::<1>), &mut buffers).unwrap();
+        assert_eq!((vec![3, 1], vec![3.0, 1.0]), buffers);
+        let (ids, mut values) = buffers;
+        let start = manager.advance_tick();
+        values.iter_mut().zip(&ids).for_each(|(value, id)| *value *= *id as f32);
+        manager.scatter(&batch, Col::<1>, &mut values).unwrap();
+        assert!(values.is_empty());
+        assert_eq!(Ok(&9.0), manager.view().col1(handles[3]));
+        let mut short = (vec![7], vec![7.0, 7.0]);
+        assert!(manager.scatter(&batch, (Col::<0>, Col::<1>), &mut short).is_err());
+        assert_eq!(Ok(&3), manager.view().col0(handles[3]), "nothing is written for bad buffers");
+        assert_eq!(2, manager.changed_since(1, start).count());
+        assert_eq!(0, manager.changed_since(0, start).count());
+
+        manager.remove(handles[1]).unwrap();
+        let mut names = vec!["stale".to_string()];
+        assert!(manager.gather(&batch, Col::<2>, &mut names).is_err());
+        assert_eq!(vec!["stale"], names, "nothing is copied for invalid handles");
+
+        make_guard!(guard);
+        let mut manager = Manager::<SoA<(u8, String)>, Exclusive<true>>::new(guard);
+        let mut handles = vec![manager.insert_within_capacity((1, "a".into())).unwrap()];
+        let mut names = Vec::new();
+        manager.gather(&handles, Col::<1>, &mut names);
+        names[0].push('b');
+        manager.scatter(&mut handles, Col::<1>, &mut names).unwrap();
+        assert_eq!(Ok(&"ab".to_string()), manager.view().col1(&handles[0]));
+        let mut names = vec!["c".to_string(), "d".to_string()];
+        assert!(manager.scatter(&mut handles, Col::<1>, &mut names).is_err());
+        assert_eq!(2, names.len(), "buffers are kept when the lengths differ");
+    }
+}
@@ -0,0 +1,406 @@
+use std::{borrow::Cow, collections::VecDeque};
+
+use super::*;
+
+/// Managers and guards that can put removed entries back at their original index and version.
+pub trait Reversible<'id, T> {
+    type Error;
+
+    fn insert(&mut self, data: T) -> Result<VHandle<'id, T>, (T, Self::Error)>;
+    fn remove(&mut self, handle: VHandle<'id, T>) -> Result<T, Self::Error>;
+    /// Returns `data` if the entry can't be put back.
+    fn restore(&mut self, handle: VHandle<'id, T>, data: T) -> Result<(), (T, Self::Error)>;
+    /// Overwrites the entry in place and returns the old value, `handle` stays valid.
+    /// Returns `data` if the entry can't be overwritten.
+    fn replace(&mut self, handle: VHandle<'id, T>, data: T) -> Result<T, (T, Self::Error)>;
+}
+impl<'id, T, H, V> Reversible<'id, T> for Manager<'id, Typed<T>, Versioned<true, H, V>>
+where
+    GlobalConfig<Typed<T>, Versioned<true, H, V>>: for<'x> Config<
+            Store: ReusableStore<(Version, T)> + Restore<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<true, H, V>>,
+        >,
+{
+    type Error = ManagerError;
+
+    fn insert(&mut self, data: T) -> Result<VHandle<'id, T>, (T, ManagerError)> {
+        let data = match self.insert_within_capacity(data) {
+            Ok(handle) => return Ok(handle),
+            Err(data) => data,
+        };
+        if let Err(err) = self.reserve(1) {
+            return Err((data, err));
+        }
+        let Ok(handle) = self.insert_within_capacity(data) else {
+            unreachable!("insert after reserve should always be successful")
+        };
+        Ok(handle)
+    }
+
+    fn remove(&mut self, handle: VHandle<'id, T>) -> MResult<T> {
+        Manager::remove(self, handle)
+    }
+
+    fn restore(&mut self, handle: VHandle<'id, T>, data: T) -> Result<(), (T, ManagerError)> {
+        Manager::restore(self, handle, data)
+    }
+
+    fn replace(&mut self, handle: VHandle<'id, T>, data: T) -> Result<T, (T, ManagerError)> {
+        match self.get_mut(handle) {
+            Ok(old) => Ok(std::mem::replace(old, data)),
+            Err(err) => Err((data, err)),
+        }
+    }
+}
+impl<'id, C, L, H, V> Reversible<'id, C> for Manager<'id, SoA<C, L>, Versioned<true, H, V>>
+where
+    C: Columns,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Versioned<true, H, V>>: for<'x> Config<
+            Store: ReusableSoAStore<Prefix<Version, C>, VHandle<'id, C>>
+                       + Restore<Prefix<Version, C>>,
+            Manager<'x> = VManager<'x, SoA<C, L>, Versioned<true, H, V>>,
+        >,
+{
+    type Error = ManagerError;
+
+    fn insert(&mut self, data: C) -> Result<VHandle<'id, C>, (C, ManagerError)> {
+        let data = match self.insert_within_capacity(data) {
+            Ok(handle) => return Ok(handle),
+            Err(data) => data,
+        };
+        if let Err(err) = self.reserve(1) {
+            return Err((data, err));
+        }
+        let Ok(handle) = self.insert_within_capacity(data) else {
+            unreachable!("insert after reserve should always be successful")
+        };
+        Ok(handle)
+    }
+
+    fn remove(&mut self, handle: VHandle<'id, C>) -> MResult<C> {
+        Manager::remove(self, handle)
+    }
+
+    fn restore(&mut self, handle: VHandle<'id, C>, data: C) -> Result<(), (C, ManagerError)> {
+        Manager::restore(self, handle, data)
+    }
+
+    fn replace(&mut self, handle: VHandle<'id, C>, data: C) -> Result<C, (C, ManagerError)> {
+        Manager::replace(self, handle, data)
+    }
+}
+
+/// Change that has to be applied to get back to a previous state.
+#[derive(Debug)]
+enum Edit<'id, T> {
+    Discard(VHandle<'id, T>),
+    Restore(VHandle<'id, T>, T),
+    Replace(VHandle<'id, T>, T),
+}
+impl<'id, T> Edit<'id, T> {
+    /// Applies this change and returns the change that undoes it,
+    /// or this change if it could not be applied.
+    fn apply<R>(self, target: &mut R) -> Result<Self, (Self, R::Error)>
+    where
+        R: Reversible<'id, T>,
+    {
+        match self {
+            Self::Discard(handle) => match target.remove(handle) {
+                Ok(data) => Ok(Self::Restore(handle, data)),
+                Err(err) => Err((Self::Discard(handle), err)),
+            },
+            Self::Restore(handle, data) => match target.restore(handle, data) {
+                Ok(()) => Ok(Self::Discard(handle)),
+                Err((data, err)) => Err((Self::Restore(handle, data), err)),
+            },
+            Self::Replace(handle, data) => match target.replace(handle, data) {
+                Ok(data) => Ok(Self::Replace(handle, data)),
+                Err((data, err)) => Err((Self::Replace(handle, data), err)),
+            },
+        }
+    }
+    fn cost(&self, cost: fn(&T) -> usize) -> usize {
+        size_of::<Self>()
+            + match self {
+                Self::Discard(_) => 0,
+                Self::Restore(_, data) | Self::Replace(_, data) => cost(data),
+            }
+    }
+}
+#[derive(Debug)]
+struct Step<'id, T> {
+    name:  Cow<'static, str>,
+    edits: Vec<Edit<'id, T>>,
+    size:  usize,
+}
+
+/// Records inverse changes to an arena, grouped into named steps.
+///
+/// All changes to the arena have to go through the history, otherwise restoring an entry might fail.
+/// A step that fails is rolled back and kept, so it can be retried after fixing the arena,
+/// only if rolling back fails as well the history is cleared.
+/// The memory budget accounts for the shallow size of recorded entries
+/// and the cost of the values they keep, the oldest steps are dropped when it is exceeded.
+#[derive(Debug)]
+pub struct History<'id, T> {
+    undo:   VecDeque<Step<'id, T>>,
+    redo:   Vec<Step<'id, T>>,
+    open:   bool,
+    size:   usize,
+    budget: usize,
+    cost:   fn(&T) -> usize,
+}
+impl<'id, T> History<'id, T> {
+    /// Values only count with their shallow size, see [`History::with_cost`].
+    pub fn new(budget: usize) -> Self {
+        Self::with_cost(budget, |_| 0)
+    }
+    /// `cost` returns the size of memory owned by a value, e.g. the heap allocation of a `Vec`.
+    pub fn with_cost(budget: usize, cost: fn(&T) -> usize) -> Self {
+        Self { undo: VecDeque::new(), redo: Vec::new(), open: false, size: 0, budget, cost }
+    }
+    /// Groups all following changes into one step, until [`History::end_step`] is called.
+    pub fn begin_step(&mut self, name: impl Into<Cow<'static, str>>) {
+        self.discard_redo();
+        self.undo.push_back(Step { name: name.into(), edits: Vec::new(), size: 0 });
+        self.open = true;
+    }
+    pub fn end_step(&mut self) {
+        self.open = false;
+    }
+    pub fn undo_name(&self) -> Option<&str> {
+        self.undo.back().map(|step| step.name.as_ref())
+    }
+    pub fn redo_name(&self) -> Option<&str> {
+        self.redo.last().map(|step| step.name.as_ref())
+    }
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.open = false;
+        self.size = 0;
+    }
+    fn discard_redo(&mut self) {
+        self.size -= self.redo.drain(..).map(|step| step.size).sum::<usize>();
+    }
+    fn record(&mut self, name: &'static str, edit: Edit<'id, T>) {
+        if !self.open {
+            self.begin_step(name);
+            self.open = false;
+        } else {
+            self.discard_redo();
+        }
+        let Some(step) = self.undo.back_mut() else {
+            unreachable!("there is always a step to record into")
+        };
+        let size = edit.cost(self.cost);
+        step.edits.push(edit);
+        step.size += size;
+        self.size += size;
+        while self.size > self.budget && self.undo.len() > 1 {
+            if let Some(step) = self.undo.pop_front() {
+                self.size -= step.size;
+            }
+        }
+    }
+    /// Applies `step` in reverse order and returns the step that undoes it.
+    ///
+    /// When an edit fails, the edits applied so far are undone and `step` is returned with the error.
+    /// If that fails too, the history is cleared and no step is returned.
+    #[expect(clippy::type_complexity)]
+    fn apply<R>(
+        &mut self,
+        step: Step<'id, T>,
+        target: &mut R,
+    ) -> Result<Step<'id, T>, (Option<Step<'id, T>>, R::Error)>
+    where
+        R: Reversible<'id, T>,
+    {
+        let Step { name, edits: mut pending, size: old } = step;
+        let mut edits = Vec::with_capacity(pending.len());
+        let mut size = 0;
+        while let Some(edit) = pending.pop() {
+            match edit.apply(target) {
+                Ok(edit) => {
+                    size += edit.cost(self.cost);
+                    edits.push(edit);
+                },
+                Err((edit, err)) => {
+                    pending.push(edit);
+                    while let Some(edit) = edits.pop() {
+                        let Ok(edit) = edit.apply(target) else {
+                            self.clear();
+                            return Err((None, err));
+                        };
+                        pending.push(edit);
+                    }
+                    return Err((Some(Step { name, edits: pending, size: old }), err));
+                },
+            }
+        }
+        self.size = self.size - old + size;
+        Ok(Step { name, edits, size })
+    }
+
+    pub fn insert<R>(&mut self, target: &mut R, data: T) -> Result<VHandle<'id, T>, (T, R::Error)>
+    where
+        R: Reversible<'id, T>,
+    {
+        let handle = target.insert(data)?;
+        self.record("insert", Edit::Discard(handle));
+        Ok(handle)
+    }
+    /// Removes the entry, the removed value is kept to restore it later.
+    pub fn remove<R>(&mut self, target: &mut R, handle: VHandle<'id, T>) -> Result<(), R::Error>
+    where
+        R: Reversible<'id, T>,
+    {
+        let data = target.remove(handle)?;
+        self.record("remove", Edit::Restore(handle, data));
+        Ok(())
+    }
+    /// Replaces the entry, the old value is kept to restore it later.
+    pub fn replace<R>(
+        &mut self,
+        target: &mut R,
+        handle: VHandle<'id, T>,
+        data: T,
+    ) -> Result<(), (T, R::Error)>
+    where
+        R: Reversible<'id, T>,
+    {
+        let data = target.replace(handle, data)?;
+        self.record("replace", Edit::Replace(handle, data));
+        Ok(())
+    }
+    /// Undoes the latest step, returns `false` when there was nothing to undo.
+    pub fn undo<R>(&mut self, target: &mut R) -> Result<bool, R::Error>
+    where
+        R: Reversible<'id, T>,
+    {
+        self.open = false;
+        let Some(step) = self.undo.pop_back() else { return Ok(false) };
+        match self.apply(step, target) {
+            Ok(step) => self.redo.push(step),
+            Err((step, err)) => {
+                self.undo.extend(step);
+                return Err(err);
+            },
+        }
+        Ok(true)
+    }
+    /// Redoes the latest undone step, returns `false` when there was nothing to redo.
+    pub fn redo<R>(&mut self, target: &mut R) -> Result<bool, R::Error>
+    where
+        R: Reversible<'id, T>,
+    {
+        let Some(step) = self.redo.pop() else { return Ok(false) };
+        match self.apply(step, target) {
+            Ok(step) => self.undo.push_back(step),
+            Err((step, err)) => {
+                self.redo.extend(step);
+                return Err(err);
+            },
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+
+    #[test]
+    fn undo_redo_restores_handles() {
+        make_guard!(guard);
+        let mut manager = Manager::<Typed<u32>, Versioned<true>>::new(guard);
+        let mut history = History::new(usize::MAX);
+        let a = history.insert(&mut manager, 1).unwrap();
+        history.begin_step("edit");
+        assert_eq!(Ok(()), history.replace(&mut manager, a, 2));
+        assert_eq!(Ok(()), history.remove(&mut manager, a));
+        history.end_step();
+        assert!(manager.get(a).is_err());
+        assert_eq!(Some("edit"), history.undo_name());
+        assert_eq!(Ok(true), history.undo(&mut manager));
+        assert_eq!(Ok(&1), manager.get(a), "removed entry should be valid again");
+        assert_eq!(Ok(true), history.undo(&mut manager));
+        assert!(manager.get(a).is_err(), "undone insert should be removed");
+        assert_eq!(Ok(false), history.undo(&mut manager));
+        assert_eq!(Ok(true), history.redo(&mut manager));
+        assert_eq!(Ok(true), history.redo(&mut manager));
+        assert!(manager.get(a).is_err());
+        assert_eq!(Ok(true), history.undo(&mut manager));
+        assert_eq!(Ok(&1), manager.get(a));
+        let mut history = History::new(0);
+        history.insert(&mut manager, 3).unwrap();
+        history.insert(&mut manager, 4).unwrap();
+        assert_eq!(Ok(true), history.undo(&mut manager));
+        assert_eq!(Ok(false), history.undo(&mut manager), "budget should drop older steps");
+    }
+
+    #[test]
+    fn failed_step_is_rolled_back() {
+        make_guard!(guard);
+        let mut manager = Manager::<Typed<u32>, Versioned<true>>::new(guard);
+        let mut history = History::new(usize::MAX);
+        let a = history.insert(&mut manager, 1).unwrap();
+        let b = history.insert(&mut manager, 2).unwrap();
+        history.begin_step("edit");
+        history.remove(&mut manager, a).unwrap();
+        history.replace(&mut manager, b, 3).unwrap();
+        history.end_step();
+        // takes the slot of `a` behind the back of the history
+        let other = Reversible::insert(&mut manager, 4).unwrap();
+        assert_eq!(a.index, other.index);
+        assert!(history.undo(&mut manager).is_err(), "restoring `a` should fail");
+        assert_eq!(Ok(&3), manager.get(b), "replace should be rolled back");
+        assert_eq!(Some("edit"), history.undo_name(), "failed step should be kept");
+        Reversible::remove(&mut manager, other).unwrap();
+        assert_eq!(Ok(true), history.undo(&mut manager));
+        assert_eq!(Ok(&1), manager.get(a));
+        assert_eq!(Ok(&2), manager.get(b));
+    }
+
+    #[test]
+    fn budget_counts_value_cost() {
+        make_guard!(guard);
+        let mut manager = Manager::<Typed<Vec<u8>>, Versioned<true>>::new(guard);
+        let budget = 2 * size_of::<Edit<Vec<u8>>>() + 64;
+        let mut history = History::with_cost(budget, Vec::capacity);
+        let a = history.insert(&mut manager, vec![0; 64]).unwrap();
+        history.replace(&mut manager, a, Vec::new()).unwrap();
+        history.replace(&mut manager, a, vec![1]).unwrap();
+        assert_eq!(Ok(true), history.undo(&mut manager));
+        assert_eq!(Ok(true), history.undo(&mut manager));
+        assert_eq!(Ok(&vec![0; 64]), manager.get(a));
+        assert_eq!(Ok(false), history.undo(&mut manager), "large values should use up the budget");
+    }
+
+    #[test]
+    fn replace_keeps_version() {
+        make_guard!(guard);
+        let mut manager = Manager::<Typed<u32>, Versioned<true>>::new(guard);
+        let mut history = History::new(usize::MAX);
+        let a = history.insert(&mut manager, 1).unwrap();
+        assert_eq!(Ok(()), history.replace(&mut manager, a, 2));
+        assert_eq!(Ok(true), history.undo(&mut manager));
+        let b = Reversible::insert(&mut manager, 3).unwrap();
+        assert_eq!(a.version, b.version, "overwrites should not bump the version");
+    }
+
+    #[test]
+    fn restores_soa_rows() {
+        make_guard!(guard);
+        let mut manager = Manager::<SoA<(u32, f32)>, Versioned<true>>::new(guard);
+        let mut history = History::new(usize::MAX);
+        let a = history.insert(&mut manager, (1, 1.0)).unwrap();
+        assert_eq!(Ok(()), history.replace(&mut manager, a, (2, 2.0)));
+        assert_eq!(Ok(&2), manager.view().col0(a));
+        assert_eq!(Ok(true), history.undo(&mut manager));
+        assert_eq!(Ok(&1), manager.view().col0(a), "replaced row should be restored");
+    }
+}
@@ -0,0 +1,428 @@
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    ops::RangeBounds,
+};
+
+use super::*;
+use crate::alloc::store::*;
+
+/// Where the rows ended up after [`Manager::permute`].
+///
+/// Rows that were moved get a new version, so their old handles are invalid.
+#[derive(Debug, Clone)]
+pub struct Relocation<'id, T> {
+    /// Old and new handle of every row, ordered by the old index.
+    handles: Vec<(VHandle<'id, T>, VHandle<'id, T>)>,
+}
+impl<'id, T> Relocation<'id, T> {
+    /// The new handle of a row that was valid before the permutation.
+    pub fn get(&self, handle: VHandle<'id, T>) -> Option<VHandle<'id, T>> {
+        let i = self.handles.binary_search_by_key(&handle.index, |(old, _)| old.index).ok()?;
+        let (old, new) = self.handles[i];
+        (old.version == handle.version).then_some(new)
+    }
+    /// Old and new handle of every row that was moved.
+    pub fn moved(&self) -> impl Iterator<Item = (VHandle<'id, T>, VHandle<'id, T>)> + '_ {
+        self.handles.iter().copied().filter(|(old, new)| old.index != new.index)
+    }
+}
+
+/// Rows grouped by their key.
+trait Groups<K>: Default {
+    fn group(&mut self, key: K) -> &mut Vec<Index>;
+    fn existing(&mut self, key: &K) -> Option<&mut Vec<Index>>;
+    fn remove_group(&mut self, key: &K);
+    fn clear(&mut self);
+}
+impl<K: Ord> Groups<K> for BTreeMap<K, Vec<Index>> {
+    fn group(&mut self, key: K) -> &mut Vec<Index> {
+        self.entry(key).or_default()
+    }
+    fn existing(&mut self, key: &K) -> Option<&mut Vec<Index>> {
+        self.get_mut(key)
+    }
+    fn remove_group(&mut self, key: &K) {
+        self.remove(key);
+    }
+    fn clear(&mut self) {
+        self.clear();
+    }
+}
+impl<K: Hash + Eq> Groups<K> for HashMap<K, Vec<Index>> {
+    fn group(&mut self, key: K) -> &mut Vec<Index> {
+        self.entry(key).or_default()
+    }
+    fn existing(&mut self, key: &K) -> Option<&mut Vec<Index>> {
+        self.get_mut(key)
+    }
+    fn remove_group(&mut self, key: &K) {
+        self.remove(key);
+    }
+    fn clear(&mut self) {
+        self.clear();
+    }
+}
+
+/// Secondary index over one column, keeping a copy of the key of every row.
+struct ColumnIndex<K, G> {
+    /// Column within the rows of the store.
+    column: usize,
+    /// Size of one slot of the column.
+    size:   usize,
+    keys:   Vec<Option<(K, Version)>>,
+    groups: G,
+}
+type SortedIndex<K> = ColumnIndex<K, BTreeMap<K, Vec<Index>>>;
+type HashIndex<K> = ColumnIndex<K, HashMap<K, Vec<Index>>>;
+
+trait RowIndex: Any + Send + Sync {
+    fn column(&self) -> usize;
+    /// # Safety
+    /// `index` has to be occupied and `rows` have to hold the indexed column.
+    unsafe fn insert(&mut self, rows: &RawRows<'_>, index: Index, version: Version);
+    fn remove(&mut self, index: Index);
+    fn clear(&mut self);
+    fn as_any(&self) -> &dyn Any;
+}
+impl<K, G> RowIndex for ColumnIndex<K, G>
+where
+    K: Clone + Send + Sync + 'static,
+    G: Groups<K> + Send + Sync + 'static,
+{
+    fn column(&self) -> usize {
+        self.column
+    }
+    unsafe fn insert(&mut self, rows: &RawRows<'_>, index: Index, version: Version) {
+        // SAFETY: guarantied by caller, the key is stored at offset 0 of its slot
+        let key = unsafe { rows.element(self.column, index, self.size).cast::<K>().as_ref() };
+        let i = index.get() as usize;
+        if self.keys.len() <= i {
+            self.keys.resize_with(i + 1, || None);
+        }
+        self.keys[i] = Some((key.clone(), version));
+        self.groups.group(key.clone()).push(index);
+    }
+    fn remove(&mut self, index: Index) {
+        let Some((key, _)) = self.keys.get_mut(index.get() as usize).and_then(Option::take) else {
+            return;
+        };
+        let Some(group) = self.groups.existing(&key) else { return };
+        group.retain(|i| *i != index);
+        if group.is_empty() {
+            self.groups.remove_group(&key);
+        }
+    }
+    fn clear(&mut self) {
+        self.keys.clear();
+        self.groups.clear();
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Secondary indexes of a [`SoA`] manager, see [`Manager::add_sorted_index`].
+#[derive(Default)]
+pub(super) struct Indexes {
+    indexes: Vec<Box<dyn RowIndex>>,
+    /// Set when the rows were accessed mutably since the indexes were built.
+    stale:   bool,
+}
+impl Indexes {
+    pub(super) fn is_empty(&self) -> bool {
+        self.indexes.is_empty()
+    }
+    pub(super) fn mark_stale(&mut self) {
+        self.stale = !self.indexes.is_empty();
+    }
+    /// # Safety
+    /// `index` has to be occupied and `rows` have to hold every indexed column.
+    pub(super) unsafe fn insert(&mut self, rows: &RawRows<'_>, index: Index, version: Version) {
+        for row_index in &mut self.indexes {
+            // SAFETY: guarantied by caller
+            unsafe { row_index.insert(rows, index, version) };
+        }
+    }
+    pub(super) fn remove(&mut self, index: Index) {
+        for row_index in &mut self.indexes {
+            row_index.remove(index);
+        }
+    }
+    pub(super) fn clear(&mut self) {
+        for row_index in &mut self.indexes {
+            row_index.clear();
+        }
+    }
+    /// Indexes every occupied row of `rows` again, column 0 has to hold the versions.
+    pub(super) fn rebuild(&mut self, rows: &RawRows<'_>) {
+        self.stale = false;
+        self.clear();
+        for index in rows.bitmap().ones() {
+            // SAFETY: index is occupied and column 0 holds the versions
+            unsafe {
+                let version =
+                    rows.slot::<FreelistEntry<Version>>(0, index).cast::<Version>().read();
+                self.insert(rows, index, version);
+            }
+        }
+    }
+    fn add(&mut self, row_index: Box<dyn RowIndex>, rows: &RawRows<'_>) {
+        self.indexes.retain(|existing| existing.column() != row_index.column());
+        self.indexes.push(row_index);
+        self.rebuild(rows);
+    }
+    fn remove_index(&mut self, column: usize) -> bool {
+        let len = self.indexes.len();
+        self.indexes.retain(|existing| existing.column() != column);
+        len != self.indexes.len()
+    }
+    fn find<T: 'static>(&self, column: usize) -> MResult<&T> {
+        if self.stale {
+            return Err(ManagerError::BadIndex("rows were accessed mutably, refresh the indexes"));
+        }
+        self.indexes
+            .iter()
+            .find(|row_index| row_index.column() == column)
+            .and_then(|row_index| row_index.as_any().downcast_ref())
+            .ok_or(ManagerError::BadIndex("no index of this kind on the column"))
+    }
+}
+
+impl<'id, C, L, const REUSE: bool, H, V> Manager<'id, SoA<C, L>, Versioned<REUSE, H, V>>
+where
+    C: Columns,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: SoAStore<Prefix<Version, C>, VHandle<'id, C>>,
+            Manager<'x> = VManager<'x, SoA<C, L>, Versioned<REUSE, H, V>>,
+        >,
+{
+    fn add_index<const N: usize, G>(&mut self)
+    where
+        C: Column<N, Item: Clone + Send + Sync + 'static>,
+        G: Groups<C::Item> + Send + Sync + 'static,
+    {
+        let row_index = ColumnIndex::<C::Item, G> {
+            // column 0 is the version
+            column: N + 1,
+            size:   size_of::<C::Slot>(),
+            keys:   Vec::new(),
+            groups: G::default(),
+        };
+        // SAFETY: indexes only read the rows
+        self.0.indexes.add(Box::new(row_index), &unsafe { self.0.store.raw_rows() });
+    }
+    /// Keeps the rows ordered by column `N`, which allows [`Manager::range`] queries.
+    ///
+    /// The index is kept up to date on insert and remove, after writes through
+    /// [`Manager::view_mut`] it has to be rebuilt with [`Manager::refresh_indexes`].
+    /// Replaces any other index on column `N`.
+    pub fn add_sorted_index<const N: usize>(&mut self)
+    where
+        C: Column<N, Item: Ord + Clone + Send + Sync + 'static>,
+    {
+        self.add_index::<N, BTreeMap<C::Item, Vec<Index>>>();
+    }
+    /// Groups the rows by column `N`, which allows [`Manager::lookup`] queries.
+    ///
+    /// Maintained like [`Manager::add_sorted_index`].
+    pub fn add_hash_index<const N: usize>(&mut self)
+    where
+        C: Column<N, Item: Hash + Eq + Clone + Send + Sync + 'static>,
+    {
+        self.add_index::<N, HashMap<C::Item, Vec<Index>>>();
+    }
+    /// Returns `false` if there was no index on column `N`.
+    pub fn remove_index<const N: usize>(&mut self) -> bool {
+        self.0.indexes.remove_index(N + 1)
+    }
+    /// Rebuilds all indexes from the current rows.
+    pub fn refresh_indexes(&mut self) {
+        // SAFETY: indexes only read the rows
+        self.0.indexes.rebuild(&unsafe { self.0.store.raw_rows() });
+    }
+    /// Handles of the rows whose column `N` lies within `range`, ordered by the column.
+    pub fn range<const N: usize>(
+        &self,
+        range: impl RangeBounds<C::Item>,
+    ) -> MResult<impl Iterator<Item = VHandle<'id, C>>>
+    where
+        C: Column<N, Item: Ord + Clone + Send + Sync + 'static>,
+    {
+        let row_index = self.0.indexes.find::<SortedIndex<C::Item>>(N + 1)?;
+        Ok(row_index
+            .groups
+            .range(range)
+            .flat_map(|(_, group)| group)
+            .map(|index| self.index_handle(&row_index.keys, *index)))
+    }
+    /// Handles of the rows whose column `N` equals `key`.
+    pub fn lookup<const N: usize>(
+        &self,
+        key: &C::Item,
+    ) -> MResult<impl Iterator<Item = VHandle<'id, C>>>
+    where
+        C: Column<N, Item: Hash + Eq + Clone + Send + Sync + 'static>,
+    {
+        let row_index = self.0.indexes.find::<HashIndex<C::Item>>(N + 1)?;
+        Ok(row_index
+            .groups
+            .get(key)
+            .into_iter()
+            .flatten()
+            .map(|index| self.index_handle(&row_index.keys, *index)))
+    }
+    fn index_handle<K>(&self, keys: &[Option<(K, Version)>], index: Index) -> VHandle<'id, C> {
+        let (_, version) = keys[index.get() as usize].as_ref().expect("indexed rows have a key");
+        self.0.handle(index, *version)
+    }
+}
+impl<'id, C, L, H, V> Manager<'id, SoA<C, L>, Versioned<true, H, V>>
+where
+    C: Columns,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Versioned<true, H, V>>: for<'x> Config<
+            Store: ReusableSoAStore<Prefix<Version, C>, VHandle<'id, C>>
+                       + Restore<Prefix<Version, C>>,
+            Manager<'x> = VManager<'x, SoA<C, L>, Versioned<true, H, V>>,
+        >,
+{
+    /// Moves the rows so that they are stored in the order of `order`,
+    /// which has to contain the handle of every row exactly once.
+    ///
+    /// The rows keep occupying the same set of slots. Moved rows get a new version,
+    /// the returned [`Relocation`] maps their old handles to the new ones.
+    /// Moves are recorded as removal of the old and insertion of the new handle.
+    pub fn permute(&mut self, order: &[VHandle<'id, C>]) -> MResult<Relocation<'id, C>> {
+        // SAFETY: only the occupation table is read
+        let slots: Vec<_> = unsafe { self.0.store.raw_rows() }.bitmap().ones().collect();
+        let mut seen = vec![false; self.0.store.capacity() as usize];
+        let versions = self.0.store.view().part0();
+        for handle in order {
+            if *versions.col0(*handle)? != handle.version {
+                return Err(ManagerError::BadHandle("version mismatch"));
+            }
+            if std::mem::replace(&mut seen[handle.index.get() as usize], true) {
+                return Err(ManagerError::BadHandle("row appears more than once"));
+            }
+        }
+        if order.len() != slots.len() {
+            return Err(ManagerError::BadHandle("permutation has to contain every row"));
+        }
+        let version = self.0.version.checked_add(1).unwrap_or(VERSION1);
+        let mut handles: Vec<_> = order
+            .iter()
+            .zip(&slots)
+            .map(|(old, slot)| {
+                let new = if old.index == *slot { *old } else { self.0.handle(*slot, version) };
+                (*old, new)
+            })
+            .collect();
+        let mut moved: Vec<_> =
+            handles.iter().filter(|(old, new)| old.index != new.index).collect();
+        if !moved.is_empty() {
+            self.0.version = version;
+            // rows are removed by ascending and restored by descending index,
+            // so every restored slot is the head of the freelist
+            moved.sort_unstable_by_key(|(old, _)| old.index);
+            let mut rows = Vec::with_capacity(moved.len());
+            for (old, new) in moved {
+                let data = self.0.store.remove(old.index)?;
+                self.0.ticks.mark_removed(old.index, old.version);
+                rows.push((new.index, data.into_rest()));
+            }
+            rows.sort_unstable_by_key(|(index, _)| std::cmp::Reverse(*index));
+            for (index, data) in rows {
+                self.0.store.restore(index, Prefix::new(version, data)).map_err(|(_, err)| err)?;
+                self.0.ticks.mark(index);
+            }
+        }
+        if !self.0.indexes.is_empty() {
+            // SAFETY: indexes only read the rows
+            self.0.indexes.rebuild(&unsafe { self.0.store.raw_rows() });
+        }
+        handles.sort_unstable_by_key(|(old, _)| old.index);
+        Ok(Relocation { handles })
+    }
+    /// Stably sorts the rows by column `N`, see [`Manager::permute`].
+    pub fn sort_by_column<const N: usize>(&mut self) -> MResult<Relocation<'id, C>>
+    where
+        C: Column<N, Item: Ord>,
+    {
+        // SAFETY: the rows are only read to collect the keys
+        let rows = unsafe { self.0.store.raw_rows() };
+        let versions = self.0.store.view().part0();
+        let mut order: Vec<_> = rows
+            .bitmap()
+            .ones()
+            .map(|index| {
+                // SAFETY: index is occupied, column N + 1 holds the slots of column N
+                let key = unsafe {
+                    rows.element(N + 1, index, size_of::<C::Slot>()).cast::<C::Item>().as_ref()
+                };
+                let version = *versions.col0(self.0.handle(index, VERSION1)).expect("occupied");
+                (key, self.0.handle(index, version))
+            })
+            .collect();
+        order.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let order: Vec<_> = order.into_iter().map(|(_, handle)| handle).collect();
+        self.permute(&order)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+
+    type Rows<'id> = Manager<'id, SoA<(u32, &'static str)>, Versioned<true>>;
+
+    fn names<'id>(
+        manager: &Rows<'id>,
+        handles: impl Iterator<Item = VHandle<'id, (u32, &'static str)>>,
+    ) -> Vec<&'static str> {
+        handles.map(|handle| *manager.view().col1(handle).unwrap()).collect()
+    }
+
+    #[test]
+    fn sorts_rows_and_keeps_indexes() {
+        make_guard!(guard);
+        let mut manager = Rows::new(guard);
+        manager.reserve(8).unwrap();
+        let handles: Vec<_> = [(30, "c"), (10, "a"), (20, "b"), (10, "d"), (40, "e")]
+            .into_iter()
+            .map(|row| manager.insert_within_capacity(row).unwrap())
+            .collect();
+        manager.add_sorted_index::<0>();
+        manager.add_hash_index::<1>();
+        manager.remove(handles[4]).unwrap();
+        let extra = manager.insert_within_capacity((15, "f")).unwrap();
+        let found = manager.range::<0>(10..=20).unwrap();
+        assert_eq!(vec!["a", "d", "f", "b"], names(&manager, found));
+        assert_eq!(vec![handles[2]], manager.lookup::<1>(&"b").unwrap().collect::<Vec<_>>());
+
+        let relocation = manager.sort_by_column::<0>().unwrap();
+        let values: Vec<_> =
+            manager.view().column_slice::<0>().unwrap().iter().map(|(_, v)| *v).collect();
+        assert_eq!(vec![10, 10, 15, 20, 30], values);
+        let a = relocation.get(handles[1]).unwrap();
+        assert_eq!(Ok(&"a"), manager.view().col1(a));
+        assert!(manager.remove(handles[1]).is_err(), "moved rows get a new version");
+        assert_eq!(None, relocation.get(handles[4]));
+        assert_eq!(5, relocation.moved().count());
+        assert_eq!(
+            vec![relocation.get(extra).unwrap()],
+            manager.lookup::<1>(&"f").unwrap().collect::<Vec<_>>()
+        );
+
+        *manager.view_mut().col0_mut(a).unwrap() = 50;
+        assert!(manager.range::<0>(..).is_err());
+        manager.refresh_indexes();
+        let found = manager.range::<0>(40..).unwrap();
+        assert_eq!(vec!["a"], names(&manager, found));
+        assert!(manager.range::<1>(..).is_err(), "column 1 has a hash index");
+    }
+}
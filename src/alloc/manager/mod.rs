@@ -1,4 +1,9 @@
+mod collect;
 mod exclusive;
+mod gather;
+mod history;
+mod index;
+mod track;
 use std::{
     array,
     marker::PhantomData,
@@ -8,7 +13,11 @@ use std::{
     slice,
 };
 
+pub use collect::*;
 pub use exclusive::*;
+pub use history::*;
+pub use index::*;
+pub use track::*;
 mod version;
 use thiserror::Error;
 pub use version::*;
@@ -21,6 +30,12 @@ pub enum ManagerError {
     StoreError(#[from] StoreError),
     #[error("bad handle {0}")]
     BadHandle(&'static str),
+    #[error("bad checkpoint {0}")]
+    BadCheckpoint(&'static str),
+    #[error("bad index {0}")]
+    BadIndex(&'static str),
+    #[error("bad buffer {0}")]
+    BadBuffer(&'static str),
 }
 pub type MResult<T> = Result<T, ManagerError>;
 
@@ -28,6 +43,9 @@ pub trait Config {
     type Store;
     type Manager<'id>;
     type Arena<'id, 'man>;
+
+    /// Number of change ticks recorded per slot of `store`, see [`Tracked`].
+    fn tracked_columns(store: &Self::Store) -> usize;
 }
 pub struct GlobalConfig<K, C>(PhantomData<(K, C)>);
 pub type RemoveSliceGuard<'a, U, C> =
@@ -44,56 +62,93 @@ impl_RawBytes!(u16);
 impl_RawBytes!(u32);
 impl_RawBytes!(u64);
 impl_RawBytes!(u128);
+/// How the rows of a [`SoA`] are laid out in memory.
+pub trait SoALayout {
+    type Store<C: Columns>: RawView<C>;
+}
+/// Every column is a single array, see [`SoAFreelistStore`].
+pub struct Columnar;
+impl SoALayout for Columnar {
+    type Store<C: Columns> = SoAFreelistStore<C>;
+}
+/// Rows are kept in chunks of `ROWS` rows with columns aligned to `ALIGN`, see [`AoSoAStore`].
+pub struct Chunked<const ROWS: usize = 64, const ALIGN: usize = 64>;
+impl<const ROWS: usize, const ALIGN: usize> SoALayout for Chunked<ROWS, ALIGN> {
+    type Store<C: Columns> = AoSoAStore<C, ROWS, ALIGN>;
+}
+pub type LayoutStore<C, L> = <L as SoALayout>::Store<C>;
 pub struct Versioned<const REUSE: bool = false, H = Headless, V = ()>(PhantomData<(H, V)>);
 pub struct Exclusive<const REUSE: bool = false, V = ()>(PhantomData<V>);
 
 macro_rules! kind {
-    ($vis:vis struct $name:ident $(<$($T:ident),*>)? [[$elX:ty, $elV:ty], [$storeS:ident, $storeR:ident]] $(where $($where:tt)*)?) => {
-        $vis struct $name$(<$($T),*>(PhantomData<($($T,)*)>))? $(where $($where)*)?;
+    ($vis:vis struct $name:ident $(<$($T:ident $(= $D:ty)?),*>)? [[$elX:ty, $elV:ty], [$storeS:ident, $storeR:ident, $storeVR:ident $(; $($S:ty),*)?], |$store:pat_param| $columns:expr] $(where $($where:tt)*)?) => {
+        $vis struct $name$(<$($T $(= $D)?),*>(PhantomData<($($T,)*)>))? $(where $($where)*)?;
         impl$(<$($T),*>)? Config for GlobalConfig<$name$(<$($T),*>)?, Exclusive<false>> $(where $($where)*)? {
-            type Store = $storeS<$elX>;
+            type Store = $storeS<$elX $($(, $S)*)?>;
             type Manager<'id> = XManager<'id, $name$(<$($T),*>)?, Exclusive<false>>;
             type Arena<'id, 'man> = XArena<'id, $name$(<$($T),*>)?, Exclusive<false>>;
+
+            fn tracked_columns(_: &Self::Store) -> usize {
+                0
+            }
         }
         impl$(<$($T),*>)? Config for GlobalConfig<$name$(<$($T),*>)?, Exclusive<true>> $(where $($where)*)? {
-            type Store = $storeR<$elX>;
+            type Store = $storeR<$elX $($(, $S)*)?>;
             type Manager<'id> = XManager<'id, $name$(<$($T),*>)?, Exclusive<true>>;
             type Arena<'id, 'man> = XArena<'id, $name$(<$($T),*>)?, Exclusive<true>>;
+
+            fn tracked_columns(_: &Self::Store) -> usize {
+                0
+            }
         }
-        impl<H$(, $($T),*)?> Config for GlobalConfig<$name$(<$($T),*>)?, Versioned<false, H>> $(where $($where)*)? {
-            type Store = $storeS<$elV>;
-            type Manager<'id> = VManager<'id, $name$(<$($T),*>)?, Versioned<false, H>>;
-            type Arena<'id, 'man> = VArena<'id, 'man, $name$(<$($T),*>)?, Versioned<false, H>>;
+        impl<H, V: Tracking$(, $($T),*)?> Config for GlobalConfig<$name$(<$($T),*>)?, Versioned<false, H, V>> $(where $($where)*)? {
+            type Store = $storeS<$elV $($(, $S)*)?>;
+            type Manager<'id> = VManager<'id, $name$(<$($T),*>)?, Versioned<false, H, V>>;
+            type Arena<'id, 'man> = VArena<'id, 'man, $name$(<$($T),*>)?, Versioned<false, H, V>>;
+
+            fn tracked_columns($store: &Self::Store) -> usize {
+                $columns
+            }
         }
-        impl<H$(, $($T),*)?> Config for GlobalConfig<$name$(<$($T),*>)?, Versioned<true, H>> $(where $($where)*)? {
-            type Store = $storeS<$elV>;
-            type Manager<'id> = VManager<'id, $name$(<$($T),*>)?, Versioned<true, H>>;
-            type Arena<'id, 'man> = VArena<'id, 'man, $name$(<$($T),*>)?, Versioned<true, H>>;
+        impl<H, V: Tracking$(, $($T),*)?> Config for GlobalConfig<$name$(<$($T),*>)?, Versioned<true, H, V>> $(where $($where)*)? {
+            type Store = $storeVR<$elV $($(, $S)*)?>;
+            type Manager<'id> = VManager<'id, $name$(<$($T),*>)?, Versioned<true, H, V>>;
+            type Arena<'id, 'man> = VArena<'id, 'man, $name$(<$($T),*>)?, Versioned<true, H, V>>;
+
+            fn tracked_columns($store: &Self::Store) -> usize {
+                $columns
+            }
         }
     };
 }
 kind! {
     pub struct Typed<T>[
         [T, (Version, T)],
-        [SimpleStore, FreelistStore]
+        [SimpleStore, FreelistStore, FreelistStore],
+        |_| 1
     ]
 }
 kind! {
-    pub struct SoA<C>[
+    pub struct SoA<C, L = Columnar>[
         [C, Prefix<Version, C>],
-        [SoAFreelistStore, SoAFreelistStore]
-    ] where C: Columns
+        [LayoutStore, LayoutStore, LayoutStore; L],
+        |store| Prefix::<Version, C>::count(RawView::<Prefix<Version, C>>::schema(store))
+    ] where C: Columns, L: SoALayout
 }
 kind! {
     pub struct Slices<U>[
         [U, U],
-        [SimpleStore, IntervaltreeStore]
+        // TODO: reuse slices of versioned arenas, removed ranges need a version to reject stale handles
+        [SimpleStore, IntervaltreeStore, SimpleStore],
+        |_| 0
     ] where U: RawBytes
 }
 kind! {
     pub struct Mixed<U>[
         [U, U],
-        [SimpleStore, IntervaltreeStore]
+        // TODO: reuse entries of versioned arenas, removed ranges need a version to reject stale handles
+        [SimpleStore, IntervaltreeStore, SimpleStore],
+        |_| 0
     ] where U: RawBytes
 }
 
@@ -210,10 +265,13 @@ impl<U: RawBytes> Slices<U> {
         // SAFETY: guarantied by caller
         unsafe { copy_nonoverlapping(src.as_ptr(), dst.as_mut_ptr() as *mut T, src.len()) };
     }
+    /// Removes the header at `header` together with the data after it,
+    /// the guard holds both.
     /// # Safety
     /// `index` and `len` are not checked (results of `read_header` are always valid).
     unsafe fn delete_slice<'a, T: Copy, S: ReusableMultiStore<U>>(
         store: &'a mut S,
+        header: Index,
         index: Index,
         len: Length,
     ) -> SResult<<S as RemoveIndirect<Multi<U>>>::Guard<'a>>
@@ -221,8 +279,7 @@ impl<U: RawBytes> Slices<U> {
         U: 'a,
     {
         let range = Self::range_of::<T>(index, len)?;
-        // FIXME: this only removes the data, not the header!
-        store.remove_indirect(range)
+        store.remove_indirect(header..range.end)
     }
 }
 impl<U: RawBytes> Mixed<U> {
@@ -296,3 +353,33 @@ impl<U: RawBytes> Mixed<U> {
         Ok(unsafe { result.assume_init() })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+
+    #[test]
+    fn reusable_versioned_managers_reuse_slots() {
+        make_guard!(guard);
+        let mut manager = Manager::<Typed<u32>, Versioned<true>>::new(guard);
+        assert_eq!(Ok(()), manager.reserve(1));
+        let a = manager.insert_within_capacity(1).ok().unwrap();
+        assert_eq!(Ok(1), manager.remove(a));
+        let b = manager
+            .insert_within_capacity(2)
+            .expect("freed slot should be reused without allocation");
+        assert!(manager.get(a).is_err(), "stale handle should not access the reused slot");
+        assert_eq!(Ok(&2), manager.get(b));
+    }
+
+    #[test]
+    fn reusable_versioned_slices_can_be_created() {
+        make_guard!(guard);
+        let mut manager = Manager::<Slices<u32>, Versioned<true>>::new(guard);
+        assert_eq!(Ok(()), manager.reserve(16));
+        let handle = manager.insert_within_capacity(&[1u16, 2, 3]).unwrap();
+        assert_eq!(Ok(&[1, 2, 3][..]), manager.get(handle));
+    }
+}
@@ -0,0 +1,206 @@
+use std::ptr::NonNull;
+
+use super::*;
+use crate::alloc::store::*;
+
+/// Selects if a [`Versioned`] manager records change ticks.
+pub trait Tracking {
+    const ENABLED: bool;
+}
+impl Tracking for () {
+    const ENABLED: bool = false;
+}
+/// Records the tick of the last write for every slot, or every column of a slot for [`SoA`].
+/// Removed entries are remembered until they are forgotten explicitly.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tracked;
+impl Tracking for Tracked {
+    const ENABLED: bool = true;
+}
+
+const TICK1: Tick = 1;
+
+#[derive(Debug)]
+pub(super) struct ChangeTicks {
+    enabled: bool,
+    tick:    Tick,
+    stride:  usize,
+    /// Tick of the last write to each column, `stride` ticks per slot.
+    changed: Vec<Tick>,
+    removed: Vec<(Index, Version, Tick)>,
+}
+impl ChangeTicks {
+    pub(super) fn new(enabled: bool, stride: usize, capacity: Length) -> Self {
+        let mut ticks =
+            Self { enabled, tick: TICK1, stride, changed: Vec::new(), removed: Vec::new() };
+        ticks.resize(capacity);
+        ticks
+    }
+    /// This has to be called whenever the capacity of the store changes.
+    pub(super) fn resize(&mut self, capacity: Length) {
+        if self.enabled {
+            self.changed.resize(capacity as usize * self.stride, 0);
+        }
+    }
+    pub(super) fn tick(&self) -> Tick {
+        self.tick
+    }
+    pub(super) fn advance(&mut self) -> Tick {
+        self.tick += 1;
+        self.tick
+    }
+    pub(super) fn mark(&mut self, index: Index) {
+        let start = index.get() as usize * self.stride;
+        if let Some(ticks) = self.changed.get_mut(start..start + self.stride) {
+            ticks.fill(self.tick);
+        }
+    }
+    /// Marks every slot, unoccupied slots are never reported.
+    #[cfg(feature = "rayon")]
+    pub(super) fn mark_all(&mut self) {
+        self.changed.fill(self.tick);
+    }
+    pub(super) fn mark_removed(&mut self, index: Index, version: Version) {
+        if self.enabled {
+            self.removed.push((index, version, self.tick));
+        }
+    }
+    /// Creates a sink for all slots within `capacity`.
+    pub(super) fn sink(&mut self, capacity: Length) -> Option<TickSink> {
+        if !self.enabled {
+            return None;
+        }
+        assert!(self.changed.len() >= capacity as usize * self.stride, "ticks were not resized");
+        // SAFETY: there are `stride` ticks for every slot within capacity
+        Some(unsafe {
+            TickSink::new(NonNull::new_unchecked(self.changed.as_mut_ptr()), self.stride, self.tick)
+        })
+    }
+    pub(super) fn changed_since(&self, column: usize, tick: Tick) -> impl Iterator<Item = Index> {
+        assert!(column < self.stride, "column out of bounds");
+        self.changed
+            .iter()
+            .skip(column)
+            .step_by(self.stride)
+            .enumerate()
+            .filter(move |(_, changed)| **changed >= tick)
+            // SAFETY: there are only ticks for slots within capacity
+            .map(|(i, _)| unsafe { Index::new_unchecked(i as u32) })
+    }
+    pub(super) fn removed_since(&self, tick: Tick) -> impl Iterator<Item = (Index, Version)> {
+        let start = self.removed.partition_point(|(_, _, removed)| *removed < tick);
+        self.removed[start..].iter().map(|(index, version, _)| (*index, *version))
+    }
+    pub(super) fn forget_removed(&mut self, before: Tick) {
+        let end = self.removed.partition_point(|(_, _, removed)| *removed < before);
+        self.removed.drain(..end);
+    }
+}
+
+impl<K, const REUSE: bool, H> Manager<'_, K, Versioned<REUSE, H, Tracked>>
+where
+    GlobalConfig<K, Versioned<REUSE, H, Tracked>>:
+        for<'x> Config<Manager<'x> = VManager<'x, K, Versioned<REUSE, H, Tracked>>>,
+{
+    /// The tick that is recorded for writes.
+    pub fn tick(&self) -> Tick {
+        self.0.ticks.tick()
+    }
+    /// Starts a new tick and returns it, writes before this call are older than the returned tick.
+    pub fn advance_tick(&mut self) -> Tick {
+        self.0.ticks.advance()
+    }
+    /// Forgets all removals that happened before `before`.
+    pub fn forget_removed(&mut self, before: Tick) {
+        self.0.ticks.forget_removed(before);
+    }
+}
+impl<'id, T, const REUSE: bool, H> Manager<'id, Typed<T>, Versioned<REUSE, H, Tracked>>
+where
+    GlobalConfig<Typed<T>, Versioned<REUSE, H, Tracked>>: for<'x> Config<
+            Store: Store<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, Tracked>>,
+        >,
+{
+    /// Handles of the entries that were removed at or after `tick` and not forgotten yet.
+    ///
+    /// Entries discarded by [`Manager::rollback_to`] are not recorded.
+    pub fn removed_since(&self, tick: Tick) -> impl Iterator<Item = VHandle<'id, T>> {
+        self.0.ticks.removed_since(tick).map(|(index, version)| self.0.handle(index, version))
+    }
+    /// Handles of the entries that were inserted or accessed mutably at or after `tick`.
+    pub fn changed_since(&self, tick: Tick) -> impl Iterator<Item = VHandle<'id, T>> {
+        self.0.ticks.changed_since(0, tick).filter_map(|index| {
+            let (version, _) = self.0.store.get(index).ok()?;
+            Some(self.0.handle(index, *version))
+        })
+    }
+}
+impl<'id, C, L, const REUSE: bool, H> Manager<'id, SoA<C, L>, Versioned<REUSE, H, Tracked>>
+where
+    C: Columns,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Versioned<REUSE, H, Tracked>>: for<'x> Config<
+            Store: SoAStore<Prefix<Version, C>, VHandle<'id, C>>,
+            Manager<'x> = VManager<'x, SoA<C, L>, Versioned<REUSE, H, Tracked>>,
+        >,
+{
+    /// Handles of the rows that were removed at or after `tick` and not forgotten yet.
+    ///
+    /// Rows discarded by [`Manager::rollback_to`] are not recorded.
+    pub fn removed_since(&self, tick: Tick) -> impl Iterator<Item = VHandle<'id, C>> {
+        self.0.ticks.removed_since(tick).map(|(index, version)| self.0.handle(index, version))
+    }
+    /// Handles of the rows whose `column` was inserted or accessed mutably at or after `tick`.
+    pub fn changed_since(
+        &self,
+        column: usize,
+        tick: Tick,
+    ) -> impl Iterator<Item = VHandle<'id, C>> {
+        assert!(column < C::count(&self.0.store.schema().1), "column out of bounds");
+        let versions = self.0.store.view().part0();
+        // column 0 is the version
+        self.0.ticks.changed_since(column + 1, tick).filter_map(move |index| {
+            let version = *versions.col0(self.0.handle(index, VERSION1)).ok()?;
+            Some(self.0.handle(index, version))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+    use crate::alloc::arena::Headless;
+
+    #[test]
+    fn reports_changes_since_tick() {
+        make_guard!(guard);
+        let mut manager = Manager::<Typed<u32>, Versioned<true, Headless, Tracked>>::new(guard);
+        manager.reserve(2).unwrap();
+        let a = manager.insert_within_capacity(1).unwrap();
+        let b = manager.insert_within_capacity(2).unwrap();
+        let start = manager.advance_tick();
+        assert_eq!(0, manager.changed_since(start).count());
+        *manager.get_mut(b).unwrap() = 3;
+        manager.remove(a).unwrap();
+        assert_eq!(vec![b], manager.changed_since(start).collect::<Vec<_>>());
+        assert_eq!(vec![a], manager.removed_since(start).collect::<Vec<_>>());
+        let end = manager.advance_tick();
+        manager.forget_removed(end);
+        assert_eq!(0, manager.removed_since(start).count());
+
+        make_guard!(guard);
+        let mut manager =
+            Manager::<SoA<(u32, f32)>, Versioned<true, Headless, Tracked>>::new(guard);
+        manager.reserve(1).unwrap();
+        let a = manager.insert_within_capacity((1, 1.0)).unwrap();
+        let start = manager.advance_tick();
+        *manager.view_mut().col1_mut(a).unwrap() = 2.0;
+        assert_eq!(0, manager.changed_since(0, start).count());
+        assert_eq!(vec![a], manager.changed_since(1, start).collect::<Vec<_>>());
+        manager.remove(a).unwrap();
+        assert_eq!(vec![a], manager.removed_since(start).collect::<Vec<_>>());
+    }
+}
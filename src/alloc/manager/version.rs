@@ -1,18 +1,20 @@
 use std::{array, marker::PhantomData, mem::transmute};
 
 use generativity::{Guard, Id};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 use super::*;
 use crate::alloc::store::*;
 
-const VERSION1: Version = Version::new(1).unwrap();
+pub(super) const VERSION1: Version = Version::new(1).unwrap();
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct VHandle<'man, T: ?Sized> {
-    index:   Index,
-    version: Version,
-    manager: Id<'man>,
-    _marker: PhantomData<fn() -> T>,
+    pub(super) index:   Index,
+    pub(super) version: Version,
+    manager:            Id<'man>,
+    _marker:            PhantomData<fn() -> T>,
 }
 impl<T: ?Sized> Clone for VHandle<'_, T> {
     fn clone(&self) -> Self {
@@ -30,23 +32,81 @@ pub struct VManager<'id, K, C>
 where
     GlobalConfig<K, C>: Config,
 {
-    store:   <GlobalConfig<K, C> as Config>::Store,
-    version: Version,
-    dirty:   bool,
-    id:      Id<'id>,
-    _marker: PhantomData<K>,
+    pub(super) store:   <GlobalConfig<K, C> as Config>::Store,
+    pub(super) version: Version,
+    pub(super) dirty:   bool,
+    pub(super) ticks:   ChangeTicks,
+    pub(super) indexes: Indexes,
+    rollbacks:          Rollbacks,
+    id:                 Id<'id>,
+    _marker:            PhantomData<K>,
+}
+/// Lengths the store was truncated to, used to reject stale [`Checkpoint`]s.
+#[derive(Debug, Default)]
+struct Rollbacks {
+    generation: u64,
+    /// Shortest length reached since each generation, ordered by generation and length.
+    floors:     Vec<(u64, Length)>,
+}
+impl Rollbacks {
+    fn push(&mut self, len: Length) {
+        self.generation += 1;
+        while self.floors.last().is_some_and(|&(_, floor)| floor >= len) {
+            self.floors.pop();
+        }
+        self.floors.push((self.generation, len));
+    }
+    /// Whether the first `len` items were kept by every truncation after `generation`.
+    fn kept(&self, generation: u64, len: Length) -> bool {
+        let after = self.floors.partition_point(|&(g, _)| g <= generation);
+        self.floors.get(after).is_none_or(|&(_, floor)| floor >= len)
+    }
+}
+impl<'id, K, C> VManager<'id, K, C>
+where
+    GlobalConfig<K, C>: Config,
+{
+    pub(super) fn handle<T: ?Sized>(&self, index: Index, version: Version) -> VHandle<'id, T> {
+        VHandle { index, version, manager: self.id, _marker: PhantomData }
+    }
 }
 impl<'id, K, const REUSE: bool, H, V> Manager<'id, K, Versioned<REUSE, H, V>>
 where
-    GlobalConfig<K, Versioned<REUSE, H, V>>:
-        for<'x> Config<Store: Default, Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
+    V: Tracking,
+    GlobalConfig<K, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: Default + Resizable,
+            Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>,
+        >,
 {
     pub fn new(guard: Guard<'id>) -> Self {
+        Self::with_store(guard, Default::default())
+    }
+}
+impl<'id, K, const REUSE: bool, H, V> Manager<'id, K, Versioned<REUSE, H, V>>
+where
+    V: Tracking,
+    GlobalConfig<K, Versioned<REUSE, H, V>>:
+        for<'x> Config<Store: Resizable, Manager<'x> = VManager<'x, K, Versioned<REUSE, H, V>>>,
+{
+    /// Creates a manager around an existing empty store,
+    /// e.g. one holding columns only known at runtime.
+    pub fn with_store(
+        guard: Guard<'id>,
+        store: <GlobalConfig<K, Versioned<REUSE, H, V>> as Config>::Store,
+    ) -> Self {
+        let ticks = ChangeTicks::new(
+            V::ENABLED,
+            <GlobalConfig<K, Versioned<REUSE, H, V>> as Config>::tracked_columns(&store),
+            store.capacity(),
+        );
         Self(VManager {
-            store:   <GlobalConfig<K, Versioned<REUSE, H, V>> as Config>::Store::default(),
+            store,
             version: VERSION1,
-            dirty:   false,
-            id:      guard.into(),
+            dirty: false,
+            ticks,
+            indexes: Indexes::default(),
+            rollbacks: Rollbacks::default(),
+            id: guard.into(),
             _marker: PhantomData,
         })
     }
@@ -60,12 +120,58 @@ where
         let new_capacity = self.0.store.capacity().checked_add(additional).ok_or_else(|| {
             StoreError::OutofMemory(self.0.store.capacity(), self.0.store.capacity() + additional)
         })?;
-        Ok(self.0.store.widen(new_capacity)?)
+        self.0.store.widen(new_capacity)?;
+        self.0.ticks.resize(self.0.store.capacity());
+        Ok(())
     }
     /// This will not drop existing items and might cause a memory leak
     pub fn clear(&mut self) {
         self.0.dirty = true;
         self.0.store.clear();
+        self.0.indexes.clear();
+        self.0.rollbacks.push(0);
+    }
+}
+/// Marks the state of an append-only manager, see [`Manager::rollback_to`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint<'id> {
+    pub(crate) len:        Length,
+    pub(crate) generation: u64,
+    pub(crate) id:         Id<'id>,
+}
+impl<'id, K, H, V> Manager<'id, K, Versioned<false, H, V>>
+where
+    GlobalConfig<K, Versioned<false, H, V>>:
+        for<'x> Config<Store: Truncate, Manager<'x> = VManager<'x, K, Versioned<false, H, V>>>,
+{
+    pub fn checkpoint(&self) -> Checkpoint<'id> {
+        let generation = self.0.rollbacks.generation;
+        Checkpoint { len: self.0.store.len(), generation, id: self.0.id }
+    }
+    pub(crate) fn rollback_to_len(&mut self, len: Length) -> MResult<()> {
+        if len > self.0.store.len() {
+            return Err(ManagerError::BadCheckpoint("checkpoint was already rolled back"));
+        }
+        if len < self.0.store.len() {
+            self.0.store.truncate(len);
+            self.0.dirty = true;
+            self.0.rollbacks.push(len);
+        }
+        Ok(())
+    }
+    pub(crate) fn rollback_to_checkpoint(&mut self, len: Length, generation: u64) -> MResult<()> {
+        if !self.0.rollbacks.kept(generation, len) {
+            return Err(ManagerError::BadCheckpoint("checkpoint was already rolled back"));
+        }
+        self.rollback_to_len(len)
+    }
+    /// Drops everything that was inserted after `checkpoint` was created.
+    /// Handles to dropped items will never be valid again.
+    ///
+    /// Fails if an earlier checkpoint was rolled back to since `checkpoint` was created,
+    /// `checkpoint` itself can be rolled back to any number of times.
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint<'id>) -> MResult<()> {
+        self.rollback_to_checkpoint(checkpoint.len, checkpoint.generation)
     }
 }
 impl<'id, T, const REUSE: bool, H, V> Manager<'id, Typed<T>, Versioned<REUSE, H, V>>
@@ -81,21 +187,24 @@ where
     }
     pub fn get_mut(&mut self, handle: VHandle<'id, T>) -> MResult<&mut T> {
         let (v, data) = self.0.store.get_mut(handle.index)?;
-        (*v == handle.version).then_some(data).ok_or(ManagerError::BadHandle("version mismatch"))
+        if *v != handle.version {
+            return Err(ManagerError::BadHandle("version mismatch"));
+        }
+        self.0.ticks.mark(handle.index);
+        Ok(data)
     }
     pub fn insert_within_capacity(&mut self, data: T) -> Result<VHandle<'id, T>, T> {
         if self.0.dirty {
             self.0.dirty = false;
             self.0.version = self.0.version.checked_add(1).unwrap_or(VERSION1);
         }
-        self.0.store.insert_within_capacity((self.0.version, data)).map_err(|(_, data)| data).map(
-            |index| VHandle {
-                index,
-                version: self.0.version,
-                manager: self.0.id,
-                _marker: PhantomData,
-            },
-        )
+        let index = self
+            .0
+            .store
+            .insert_within_capacity((self.0.version, data))
+            .map_err(|(_, data)| data)?;
+        self.0.ticks.mark(index);
+        Ok(VHandle { index, version: self.0.version, manager: self.0.id, _marker: PhantomData })
     }
     pub(crate) fn bump_version(&mut self, mut handle: VHandle<'id, T>) -> MResult<VHandle<'id, T>> {
         let (v, _) = self.0.store.get_mut(handle.index)?;
@@ -104,9 +213,39 @@ where
         }
         handle.version = handle.version.checked_add(1).unwrap_or(VERSION1);
         *v = handle.version;
+        self.0.ticks.mark(handle.index);
         Ok(handle)
     }
 }
+#[cfg(feature = "rayon")]
+impl<'id, T, const REUSE: bool, H, V> Manager<'id, Typed<T>, Versioned<REUSE, H, V>>
+where
+    GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: Store<(Version, T)> + ParIter<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<REUSE, H, V>>,
+        >,
+{
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = (VHandle<'id, T>, &T)>
+    where
+        T: Sync,
+    {
+        let id = self.0.id;
+        self.0.store.par_iter().map(move |(index, (version, data))| {
+            (VHandle { index, version: *version, manager: id, _marker: PhantomData }, data)
+        })
+    }
+    /// Every entry is recorded as changed.
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = (VHandle<'id, T>, &mut T)>
+    where
+        T: Send,
+    {
+        let id = self.0.id;
+        self.0.ticks.mark_all();
+        self.0.store.par_iter_mut().map(move |(index, (version, data))| {
+            (VHandle { index, version: *version, manager: id, _marker: PhantomData }, data)
+        })
+    }
+}
 impl<'id, T, const REUSE: bool, H, V> Manager<'id, Typed<T>, Versioned<REUSE, H, V>>
 where
     GlobalConfig<Typed<T>, Versioned<REUSE, H, V>>: for<'x> Config<
@@ -122,6 +261,9 @@ where
         if entries.iter().zip(handles).any(|((v, _), handle)| *v != handle.version) {
             return Err(ManagerError::BadHandle("version mismatch"));
         }
+        for handle in handles {
+            self.0.ticks.mark(handle.index);
+        }
         Ok(entries.map(|(_, data)| data))
     }
 }
@@ -138,38 +280,68 @@ where
         }
         let removed = self.0.store.remove(handle.index)?;
         self.0.dirty = true;
+        self.0.ticks.mark_removed(handle.index, handle.version);
         Ok(removed.1)
     }
 }
-impl<'id, C, const REUSE: bool, H, V> Manager<'id, SoA<C>, Versioned<REUSE, H, V>>
+impl<'id, T, H, V> Manager<'id, Typed<T>, Versioned<true, H, V>>
+where
+    GlobalConfig<Typed<T>, Versioned<true, H, V>>: for<'x> Config<
+            Store: ReusableStore<(Version, T)> + Restore<(Version, T)>,
+            Manager<'x> = VManager<'x, Typed<T>, Versioned<true, H, V>>,
+        >,
+{
+    /// Puts a removed entry back, `handle` will be valid again.
+    pub(crate) fn restore(
+        &mut self,
+        handle: VHandle<'id, T>,
+        data: T,
+    ) -> Result<(), (T, ManagerError)> {
+        if let Err(((_, data), err)) = self.0.store.restore(handle.index, (handle.version, data)) {
+            return Err((data, err.into()));
+        }
+        self.0.ticks.mark(handle.index);
+        Ok(())
+    }
+}
+impl<'id, C, L, const REUSE: bool, H, V> Manager<'id, SoA<C, L>, Versioned<REUSE, H, V>>
 where
     C: Columns,
-    GlobalConfig<SoA<C>, Versioned<REUSE, H, V>>: for<'x> Config<
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Versioned<REUSE, H, V>>: for<'x> Config<
             Store: SoAStore<Prefix<Version, C>, VHandle<'id, C>>,
-            Manager<'x> = VManager<'x, SoA<C>, Versioned<REUSE, H, V>>,
+            Manager<'x> = VManager<'x, SoA<C, L>, Versioned<REUSE, H, V>>,
         >,
 {
     pub fn view(&self) -> C::Ref<'_, VHandle<'id, C>> {
         self.0.store.view().part1()
     }
+    /// Indexes are marked as stale, see [`Manager::refresh_indexes`].
     pub fn view_mut(&mut self) -> C::Mut<'_, VHandle<'id, C>> {
-        self.0.store.view_mut().into_part1_mut()
+        self.0.indexes.mark_stale();
+        let Some(ticks) = self.0.ticks.sink(self.0.store.capacity()) else {
+            return self.0.store.view_mut().into_part1_mut();
+        };
+        let (schema, rows) = self.0.store.raw_rows_mut();
+        // SAFETY: the rows were registered by the schema and self is borrowed mutably
+        unsafe { Prefix::<Version, C>::make_mut(schema, rows.with_ticks(ticks)) }.into_part1_mut()
     }
     pub fn insert_within_capacity(&mut self, data: C) -> Result<VHandle<'id, C>, C> {
         if self.0.dirty {
             self.0.dirty = false;
             self.0.version = self.0.version.checked_add(1).unwrap_or(VERSION1);
         }
-        self.0
+        let index = self
+            .0
             .store
             .insert_within_capacity(Prefix::new(self.0.version, data))
-            .map_err(|data| data.0.1)
-            .map(|index| VHandle {
-                index,
-                version: self.0.version,
-                manager: self.0.id,
-                _marker: PhantomData,
-            })
+            .map_err(|data| data.0.1)?;
+        self.0.ticks.mark(index);
+        if !self.0.indexes.is_empty() {
+            // SAFETY: index was just inserted, indexes only read the rows
+            unsafe { self.0.indexes.insert(&self.0.store.raw_rows(), index, self.0.version) };
+        }
+        Ok(VHandle { index, version: self.0.version, manager: self.0.id, _marker: PhantomData })
     }
     pub(crate) fn bump_version(&mut self, mut handle: VHandle<'id, C>) -> MResult<VHandle<'id, C>> {
         let v = self.0.store.view_mut().into_part0_mut().into_col0_mut(handle)?;
@@ -178,15 +350,17 @@ where
         }
         handle.version = handle.version.checked_add(1).unwrap_or(VERSION1);
         *v = handle.version;
+        self.0.ticks.mark(handle.index);
         Ok(handle)
     }
 }
-impl<'id, C, H, V> Manager<'id, SoA<C>, Versioned<true, H, V>>
+impl<'id, C, L, H, V> Manager<'id, SoA<C, L>, Versioned<true, H, V>>
 where
     C: Columns,
-    GlobalConfig<SoA<C>, Versioned<true, H, V>>: for<'x> Config<
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Versioned<true, H, V>>: for<'x> Config<
             Store: ReusableSoAStore<Prefix<Version, C>, VHandle<'id, C>>,
-            Manager<'x> = VManager<'x, SoA<C>, Versioned<true, H, V>>,
+            Manager<'x> = VManager<'x, SoA<C, L>, Versioned<true, H, V>>,
         >,
 {
     pub fn remove(&mut self, handle: VHandle<'id, C>) -> MResult<C> {
@@ -194,7 +368,89 @@ where
         if *v != handle.version {
             return Err(ManagerError::BadHandle("version mismatch"));
         }
-        Ok(self.0.store.remove(handle.index)?.into_rest())
+        let removed = self.0.store.remove(handle.index)?;
+        self.0.dirty = true;
+        self.0.ticks.mark_removed(handle.index, handle.version);
+        self.0.indexes.remove(handle.index);
+        Ok(removed.into_rest())
+    }
+}
+impl<'id, C, L, H, V> Manager<'id, SoA<C, L>, Versioned<true, H, V>>
+where
+    C: Columns,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Versioned<true, H, V>>: for<'x> Config<
+            Store: ReusableSoAStore<Prefix<Version, C>, VHandle<'id, C>>
+                       + Restore<Prefix<Version, C>>,
+            Manager<'x> = VManager<'x, SoA<C, L>, Versioned<true, H, V>>,
+        >,
+{
+    /// Puts a removed entry back, `handle` will be valid again.
+    pub(crate) fn restore(
+        &mut self,
+        handle: VHandle<'id, C>,
+        data: C,
+    ) -> Result<(), (C, ManagerError)> {
+        let data = Prefix::new(handle.version, data);
+        if let Err((data, err)) = self.0.store.restore(handle.index, data) {
+            return Err((data.into_rest(), err.into()));
+        }
+        self.0.ticks.mark(handle.index);
+        if !self.0.indexes.is_empty() {
+            // SAFETY: index was just restored, indexes only read the rows
+            unsafe {
+                self.0.indexes.insert(&self.0.store.raw_rows(), handle.index, handle.version)
+            };
+        }
+        Ok(())
+    }
+    /// Overwrites the row in place, unlike [`Manager::remove`] this keeps other handles valid.
+    pub(crate) fn replace(
+        &mut self,
+        handle: VHandle<'id, C>,
+        data: C,
+    ) -> Result<C, (C, ManagerError)> {
+        match self.0.store.view().part0().col0(handle) {
+            Ok(v) if *v == handle.version => {},
+            Ok(_) => return Err((data, ManagerError::BadHandle("version mismatch"))),
+            Err(err) => return Err((data, err.into())),
+        }
+        let Ok(old) = self.0.store.remove(handle.index) else {
+            unreachable!("validated row should be removable")
+        };
+        let Ok(()) = self.0.store.restore(handle.index, Prefix::new(handle.version, data)) else {
+            unreachable!("removed row should be restorable")
+        };
+        self.0.ticks.mark(handle.index);
+        self.0.indexes.remove(handle.index);
+        if !self.0.indexes.is_empty() {
+            // SAFETY: index was just overwritten, indexes only read the rows
+            unsafe {
+                self.0.indexes.insert(&self.0.store.raw_rows(), handle.index, handle.version)
+            };
+        }
+        Ok(old.into_rest())
+    }
+}
+#[cfg(feature = "arrow")]
+impl<'id, C, L, const REUSE: bool, H, V> Manager<'id, SoA<C, L>, Versioned<REUSE, H, V>>
+where
+    C: ArrowColumns,
+    L: SoALayout,
+    GlobalConfig<SoA<C, L>, Versioned<REUSE, H, V>>: for<'x> Config<
+            Store: SoAStore<Prefix<Version, C>, VHandle<'id, C>>,
+            Manager<'x> = VManager<'x, SoA<C, L>, Versioned<REUSE, H, V>>,
+        >,
+{
+    /// Exports all columns as an Arrow struct array with the fields `names`, without copying.
+    /// Rows that are not occupied are null.
+    pub fn export_arrow(&self, names: &[&str]) -> MResult<ArrowExport<'_>> {
+        // column 0 is the version
+        let count = C::count(&self.0.store.schema().1);
+        // SAFETY: the export only reads the rows while self is borrowed
+        let rows = unsafe { self.0.store.raw_rows() }.slice(1..count + 1);
+        // SAFETY: the layout of the columns is described by C::FORMATS
+        Ok(unsafe { export_rows(rows, C::FORMATS, names) }?)
     }
 }
 impl<'id, U, const REUSE: bool, H, V> Manager<'id, Slices<U>, Versioned<REUSE, H, V>>
@@ -308,7 +564,7 @@ where
             Slices::<U>::delete_slice::<
                 T,
                 <GlobalConfig<Slices<U>, Versioned<true, H, V>> as Config>::Store,
-            >(&mut self.0.store, index, len)?
+            >(&mut self.0.store, handle.index, index, len)?
         };
         self.0.dirty = true;
         Ok(lock)
@@ -343,7 +599,7 @@ where
         let size = Mixed::<U>::size_of::<(Version, T)>();
         match self.0.store.insert_indirect_within_capacity(size) {
             Some((index, mut lock)) => {
-                unsafe { Mixed::<U>::write_instance(data, lock.as_mut()) };
+                unsafe { Mixed::<U>::write_instance((self.0.version, data), lock.as_mut()) };
                 Ok(VHandle {
                     index:   index.start,
                     version: self.0.version,
@@ -417,3 +673,52 @@ where
     }
 }
 // TODO: testing
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+
+    #[test]
+    fn rollback_invalidates_newer_handles() {
+        make_guard!(guard);
+        let mut manager = Manager::<Typed<u32>, Versioned>::new(guard);
+        assert_eq!(Ok(()), manager.reserve(2));
+        let old = manager.insert_within_capacity(1).unwrap();
+        let checkpoint = manager.checkpoint();
+        let new = manager.insert_within_capacity(2).unwrap();
+        assert_eq!(Ok(()), manager.rollback_to(checkpoint));
+        assert_eq!(Ok(&1), manager.get(old), "older items should be kept");
+        let newer = manager.insert_within_capacity(3).unwrap();
+        assert!(manager.get(new).is_err(), "rolled back handles should be invalid");
+        assert_eq!(Ok(&3), manager.get(newer));
+        assert_eq!(Ok(()), manager.rollback_to(checkpoint), "checkpoint can be reused");
+        assert!(manager.rollback_to(Checkpoint { len: 2, ..checkpoint }).is_err());
+    }
+
+    #[test]
+    fn rejects_stale_checkpoints() {
+        fn insert(manager: &mut Manager<'_, Typed<u32>, Versioned>, count: u32) {
+            for value in 0..count {
+                manager.insert_within_capacity(value).unwrap();
+            }
+        }
+        make_guard!(guard);
+        let mut manager = Manager::<Typed<u32>, Versioned>::new(guard);
+        assert_eq!(Ok(()), manager.reserve(20));
+        insert(&mut manager, 5);
+        let first = manager.checkpoint();
+        insert(&mut manager, 3);
+        let second = manager.checkpoint();
+        assert_eq!(Ok(()), manager.rollback_to(first));
+        insert(&mut manager, 10);
+        assert_eq!(
+            Err(ManagerError::BadCheckpoint("checkpoint was already rolled back")),
+            manager.rollback_to(second)
+        );
+        assert_eq!(15, manager.checkpoint().len, "stale checkpoint should not truncate");
+        assert_eq!(Ok(()), manager.rollback_to(first));
+        assert_eq!(5, manager.checkpoint().len);
+    }
+}
@@ -6,6 +6,7 @@ pub mod store;
 
 pub type Index = nonmax::NonMaxU32;
 pub type Length = u32;
+pub type Tick = u64;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(transparent)]
@@ -31,7 +32,10 @@ impl Version {
 #[allow(type_alias_bounds)]
 pub mod prelude {
     pub use super::{
-        arena::{Arena, Guarded, Header, Headless, header},
-        manager::{Exclusive, Mixed, Slices, SoA, Typed, VHandle, Versioned, XHandle},
+        arena::{AllocAll, Arena, Guarded, Header, Headless, Transaction, header},
+        manager::{
+            Chunked, Columnar, Exclusive, History, Mixed, Slices, SoA, Trace, Tracer, Tracked,
+            Typed, VHandle, Versioned, XHandle,
+        },
     };
 }
@@ -0,0 +1,339 @@
+use std::alloc::{Layout, alloc_zeroed, dealloc, handle_alloc_error};
+
+use super::*;
+
+/// Store that keeps rows in chunks of `ROWS` rows, where each chunk holds all columns of its rows
+/// and every column starts at an address aligned to at least `ALIGN` bytes.
+///
+/// Chunks are never moved, so elements keep their address when the store grows.
+#[derive(Debug)]
+pub struct AoSoAStore<C: Columns, const ROWS: usize = 64, const ALIGN: usize = 64> {
+    /// Column pointers of every chunk, chunk by chunk.
+    columns:    Vec<NonNull<u8>>,
+    chunks:     Vec<NonNull<u8>>,
+    /// Layout of a single chunk.
+    layout:     Layout,
+    /// Offset of each column within a chunk.
+    offsets:    Box<[usize]>,
+    occupation: Vec<u8>,
+    next_free:  Index,
+    head:       Option<Index>,
+    schema:     C::Schema,
+    _marker:    PhantomData<C>,
+}
+impl<C, const ROWS: usize, const ALIGN: usize> Default for AoSoAStore<C, ROWS, ALIGN>
+where
+    C: Columns<Schema: Default>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<C, const ROWS: usize, const ALIGN: usize> AoSoAStore<C, ROWS, ALIGN>
+where
+    C: Columns,
+{
+    const DEFAULT_CAPACITY: Length = ROWS as Length;
+
+    pub fn new() -> Self
+    where
+        C::Schema: Default,
+    {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+    pub fn with_capacity(capacity: Length) -> Self
+    where
+        C::Schema: Default,
+    {
+        Self::with_schema(C::Schema::default(), capacity)
+    }
+    /// Creates a store for columns only described at runtime.
+    pub fn with_schema(schema: C::Schema, capacity: Length) -> Self {
+        const {
+            assert!(ROWS > 0 && ROWS <= Length::MAX as usize, "invalid chunk size");
+            assert!(ALIGN.is_power_of_two(), "alignment has to be a power of two");
+        };
+        let mut offsets = Vec::with_capacity(C::count(&schema));
+        let (mut size, mut align) = (0usize, ALIGN);
+        C::register_layout(&schema, ROWS as Length, &mut |layout: Layout| {
+            let column_align = layout.align().max(ALIGN);
+            size = size.next_multiple_of(column_align);
+            offsets.push(size);
+            size += layout.size();
+            align = align.max(column_align);
+        })
+        .expect("invalid column layout");
+        let layout = Layout::from_size_align(size.max(1), align).expect("invalid chunk layout");
+        let mut store = Self {
+            columns: Vec::new(),
+            chunks: Vec::new(),
+            layout,
+            offsets: offsets.into(),
+            occupation: Vec::new(),
+            next_free: Index::ZERO,
+            head: None,
+            schema,
+            _marker: PhantomData,
+        };
+        if capacity > 0 {
+            store.widen(capacity).expect("failed to allocate memory");
+        }
+        store
+    }
+    pub fn schema(&self) -> &C::Schema {
+        &self.schema
+    }
+    /// Column pointers of the chunk holding `index`, together with the index within the chunk.
+    fn chunk(&self, index: Index) -> (&[NonNull<u8>], Index) {
+        let count = self.offsets.len();
+        let chunk = index.get() as usize / ROWS;
+        // SAFETY: remainder is smaller than the index
+        let local = unsafe { Index::new_unchecked((index.get() as usize % ROWS) as u32) };
+        (&self.columns[chunk * count..(chunk + 1) * count], local)
+    }
+    fn is_occupied(&self, index: Index) -> bool {
+        self.occupation[index.get() as usize / 8] >> (index.get() % 8) & 1 == 1
+    }
+    fn set_occupied(&mut self, index: Index) {
+        self.occupation[index.get() as usize / 8] |= 1 << (index.get() % 8);
+    }
+    fn clear_occupied(&mut self, index: Index) {
+        self.occupation[index.get() as usize / 8] &= !(1 << (index.get() % 8));
+    }
+    fn rows(&self) -> RawRows<'_> {
+        // SAFETY: every chunk holds `ROWS` rows and the occupation table has a bit for each row
+        unsafe {
+            RawRows::chunked(
+                &self.columns,
+                self.offsets.len(),
+                ROWS,
+                NonNull::from(self.occupation.as_slice()).cast(),
+                self.capacity(),
+            )
+        }
+    }
+}
+impl<C, const ROWS: usize, const ALIGN: usize> Drop for AoSoAStore<C, ROWS, ALIGN>
+where
+    C: Columns,
+{
+    fn drop(&mut self) {
+        for chunk in &self.chunks {
+            // SAFETY: every chunk was allocated with this layout
+            unsafe { dealloc(chunk.as_ptr(), self.layout) };
+        }
+    }
+}
+
+impl<C, const ROWS: usize, const ALIGN: usize> Resizable for AoSoAStore<C, ROWS, ALIGN>
+where
+    C: Columns,
+{
+    fn capacity(&self) -> Length {
+        (self.chunks.len() * ROWS) as Length
+    }
+
+    fn widen(&mut self, new_capacity: Length) -> SResult<()> {
+        let capacity = self.capacity();
+        if new_capacity <= capacity {
+            return Err(StoreError::Narrow(new_capacity, capacity));
+        }
+        let chunks = (new_capacity as usize).div_ceil(ROWS);
+        if chunks * ROWS > Length::MAX as usize {
+            return Err(StoreError::OutofMemory(capacity, new_capacity));
+        }
+        self.columns.reserve((chunks - self.chunks.len()) * self.offsets.len());
+        while self.chunks.len() < chunks {
+            // SAFETY: layout has a non-zero size
+            let Some(chunk) = NonNull::new(unsafe { alloc_zeroed(self.layout) }) else {
+                handle_alloc_error(self.layout)
+            };
+            self.chunks.push(chunk);
+            // SAFETY: offsets are within the chunk
+            self.columns.extend(self.offsets.iter().map(|offset| unsafe { chunk.add(*offset) }));
+        }
+        self.occupation.resize(self.capacity().div_ceil(8) as usize, 0);
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        self.occupation.fill(0);
+        self.next_free = Index::ZERO;
+        self.head = None;
+    }
+}
+impl<C, const ROWS: usize, const ALIGN: usize> Insert<Single<C>> for AoSoAStore<C, ROWS, ALIGN>
+where
+    C: Columns,
+{
+    fn insert_within_capacity(&mut self, element: C) -> Result<Index, C> {
+        let index = if let Some(head) = self.head {
+            let (columns, local) = self.chunk(head);
+            self.head = *C::as_freelist_entry(&self.schema, local, columns);
+            head
+        } else if self.next_free.get() < self.capacity() {
+            let next_free = self.next_free;
+            // SAFETY: all indices within capacity are valid
+            self.next_free = unsafe { Index::new_unchecked(next_free.get() + 1) };
+            next_free
+        } else {
+            return Err(element);
+        };
+        debug_assert!(!self.is_occupied(index));
+        let (columns, local) = self.chunk(index);
+        element.move_into(&self.schema, local, columns);
+        self.set_occupied(index);
+        Ok(index)
+    }
+}
+impl<C, I, const ROWS: usize, const ALIGN: usize> View<Rows<C, I>> for AoSoAStore<C, ROWS, ALIGN>
+where
+    I: IntoIndex,
+    C: Columns,
+{
+    fn view(&self) -> <Rows<C, I> as Element>::Ref<'_> {
+        // SAFETY: the rows were registered by the schema and are only read through &self
+        unsafe { C::make_ref(&self.schema, self.rows()) }
+    }
+
+    fn view_mut(&mut self) -> <Rows<C, I> as Element>::Mut<'_> {
+        // SAFETY: the rows were registered by the schema and self is borrowed mutably
+        unsafe { C::make_mut(&self.schema, self.rows()) }
+    }
+}
+impl<C, const ROWS: usize, const ALIGN: usize> RawView<C> for AoSoAStore<C, ROWS, ALIGN>
+where
+    C: Columns,
+{
+    fn schema(&self) -> &C::Schema {
+        &self.schema
+    }
+
+    unsafe fn raw_rows(&self) -> RawRows<'_> {
+        self.rows()
+    }
+
+    fn raw_rows_mut(&mut self) -> (&C::Schema, RawRows<'_>) {
+        (&self.schema, self.rows())
+    }
+}
+impl<C, I, const ROWS: usize, const ALIGN: usize> SoAStore<C, I> for AoSoAStore<C, ROWS, ALIGN>
+where
+    C: Columns,
+    I: IntoIndex,
+{
+}
+
+impl<C, const ROWS: usize, const ALIGN: usize> Remove<Single<C>> for AoSoAStore<C, ROWS, ALIGN>
+where
+    C: Columns,
+{
+    fn remove(&mut self, index: Index) -> SResult<C> {
+        if index >= self.next_free {
+            return Err(StoreError::OutOfBounds(index, self.next_free.get()));
+        }
+        if !self.is_occupied(index) {
+            return Err(StoreError::DoubleFree(index));
+        }
+        let (columns, local) = self.chunk(index);
+        let element = C::take(&self.schema, local, columns);
+        *C::as_freelist_entry(&self.schema, local, columns) = self.head;
+        self.head = Some(index);
+        self.clear_occupied(index);
+        Ok(element)
+    }
+}
+impl<C, const ROWS: usize, const ALIGN: usize> Restore<C> for AoSoAStore<C, ROWS, ALIGN>
+where
+    C: Columns,
+{
+    fn restore(&mut self, index: Index, element: C) -> Result<(), (C, StoreError)> {
+        if index >= self.next_free {
+            return Err((element, StoreError::OutOfBounds(index, self.next_free.get())));
+        }
+        if self.is_occupied(index) {
+            return Err((element, StoreError::Occupied(index)));
+        }
+        let next = {
+            let (columns, local) = self.chunk(index);
+            *C::as_freelist_entry(&self.schema, local, columns)
+        };
+        if self.head == Some(index) {
+            self.head = next;
+        } else {
+            let mut current = self.head;
+            while let Some(i) = current {
+                let (columns, local) = self.chunk(i);
+                let link = C::as_freelist_entry(&self.schema, local, columns);
+                if *link == Some(index) {
+                    *link = next;
+                    break;
+                }
+                current = *link;
+            }
+        }
+        let (columns, local) = self.chunk(index);
+        element.move_into(&self.schema, local, columns);
+        self.set_occupied(index);
+        Ok(())
+    }
+}
+impl<C, I, const ROWS: usize, const ALIGN: usize> ReusableSoAStore<C, I>
+    for AoSoAStore<C, ROWS, ALIGN>
+where
+    C: Columns,
+    I: IntoIndex,
+{
+}
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+    use crate::alloc::manager::{Chunked, Manager, SoA, Versioned};
+
+    #[test]
+    fn keeps_addresses_across_growth() {
+        let mut store = AoSoAStore::<(u8, u64), 4, 32>::with_capacity(3);
+        assert_eq!(4, store.capacity());
+        let indices: Vec<_> =
+            (0..4).map(|i| store.insert_within_capacity((i, i as u64 * 10)).unwrap()).collect();
+        let view: TupleRef<'_, (u8, u64), Index> = store.view();
+        let first = view.col1(indices[1]).unwrap() as *const u64;
+        assert_eq!(0, view.col0(indices[0]).unwrap() as *const u8 as usize % 32);
+        assert_eq!(0, view.col1(indices[0]).unwrap() as *const u64 as usize % 32);
+        store.widen(9).unwrap();
+        assert_eq!(12, store.capacity());
+        let more: Vec<_> =
+            (4..10).map(|i| store.insert_within_capacity((i, i as u64 * 10)).unwrap()).collect();
+        let mut view: TupleMut<'_, (u8, u64), Index> = store.view_mut();
+        assert_eq!(first, view.col1(indices[1]).unwrap() as *const u64);
+        *view.col1_mut(more[5]).unwrap() += 1;
+        assert_eq!(Ok(&91), view.col1(more[5]));
+        assert_eq!(Ok((5, 50)), store.remove(more[1]));
+        assert_eq!(Ok((2, 20)), store.remove(indices[2]));
+        store.restore(more[1], (5, 51)).unwrap();
+        assert_eq!(Ok(indices[2]), store.insert_within_capacity((2, 21)));
+
+        make_guard!(guard);
+        let mut manager = Manager::<SoA<(u32, f32), Chunked<8>>, Versioned<true>>::new(guard);
+        manager.reserve(10).unwrap();
+        let handles: Vec<_> =
+            (0..20).map(|i| manager.insert_within_capacity((i, i as f32)).unwrap()).collect();
+        assert_eq!(Ok(&19), manager.view().col0(handles[19]));
+        assert_eq!((19, 19.0), manager.remove(handles[19]).unwrap());
+    }
+
+    #[test]
+    fn rejects_column_slices() {
+        let mut store = AoSoAStore::<(u8, u64), 4, 32>::with_capacity(8);
+        let index = store.insert_within_capacity((1, 10)).unwrap();
+        let view: TupleRef<'_, (u8, u64), Index> = store.view();
+        assert!(matches!(view.column_slice::<1>(), Err(StoreError::InvalidLayout(_))));
+        let mut view: TupleMut<'_, (u8, u64), Index> = store.view_mut();
+        assert!(matches!(view.column_slice_mut::<0>(), Err(StoreError::InvalidLayout(_))));
+        assert!(view.columns_mut().is_err());
+        assert_eq!(Ok(&10), view.col1(index));
+    }
+}
@@ -0,0 +1,280 @@
+use std::{
+    ffi::{CStr, CString, c_char, c_void},
+    ptr::{null, null_mut},
+};
+
+use super::*;
+
+/// Schema of the Arrow C data interface.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ArrowSchema {
+    pub format:       *const c_char,
+    pub name:         *const c_char,
+    pub metadata:     *const c_char,
+    pub flags:        i64,
+    pub n_children:   i64,
+    pub children:     *mut *mut ArrowSchema,
+    pub dictionary:   *mut ArrowSchema,
+    pub release:      Option<unsafe extern "C" fn(*mut ArrowSchema)>,
+    pub private_data: *mut c_void,
+}
+/// Array of the Arrow C data interface.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ArrowArray {
+    pub length:       i64,
+    pub null_count:   i64,
+    pub offset:       i64,
+    pub n_buffers:    i64,
+    pub n_children:   i64,
+    pub buffers:      *mut *const c_void,
+    pub children:     *mut *mut ArrowArray,
+    pub dictionary:   *mut ArrowArray,
+    pub release:      Option<unsafe extern "C" fn(*mut ArrowArray)>,
+    pub private_data: *mut c_void,
+}
+
+const ARROW_FLAG_NULLABLE: i64 = 2;
+
+/// # Safety
+/// `FORMAT` has to describe the memory layout of `Self` as an Arrow primitive type.
+pub unsafe trait ArrowPrimitive: Copy {
+    const FORMAT: &'static CStr;
+}
+macro_rules! impl_arrow_primitive {
+    ($($T:ty => $format:literal),*) => {
+        $(
+            // SAFETY: the format matches the layout of the type
+            unsafe impl ArrowPrimitive for $T {
+                const FORMAT: &'static CStr = $format;
+            }
+        )*
+    };
+}
+impl_arrow_primitive!(
+    i8 => c"c", u8 => c"C", i16 => c"s", u16 => c"S", i32 => c"i", u32 => c"I",
+    i64 => c"l", u64 => c"L", f32 => c"f", f64 => c"g"
+);
+/// # Safety
+/// `FORMATS` has to describe the memory layout of every column,
+/// where `None` marks columns that are not densely packed.
+pub unsafe trait ArrowColumns: Columns {
+    const FORMATS: &'static [Option<&'static CStr>];
+}
+macro_rules! impl_arrow_columns {
+    ((0, $T0:ident) $(,($i:tt, $T:ident))*) => {
+        // SAFETY: column 0 is only packed when the freelist entry fits into the value
+        unsafe impl<$T0: ArrowPrimitive, $($T: ArrowPrimitive),*> ArrowColumns for ($T0, $($T,)*) {
+            const FORMATS: &'static [Option<&'static CStr>] = &[
+                if size_of::<FreelistEntry<$T0>>() == size_of::<$T0>() {
+                    Some($T0::FORMAT)
+                } else {
+                    None
+                },
+                $(Some($T::FORMAT)),*
+            ];
+        }
+    };
+}
+all_tuples_enumerated!(impl_arrow_columns, 1, 16, T);
+
+struct SchemaData {
+    name:     CString,
+    children: Vec<*mut ArrowSchema>,
+}
+unsafe extern "C" fn release_schema(schema: *mut ArrowSchema) {
+    // SAFETY: only called on schemas created by `new_schema`
+    let schema = unsafe { &mut *schema };
+    // SAFETY: private data was created by `new_schema`
+    let data = unsafe { Box::from_raw(schema.private_data.cast::<SchemaData>()) };
+    for child in data.children {
+        // SAFETY: children were created by `new_schema`
+        let mut child = unsafe { Box::from_raw(child) };
+        if let Some(release) = child.release {
+            // SAFETY: child was not released yet
+            unsafe { release(&mut *child) };
+        }
+    }
+    schema.release = None;
+}
+fn new_schema(format: &'static CStr, name: CString, children: Vec<ArrowSchema>) -> ArrowSchema {
+    let mut data = Box::new(SchemaData {
+        name,
+        children: children.into_iter().map(|child| Box::into_raw(Box::new(child))).collect(),
+    });
+    ArrowSchema {
+        format:       format.as_ptr(),
+        name:         data.name.as_ptr(),
+        metadata:     null(),
+        flags:        ARROW_FLAG_NULLABLE,
+        n_children:   data.children.len() as i64,
+        children:     data.children.as_mut_ptr(),
+        dictionary:   null_mut(),
+        release:      Some(release_schema),
+        private_data: Box::into_raw(data).cast(),
+    }
+}
+struct ArrayData {
+    buffers:  Vec<*const c_void>,
+    children: Vec<*mut ArrowArray>,
+}
+unsafe extern "C" fn release_array(array: *mut ArrowArray) {
+    // SAFETY: only called on arrays created by `new_array`
+    let array = unsafe { &mut *array };
+    // SAFETY: private data was created by `new_array`
+    let data = unsafe { Box::from_raw(array.private_data.cast::<ArrayData>()) };
+    for child in data.children {
+        // SAFETY: children were created by `new_array`
+        let mut child = unsafe { Box::from_raw(child) };
+        if let Some(release) = child.release {
+            // SAFETY: child was not released yet
+            unsafe { release(&mut *child) };
+        }
+    }
+    array.release = None;
+}
+fn new_array(
+    length: Length,
+    null_count: Length,
+    buffers: Vec<*const c_void>,
+    children: Vec<ArrowArray>,
+) -> ArrowArray {
+    let mut data = Box::new(ArrayData {
+        buffers,
+        children: children.into_iter().map(|child| Box::into_raw(Box::new(child))).collect(),
+    });
+    ArrowArray {
+        length:       length as i64,
+        null_count:   null_count as i64,
+        offset:       0,
+        n_buffers:    data.buffers.len() as i64,
+        n_children:   data.children.len() as i64,
+        buffers:      data.buffers.as_mut_ptr(),
+        children:     data.children.as_mut_ptr(),
+        dictionary:   null_mut(),
+        release:      Some(release_array),
+        private_data: Box::into_raw(data).cast(),
+    }
+}
+
+/// Columns exported through the Arrow C data interface without copying.
+///
+/// The exported buffers point into the store, so arrays and schemas moved out of the export
+/// have to be released before `'a` ends. Everything that was not moved is released on drop.
+#[derive(Debug)]
+pub struct ArrowExport<'a> {
+    array:   ArrowArray,
+    schema:  ArrowSchema,
+    _marker: PhantomData<&'a ()>,
+}
+impl ArrowExport<'_> {
+    /// Struct array with one child per column, unoccupied rows are null.
+    pub fn array(&mut self) -> *mut ArrowArray {
+        &raw mut self.array
+    }
+    pub fn schema(&mut self) -> *mut ArrowSchema {
+        &raw mut self.schema
+    }
+}
+impl Drop for ArrowExport<'_> {
+    fn drop(&mut self) {
+        if let Some(release) = self.array.release {
+            // SAFETY: array was not released yet
+            unsafe { release(&mut self.array) };
+        }
+        if let Some(release) = self.schema.release {
+            // SAFETY: schema was not released yet
+            unsafe { release(&mut self.schema) };
+        }
+    }
+}
+/// Exports `rows` as a struct array with the fields `names`.
+/// # Errors
+/// Fails with [`StoreError::InvalidLayout`] if a column can't be exported,
+/// `formats` or `names` don't match the columns or a name contains a nul byte.
+/// # Safety
+/// `rows` has to contain densely packed columns of the types described by `formats`,
+/// which stay valid for `'a`.
+pub unsafe fn export_rows<'a>(
+    rows: RawRows<'a>,
+    formats: &[Option<&'static CStr>],
+    names: &[&str],
+) -> SResult<ArrowExport<'a>> {
+    let Some(columns) = rows.columns() else {
+        return Err(StoreError::InvalidLayout("columns are not single arrays"));
+    };
+    if formats.len() != columns.len() {
+        return Err(StoreError::InvalidLayout("formats have to match the columns"));
+    }
+    if names.len() != columns.len() {
+        return Err(StoreError::InvalidLayout("names have to match the columns"));
+    }
+    let Some(formats) = formats.iter().copied().collect::<Option<Vec<_>>>() else {
+        return Err(StoreError::InvalidLayout("column is not densely packed"));
+    };
+    let Ok(names) = names.iter().map(|&name| CString::new(name)).collect::<Result<Vec<_>, _>>()
+    else {
+        return Err(StoreError::InvalidLayout("column names can't contain nul bytes"));
+    };
+    let len = rows.len();
+    let null_count = len - rows.bitmap().count_ones();
+    let occupation = rows.occupation();
+    let validity = occupation.as_ptr().cast_const().cast::<c_void>();
+    let mut arrays = Vec::with_capacity(formats.len());
+    let mut schemas = Vec::with_capacity(formats.len());
+    for ((column, format), name) in columns.iter().zip(formats).zip(names) {
+        let values = column.as_ptr().cast_const().cast::<c_void>();
+        arrays.push(new_array(len, null_count, vec![validity, values], Vec::new()));
+        schemas.push(new_schema(format, name, Vec::new()));
+    }
+    Ok(ArrowExport {
+        array:   new_array(len, null_count, vec![validity], arrays),
+        schema:  new_schema(c"+s", CString::default(), schemas),
+        _marker: PhantomData,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::slice;
+
+    use generativity::make_guard;
+
+    use super::*;
+    use crate::alloc::manager::{Manager, SoA, Versioned};
+
+    #[test]
+    fn exports_columns_without_copy() {
+        make_guard!(guard);
+        let mut manager = Manager::<SoA<(u32, f64)>, Versioned<true>>::new(guard);
+        manager.reserve(3).unwrap();
+        let a = manager.insert_within_capacity((1, 1.0)).unwrap();
+        manager.insert_within_capacity((2, 2.0)).unwrap();
+        manager.remove(a).unwrap();
+        let mut export = manager.export_arrow(&["id", "value"]).unwrap();
+        // SAFETY: export is alive and was not moved
+        let (array, schema) = unsafe { (&*export.array(), &*export.schema()) };
+        assert_eq!(c"+s", unsafe { CStr::from_ptr(schema.format) });
+        assert_eq!(2, schema.n_children);
+        let field = unsafe { &**schema.children.add(1) };
+        assert_eq!(c"value", unsafe { CStr::from_ptr(field.name) });
+        assert_eq!(c"g", unsafe { CStr::from_ptr(field.format) });
+        assert_eq!(array.length - 1, array.null_count, "only one row should be valid");
+        let column = unsafe { &**array.children.add(1) };
+        let values =
+            unsafe { slice::from_raw_parts(column.buffers.add(1).read().cast::<f64>(), 2) };
+        let validity = unsafe { column.buffers.read().cast::<u8>().read() };
+        assert_eq!(0b10, validity & 0b11);
+        assert_eq!(2.0, values[1]);
+
+        make_guard!(guard);
+        let manager = Manager::<SoA<(u8, u32)>, Versioned<true>>::new(guard);
+        assert!(manager.export_arrow(&["a", "b"]).is_err(), "padded column can't be exported");
+
+        make_guard!(guard);
+        let manager = Manager::<SoA<(u32, f64)>, Versioned<true>>::new(guard);
+        assert!(manager.export_arrow(&["id"]).is_err(), "every column needs a name");
+        assert!(manager.export_arrow(&["id", "va\0lue"]).is_err(), "names are C strings");
+    }
+}
@@ -0,0 +1,539 @@
+use std::{iter, slice};
+
+use super::*;
+
+/// Occupation table of a store, with one bit for every row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bitmap<'a> {
+    bits:    NonNull<u8>,
+    len:     Length,
+    _marker: PhantomData<&'a [u8]>,
+}
+impl<'a> Bitmap<'a> {
+    const WORD_BITS: Length = u64::BITS;
+
+    /// # Safety
+    /// `bits` has to point to `len.div_ceil(8)` bytes that are valid for `'a`.
+    pub(super) const unsafe fn new(bits: NonNull<u8>, len: Length) -> Self {
+        Self { bits, len, _marker: PhantomData }
+    }
+    pub fn len(&self) -> Length {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn get(&self, index: Index) -> bool {
+        index.get() < self.len && {
+            // SAFETY: index is within bounds
+            let chunk = unsafe { self.bits.add(index.get() as usize / 8).read() };
+            chunk >> (index.get() % 8) & 1 == 1
+        }
+    }
+    /// Bits of the rows `64 * index..64 * (index + 1)`, rows out of bounds are not set.
+    pub fn word(&self, index: usize) -> u64 {
+        let start = index * 8;
+        let end = (self.len.div_ceil(8) as usize).min(start + 8);
+        let mut word = 0;
+        for (shift, byte) in (start..end).enumerate() {
+            // SAFETY: byte is within bounds
+            word |= (unsafe { self.bits.add(byte).read() } as u64) << (shift * 8);
+        }
+        let rest = self.len as usize - (index * Self::WORD_BITS as usize).min(self.len as usize);
+        if rest < Self::WORD_BITS as usize { word & ((1 << rest) - 1) } else { word }
+    }
+    pub fn words(self) -> impl Iterator<Item = u64> + 'a {
+        (0..self.len.div_ceil(Self::WORD_BITS) as usize).map(move |index| self.word(index))
+    }
+    pub fn count_ones(&self) -> Length {
+        self.words().map(u64::count_ones).sum()
+    }
+    /// Indices of all set bits, in ascending order.
+    pub fn ones(&self) -> Ones<'a> {
+        Ones { bitmap: *self, index: 0, word: self.word(0) }
+    }
+    /// Indices of the set bits in the word at `index`, in ascending order.
+    pub fn word_ones(&self, index: usize) -> impl Iterator<Item = Index> + use<> {
+        let mut word = self.word(index);
+        iter::from_fn(move || {
+            (word != 0).then(|| {
+                let bit = word.trailing_zeros();
+                word &= word - 1;
+                // SAFETY: set bits are always within bounds
+                unsafe { Index::new_unchecked(index as u32 * Self::WORD_BITS + bit) }
+            })
+        })
+    }
+}
+// SAFETY: the bitmap is only read
+unsafe impl Send for Bitmap<'_> {}
+// SAFETY: the bitmap is only read
+unsafe impl Sync for Bitmap<'_> {}
+/// Iterator over the set bits of a [`Bitmap`], one word at a time.
+#[derive(Debug, Clone)]
+pub struct Ones<'a> {
+    bitmap: Bitmap<'a>,
+    index:  usize,
+    word:   u64,
+}
+impl Iterator for Ones<'_> {
+    type Item = Index;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.word == 0 {
+            self.index += 1;
+            if self.index * Bitmap::WORD_BITS as usize >= self.bitmap.len as usize {
+                return None;
+            }
+            self.word = self.bitmap.word(self.index);
+        }
+        let bit = self.word.trailing_zeros();
+        self.word &= self.word - 1;
+        // SAFETY: set bits are always within bounds
+        Some(unsafe { Index::new_unchecked(self.index as u32 * Bitmap::WORD_BITS + bit) })
+    }
+}
+
+/// Type of the `N`-th column.
+pub trait Column<const N: usize>: Columns {
+    type Item;
+    /// Type that is stored for every row, which holds an `Item` at offset 0 when occupied.
+    type Slot;
+}
+/// Rows of a single column, together with the occupation table.
+#[derive(Debug)]
+pub struct ColumnSlice<'a, T, S = T> {
+    slots:      NonNull<S>,
+    occupation: Bitmap<'a>,
+    _marker:    PhantomData<&'a T>,
+}
+impl<T, S> Clone for ColumnSlice<'_, T, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T, S> Copy for ColumnSlice<'_, T, S> {}
+// SAFETY: the column behaves like `&[T]`
+unsafe impl<T: Sync, S> Send for ColumnSlice<'_, T, S> {}
+// SAFETY: the column behaves like `&[T]`
+unsafe impl<T: Sync, S> Sync for ColumnSlice<'_, T, S> {}
+impl<'a, T, S> ColumnSlice<'a, T, S> {
+    /// # Safety
+    /// `slots` has to point to a slot for every row of `occupation`,
+    /// where each occupied slot holds a `T` at offset 0.
+    pub(super) unsafe fn new(slots: NonNull<S>, occupation: Bitmap<'a>) -> Self {
+        Self { slots, occupation, _marker: PhantomData }
+    }
+    pub fn len(&self) -> Length {
+        self.occupation.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.occupation.is_empty()
+    }
+    pub fn occupation(&self) -> Bitmap<'a> {
+        self.occupation
+    }
+    /// Raw storage of every row, only slots of occupied rows are initialized.
+    pub fn slots(&self) -> &'a [MaybeUninit<S>] {
+        // SAFETY: there is a slot for every row
+        unsafe { slice::from_raw_parts(self.slots.cast().as_ptr(), self.len() as usize) }
+    }
+    pub fn get(&self, index: Index) -> Option<&'a T> {
+        self.occupation.get(index).then(|| {
+            // SAFETY: slot is occupied
+            unsafe { self.slots.add(index.get() as usize).cast::<T>().as_ref() }
+        })
+    }
+    pub fn iter(self) -> impl Iterator<Item = (Index, &'a T)> {
+        self.occupation.ones().map(move |index| {
+            // SAFETY: slot is occupied
+            (index, unsafe { self.slots.add(index.get() as usize).cast::<T>().as_ref() })
+        })
+    }
+}
+/// Mutable rows of a single column, together with the occupation table.
+///
+/// Writes are recorded for every row that is accessed mutably.
+#[derive(Debug)]
+pub struct ColumnSliceMut<'a, T, S = T> {
+    slots:   NonNull<S>,
+    rows:    RawRows<'a>,
+    column:  usize,
+    _marker: PhantomData<&'a mut T>,
+}
+// SAFETY: the column behaves like `&mut [T]`, ticks of different rows don't overlap
+unsafe impl<T: Send, S> Send for ColumnSliceMut<'_, T, S> {}
+// SAFETY: shared access only gives out `&T`, except for `ColumnAccess::fetch`
+unsafe impl<T: Send + Sync, S> Sync for ColumnSliceMut<'_, T, S> {}
+impl<'a, T, S> ColumnSliceMut<'a, T, S> {
+    /// # Safety
+    /// `column` of `rows` has to hold a slot of type `S` for every row,
+    /// where each occupied slot holds a `T` at offset 0.
+    /// There can't be any other access to the column for `'a`.
+    pub(super) unsafe fn new(rows: RawRows<'a>, column: usize) -> SResult<Self> {
+        let slots = contiguous(&rows)?[column].cast();
+        Ok(Self { slots, rows, column, _marker: PhantomData })
+    }
+    pub fn len(&self) -> Length {
+        self.rows.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+    pub fn occupation(&self) -> Bitmap<'a> {
+        self.rows.bitmap()
+    }
+    pub fn as_slice(&self) -> ColumnSlice<'_, T, S> {
+        // SAFETY: guarantied by construction
+        unsafe { ColumnSlice::new(self.slots, self.rows.bitmap()) }
+    }
+    /// Raw storage of every row, only slots of occupied rows are initialized.
+    /// All occupied rows are recorded as written.
+    /// # Safety
+    /// Occupied slots have to hold a valid `T` and unoccupied slots can't be changed.
+    pub unsafe fn slots_mut(&mut self) -> &mut [MaybeUninit<S>] {
+        self.rows.mark_column(self.column);
+        // SAFETY: there is a slot for every row
+        unsafe { slice::from_raw_parts_mut(self.slots.cast().as_ptr(), self.len() as usize) }
+    }
+    pub fn get(&self, index: Index) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+    pub fn get_mut(&mut self, index: Index) -> Option<&mut T> {
+        // SAFETY: index is occupied and self is borrowed mutably
+        self.rows.bitmap().get(index).then(|| unsafe { self.fetch_unchecked(index) })
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (Index, &T)> {
+        self.as_slice().iter()
+    }
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Index, &mut T)> {
+        let this = &*self;
+        // SAFETY: every occupied index is visited once and self is borrowed mutably
+        self.rows.bitmap().ones().map(move |index| (index, unsafe { this.fetch_unchecked(index) }))
+    }
+    /// # Safety
+    /// `index` has to be occupied and there can't be another reference to its row.
+    unsafe fn fetch_unchecked<'b>(&self, index: Index) -> &'b mut T {
+        // SAFETY: guarantied by caller
+        unsafe {
+            self.rows.mark(self.column, index);
+            self.slots.add(index.get() as usize).cast::<T>().as_mut()
+        }
+    }
+}
+
+/// Columns that can be walked together by [`zip_rows`].
+pub trait ColumnAccess<'a> {
+    type Item;
+
+    fn occupation(&self) -> Bitmap<'a>;
+    /// # Safety
+    /// `index` has to be occupied and every index can only be fetched once.
+    unsafe fn fetch(&self, index: Index) -> Self::Item;
+}
+impl<'a, T, S> ColumnAccess<'a> for ColumnSlice<'a, T, S> {
+    type Item = &'a T;
+
+    fn occupation(&self) -> Bitmap<'a> {
+        self.occupation
+    }
+
+    unsafe fn fetch(&self, index: Index) -> &'a T {
+        // SAFETY: guarantied by caller
+        unsafe { self.slots.add(index.get() as usize).cast::<T>().as_ref() }
+    }
+}
+impl<'a, T, S> ColumnAccess<'a> for ColumnSliceMut<'a, T, S> {
+    type Item = &'a mut T;
+
+    fn occupation(&self) -> Bitmap<'a> {
+        self.rows.bitmap()
+    }
+
+    unsafe fn fetch(&self, index: Index) -> &'a mut T {
+        // SAFETY: guarantied by caller
+        unsafe { self.fetch_unchecked(index) }
+    }
+}
+impl<'b, T, S> ColumnAccess<'b> for &'b mut ColumnSliceMut<'_, T, S> {
+    type Item = &'b mut T;
+
+    fn occupation(&self) -> Bitmap<'b> {
+        self.rows.bitmap()
+    }
+
+    unsafe fn fetch(&self, index: Index) -> &'b mut T {
+        // SAFETY: guarantied by caller
+        unsafe { self.fetch_unchecked(index) }
+    }
+}
+macro_rules! impl_column_access {
+    ((0, $T0:ident) $(,($i:tt, $T:ident))*) => {
+        impl<'a, $T0: ColumnAccess<'a>, $($T: ColumnAccess<'a>),*> ColumnAccess<'a> for ($T0, $($T,)*) {
+            type Item = ($T0::Item, $($T::Item,)*);
+
+            fn occupation(&self) -> Bitmap<'a> {
+                let occupation = self.0.occupation();
+                $(assert!(occupation == self.$i.occupation(), "columns have to be from the same rows");)*
+                occupation
+            }
+
+            unsafe fn fetch(&self, index: Index) -> Self::Item {
+                // SAFETY: guarantied by caller
+                unsafe { (self.0.fetch(index), $(self.$i.fetch(index),)*) }
+            }
+        }
+    };
+}
+all_tuples_enumerated!(impl_column_access, 1, 16, T);
+/// Iterator over the occupied rows of multiple columns, see [`zip_rows`].
+#[derive(Debug)]
+pub struct ZipRows<'a, A> {
+    access: A,
+    rows:   Ones<'a>,
+}
+impl<'a, A> Iterator for ZipRows<'a, A>
+where
+    A: ColumnAccess<'a>,
+{
+    type Item = (Index, A::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.rows.next()?;
+        // SAFETY: every occupied index is visited once
+        Some((index, unsafe { self.access.fetch(index) }))
+    }
+}
+/// Walks the occupied rows of all columns in `access`, skipping 64 free rows at a time.
+/// # Panics
+/// All columns have to share the same occupation table.
+pub fn zip_rows<'a, A>(access: A) -> ZipRows<'a, A>
+where
+    A: ColumnAccess<'a>,
+{
+    ZipRows { rows: access.occupation().ones(), access }
+}
+
+/// Selects column `N` for gather and scatter, multiple columns are selected by tuples.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Col<const N: usize>;
+/// Columns of `C` that are copied between rows and dense buffers, one `Vec` per column.
+pub trait ColumnSet<C: Columns> {
+    type Buffers;
+
+    /// Whether every buffer holds exactly `len` values.
+    fn has_len(buffers: &Self::Buffers, len: usize) -> bool;
+    /// Replaces the content of `buffers` with the values at `indices`, in that order.
+    /// # Safety
+    /// Every index has to be occupied and `rows` have to hold the columns of `C`.
+    unsafe fn gather(
+        rows: &RawRows<'_>,
+        indices: impl Iterator<Item = Index> + Clone,
+        buffers: &mut Self::Buffers,
+    );
+    /// Moves the values out of `buffers` into the rows at `indices`, dropping the old values.
+    /// # Safety
+    /// Every index has to be occupied and not borrowed elsewhere,
+    /// `rows` have to hold the columns of `C`.
+    /// # Panics
+    /// Every buffer has to hold a value for each index.
+    unsafe fn scatter(
+        rows: &RawRows<'_>,
+        indices: impl Iterator<Item = Index> + Clone,
+        buffers: &mut Self::Buffers,
+    );
+}
+impl<C, const N: usize> ColumnSet<C> for Col<N>
+where
+    C: Column<N, Item: Clone>,
+{
+    type Buffers = Vec<C::Item>;
+
+    fn has_len(buffers: &Vec<C::Item>, len: usize) -> bool {
+        buffers.len() == len
+    }
+
+    unsafe fn gather(
+        rows: &RawRows<'_>,
+        indices: impl Iterator<Item = Index> + Clone,
+        buffers: &mut Vec<C::Item>,
+    ) {
+        buffers.clear();
+        buffers.extend(indices.map(|index| {
+            // SAFETY: guarantied by caller, the item is stored at offset 0 of its slot
+            unsafe { rows.element(N, index, size_of::<C::Slot>()).cast::<C::Item>().as_ref() }
+                .clone()
+        }));
+    }
+
+    unsafe fn scatter(
+        rows: &RawRows<'_>,
+        indices: impl Iterator<Item = Index> + Clone,
+        buffers: &mut Vec<C::Item>,
+    ) {
+        let mut values = buffers.drain(..);
+        for index in indices {
+            let value = values.next().expect("buffer is shorter than the list of rows");
+            // SAFETY: guarantied by caller, the item is stored at offset 0 of its slot
+            unsafe {
+                rows.mark(N, index);
+                *rows.element(N, index, size_of::<C::Slot>()).cast::<C::Item>().as_mut() = value;
+            }
+        }
+        assert!(values.next().is_none(), "buffer is longer than the list of rows");
+    }
+}
+macro_rules! impl_column_set {
+    ($(($i:tt, $T:ident)),*) => {
+        impl<C, $(const $T: usize),*> ColumnSet<C> for ($(Col<$T>,)*)
+        where
+            C: Columns $(+ Column<$T, Item: Clone>)*,
+        {
+            type Buffers = ($(Vec<<C as Column<$T>>::Item>,)*);
+
+            fn has_len(buffers: &Self::Buffers, len: usize) -> bool {
+                $(<Col<$T> as ColumnSet<C>>::has_len(&buffers.$i, len))&&*
+            }
+
+            unsafe fn gather(
+                rows: &RawRows<'_>,
+                indices: impl Iterator<Item = Index> + Clone,
+                buffers: &mut Self::Buffers,
+            ) {
+                // SAFETY: guarantied by caller
+                unsafe { $(<Col<$T> as ColumnSet<C>>::gather(rows, indices.clone(), &mut buffers.$i);)* }
+            }
+
+            unsafe fn scatter(
+                rows: &RawRows<'_>,
+                indices: impl Iterator<Item = Index> + Clone,
+                buffers: &mut Self::Buffers,
+            ) {
+                // SAFETY: guarantied by caller
+                unsafe { $(<Col<$T> as ColumnSet<C>>::scatter(rows, indices.clone(), &mut buffers.$i);)* }
+            }
+        }
+    };
+}
+all_tuples_enumerated!(impl_column_set, 1, 16, T);
+
+fn contiguous<'a>(rows: &RawRows<'a>) -> SResult<&'a [NonNull<u8>]> {
+    rows.columns()
+        .ok_or(StoreError::InvalidLayout("column slices need every column in a single array"))
+}
+impl<'a, C, I> TupleRef<'a, C, I> {
+    /// Fails for stores that split their columns into chunks.
+    pub fn column_slice<const N: usize>(&self) -> SResult<ColumnSlice<'a, C::Item, C::Slot>>
+    where
+        C: Column<N>,
+    {
+        let slots = contiguous(&self.0)?[N].cast();
+        // SAFETY: column N was registered to hold C::Slot
+        Ok(unsafe { ColumnSlice::new(slots, self.0.bitmap()) })
+    }
+}
+impl<'a, C, I> TupleMut<'a, C, I> {
+    /// Fails for stores that split their columns into chunks.
+    pub fn column_slice<const N: usize>(&self) -> SResult<ColumnSlice<'_, C::Item, C::Slot>>
+    where
+        C: Column<N>,
+    {
+        let slots = contiguous(&self.0)?[N].cast();
+        // SAFETY: column N was registered to hold C::Slot
+        Ok(unsafe { ColumnSlice::new(slots, self.0.bitmap()) })
+    }
+    /// Fails for stores that split their columns into chunks.
+    pub fn column_slice_mut<const N: usize>(
+        &mut self,
+    ) -> SResult<ColumnSliceMut<'_, C::Item, C::Slot>>
+    where
+        C: Column<N>,
+    {
+        // SAFETY: column N was registered to hold C::Slot and self is borrowed mutably
+        unsafe { ColumnSliceMut::new(self.0, N) }
+    }
+    pub fn into_column_slice_mut<const N: usize>(
+        self,
+    ) -> SResult<ColumnSliceMut<'a, C::Item, C::Slot>>
+    where
+        C: Column<N>,
+    {
+        // SAFETY: column N was registered to hold C::Slot and self is consumed
+        unsafe { ColumnSliceMut::new(self.0, N) }
+    }
+}
+macro_rules! impl_column_at {
+    ($generics:tt $tuple:ty;) => {};
+    ($generics:tt $tuple:ty; $column:tt $(, $rest:tt)*) => {
+        impl_column_at!(@impl $generics $tuple; $column);
+        impl_column_at!($generics $tuple; $($rest),*);
+    };
+    (@impl [$($G:ident),*] $tuple:ty; ($i:tt, $T:ident, $S:ty)) => {
+        impl<$($G),*> Column<$i> for $tuple {
+            type Item = $T;
+            type Slot = $S;
+        }
+    };
+}
+macro_rules! impl_column_slices {
+    ((0, $T0:ident) $(,($i:tt, $T:ident))*) => {
+        impl_column_at!(
+            [$T0 $(, $T)*] ($T0, $($T,)*);
+            (0, $T0, FreelistEntry<$T0>) $(, ($i, $T, $T))*
+        );
+        impl<'a, $T0, $($T,)* I> TupleRef<'a, ($T0, $($T,)*), I> {
+            /// Fails for stores that split their columns into chunks.
+            pub fn columns(&self) -> SResult<(ColumnSlice<'a, $T0, FreelistEntry<$T0>>, $(ColumnSlice<'a, $T>,)*)> {
+                Ok((self.column_slice::<0>()?, $(self.column_slice::<$i>()?,)*))
+            }
+        }
+        impl<'a, $T0, $($T,)* I> TupleMut<'a, ($T0, $($T,)*), I> {
+            /// Fails for stores that split their columns into chunks.
+            pub fn columns(&self) -> SResult<(ColumnSlice<'_, $T0, FreelistEntry<$T0>>, $(ColumnSlice<'_, $T>,)*)> {
+                Ok((self.column_slice::<0>()?, $(self.column_slice::<$i>()?,)*))
+            }
+            /// Borrows all columns mutably at once.
+            pub fn columns_mut(
+                &mut self,
+            ) -> SResult<(ColumnSliceMut<'_, $T0, FreelistEntry<$T0>>, $(ColumnSliceMut<'_, $T>,)*)> {
+                // SAFETY: every column is only borrowed once and self is borrowed mutably
+                unsafe { Ok((ColumnSliceMut::new(self.0, 0)?, $(ColumnSliceMut::new(self.0, $i)?,)*)) }
+            }
+            pub fn into_columns_mut(
+                self,
+            ) -> SResult<(ColumnSliceMut<'a, $T0, FreelistEntry<$T0>>, $(ColumnSliceMut<'a, $T>,)*)> {
+                // SAFETY: every column is only borrowed once and self is consumed
+                unsafe { Ok((ColumnSliceMut::new(self.0, 0)?, $(ColumnSliceMut::new(self.0, $i)?,)*)) }
+            }
+        }
+    };
+}
+all_tuples_enumerated!(impl_column_slices, 1, 16, T);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn zips_occupied_rows() {
+        let mut store = SoAFreelistStore::<(u8, f32)>::with_capacity(130);
+        let indices: Vec<_> =
+            (0..130).map(|i| store.insert_within_capacity((i as u8, i as f32)).unwrap()).collect();
+        for index in indices.iter().filter(|index| index.get() % 3 != 0) {
+            store.remove(*index).unwrap();
+        }
+        let mut view: TupleMut<'_, (u8, f32), Index> = store.view_mut();
+        assert_eq!(44, view.column_slice::<1>().unwrap().occupation().count_ones());
+        let (ids, mut values) = view.columns_mut().unwrap();
+        for (_, (id, value)) in zip_rows((ids, &mut values)) {
+            *value += *id as f32;
+        }
+        let values = view.column_slice::<1>().unwrap();
+        assert_eq!(Some(&258.0), values.get(indices[129]));
+        assert_eq!(None, values.get(indices[128]), "removed rows should be skipped");
+        assert_eq!(
+            (0..130).step_by(3).collect::<Vec<_>>(),
+            values.iter().map(|(index, _)| index.get()).collect::<Vec<_>>()
+        );
+    }
+}
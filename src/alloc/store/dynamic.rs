@@ -0,0 +1,538 @@
+use std::{
+    alloc::{alloc, dealloc, handle_alloc_error},
+    any::TypeId,
+    fmt::{self, Debug},
+    ptr::without_provenance_mut,
+    sync::Arc,
+};
+
+use super::*;
+
+/// Type erased description of a single column, see [`DynSchema`].
+#[derive(Debug, Clone, Copy)]
+pub struct DynColumn {
+    layout:  Layout,
+    type_id: TypeId,
+    drop:    unsafe fn(NonNull<u8>),
+    clone:   unsafe fn(NonNull<u8>, NonNull<u8>),
+}
+impl DynColumn {
+    pub fn of<T: Clone + 'static>() -> Self {
+        unsafe fn drop<T>(value: NonNull<u8>) {
+            // SAFETY: value holds a T
+            unsafe { value.cast::<T>().drop_in_place() };
+        }
+        unsafe fn clone<T: Clone>(src: NonNull<u8>, dst: NonNull<u8>) {
+            // SAFETY: src holds a T and dst has space for one
+            unsafe { dst.cast::<T>().write(src.cast::<T>().as_ref().clone()) };
+        }
+        Self {
+            layout:  Layout::new::<T>(),
+            type_id: TypeId::of::<T>(),
+            drop:    drop::<T>,
+            clone:   clone::<T>,
+        }
+    }
+    /// # Safety
+    /// Every value identified by `type_id` has to fit `layout`,
+    /// `drop` has to drop such a value in place
+    /// and `clone` has to write a copy of its first argument to its second.
+    pub const unsafe fn new(
+        layout: Layout,
+        type_id: TypeId,
+        drop: unsafe fn(NonNull<u8>),
+        clone: unsafe fn(NonNull<u8>, NonNull<u8>),
+    ) -> Self {
+        Self { layout, type_id, drop, clone }
+    }
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+}
+
+#[derive(Debug)]
+struct SchemaInner {
+    columns: Box<[DynColumn]>,
+    /// Size of one slot in each column of a store.
+    strides: Box<[usize]>,
+    /// Offset of each value in the buffer of a [`DynColumns`].
+    offsets: Box<[usize]>,
+    row:     Layout,
+}
+/// Columns only known at runtime, used as [`Columns::Schema`] of [`DynColumns`].
+///
+/// Schemas are compared by identity, rows can only be moved into stores built from a clone
+/// of the schema they were created with.
+#[derive(Debug, Clone)]
+pub struct DynSchema(Arc<SchemaInner>);
+impl DynSchema {
+    /// # Panics
+    /// A schema needs at least one column to hold the freelist.
+    pub fn new(columns: impl IntoIterator<Item = DynColumn>) -> Self {
+        let columns: Box<[DynColumn]> = columns.into_iter().collect();
+        assert!(!columns.is_empty(), "schema needs at least one column");
+        let freelist = Layout::new::<Option<Index>>();
+        let strides = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                if i == 0 {
+                    column
+                        .layout
+                        .size()
+                        .max(freelist.size())
+                        .next_multiple_of(Self::align(column, 0))
+                } else {
+                    column.layout.pad_to_align().size()
+                }
+            })
+            .collect();
+        let mut row = Layout::new::<()>();
+        let offsets = columns
+            .iter()
+            .map(|column| {
+                let (extended, offset) = row.extend(column.layout).expect("row is too large");
+                row = extended;
+                offset
+            })
+            .collect();
+        Self(Arc::new(SchemaInner { columns, strides, offsets, row: row.pad_to_align() }))
+    }
+    pub fn columns(&self) -> &[DynColumn] {
+        &self.0.columns
+    }
+    fn align(column: &DynColumn, i: usize) -> usize {
+        if i == 0 {
+            column.layout.align().max(align_of::<Option<Index>>())
+        } else {
+            column.layout.align()
+        }
+    }
+    fn check_index(&self, column: usize) -> SResult<&DynColumn> {
+        self.0.columns.get(column).ok_or(StoreError::ColumnIndex(column, self.0.columns.len()))
+    }
+    fn check<T: 'static>(&self, column: usize) -> SResult<()> {
+        if self.check_index(column)?.type_id == TypeId::of::<T>() {
+            Ok(())
+        } else {
+            Err(StoreError::ColumnType(column))
+        }
+    }
+    /// # Safety
+    /// `columns` have to be registered by this schema and `index` has to be within capacity.
+    unsafe fn slot(&self, columns: &[NonNull<u8>], column: usize, index: Index) -> NonNull<u8> {
+        // SAFETY: guarantied by caller
+        unsafe { columns[column].add(index.get() as usize * self.0.strides[column]) }
+    }
+    /// # Safety
+    /// `rows` have to be registered by this schema.
+    unsafe fn value(
+        &self,
+        rows: &RawRows<'_>,
+        column: usize,
+        index: Index,
+    ) -> SResult<NonNull<u8>> {
+        self.check_index(column)?;
+        // SAFETY: rows hold a valid pointer to an occupation table
+        unsafe { validate_row_index(rows.occupation(), index)? };
+        // SAFETY: index is occupied and therefore within capacity
+        Ok(unsafe { rows.element(column, index, self.0.strides[column]) })
+    }
+    fn allocate_row(&self) -> NonNull<u8> {
+        if self.0.row.size() == 0 {
+            // SAFETY: alignment is never zero
+            return unsafe { NonNull::new_unchecked(without_provenance_mut(self.0.row.align())) };
+        }
+        // SAFETY: size is not zero
+        let Some(data) = NonNull::new(unsafe { alloc(self.0.row) }) else {
+            handle_alloc_error(self.0.row)
+        };
+        data
+    }
+    /// # Safety
+    /// `data` has to be created by `allocate_row` of this schema.
+    unsafe fn deallocate_row(&self, data: NonNull<u8>) {
+        if self.0.row.size() != 0 {
+            // SAFETY: guarantied by caller
+            unsafe { dealloc(data.as_ptr(), self.0.row) };
+        }
+    }
+    /// # Safety
+    /// `data` has to be created by `allocate_row` of this schema.
+    unsafe fn field(&self, data: NonNull<u8>, column: usize) -> NonNull<u8> {
+        // SAFETY: offsets are within the row layout
+        unsafe { data.add(self.0.offsets[column]) }
+    }
+}
+impl PartialEq for DynSchema {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+impl Eq for DynSchema {}
+
+/// A row of columns described by a [`DynSchema`], stored in its own buffer until inserted.
+pub struct DynColumns {
+    schema: DynSchema,
+    data:   NonNull<u8>,
+}
+impl DynColumns {
+    pub fn builder(schema: &DynSchema) -> DynColumnsBuilder {
+        DynColumnsBuilder {
+            data:   schema.allocate_row(),
+            init:   vec![false; schema.columns().len()],
+            schema: schema.clone(),
+        }
+    }
+    pub fn schema(&self) -> &DynSchema {
+        &self.schema
+    }
+    pub fn get<T: 'static>(&self, column: usize) -> SResult<&T> {
+        self.schema.check::<T>(column)?;
+        // SAFETY: column holds a T
+        Ok(unsafe { self.schema.field(self.data, column).cast::<T>().as_ref() })
+    }
+    pub fn get_mut<T: 'static>(&mut self, column: usize) -> SResult<&mut T> {
+        self.schema.check::<T>(column)?;
+        // SAFETY: column holds a T
+        Ok(unsafe { self.schema.field(self.data, column).cast::<T>().as_mut() })
+    }
+}
+impl Clone for DynColumns {
+    fn clone(&self) -> Self {
+        let mut builder = Self::builder(&self.schema);
+        for (i, column) in self.schema.columns().iter().enumerate() {
+            // SAFETY: both rows use the same schema and the source is initialized
+            unsafe {
+                (column.clone)(self.schema.field(self.data, i), self.schema.field(builder.data, i))
+            };
+            builder.init[i] = true;
+        }
+        let Ok(clone) = builder.build() else { unreachable!("all columns are cloned") };
+        clone
+    }
+}
+impl Drop for DynColumns {
+    fn drop(&mut self) {
+        for (i, column) in self.schema.columns().iter().enumerate() {
+            // SAFETY: all columns are initialized
+            unsafe { (column.drop)(self.schema.field(self.data, i)) };
+        }
+        // SAFETY: data was allocated by this schema
+        unsafe { self.schema.deallocate_row(self.data) };
+    }
+}
+impl Debug for DynColumns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynColumns").field("schema", &self.schema).finish_non_exhaustive()
+    }
+}
+/// Initializes the columns of a [`DynColumns`] one by one.
+pub struct DynColumnsBuilder {
+    schema: DynSchema,
+    data:   NonNull<u8>,
+    init:   Vec<bool>,
+}
+impl DynColumnsBuilder {
+    pub fn set<T: 'static>(mut self, column: usize, value: T) -> SResult<Self> {
+        self.schema.check::<T>(column)?;
+        // SAFETY: column holds a T
+        unsafe { self.set_raw(column, NonNull::from(&value).cast()) };
+        std::mem::forget(value);
+        Ok(self)
+    }
+    /// Moves the value behind `value` into `column`, replacing the previous one.
+    /// # Safety
+    /// `column` has to exist and `value` has to point to a value of its type,
+    /// which must not be used afterwards.
+    pub unsafe fn set_raw(&mut self, column: usize, value: NonNull<u8>) {
+        let layout = self.schema.columns()[column].layout;
+        // SAFETY: data was allocated by this schema
+        let field = unsafe { self.schema.field(self.data, column) };
+        if self.init[column] {
+            // SAFETY: column was initialized before
+            unsafe { (self.schema.columns()[column].drop)(field) };
+        }
+        // SAFETY: guarantied by caller
+        unsafe { field.copy_from_nonoverlapping(value, layout.size()) };
+        self.init[column] = true;
+    }
+    /// # Errors
+    /// Fails with [`StoreError::MissingColumn`] if a column was not set.
+    pub fn build(self) -> SResult<DynColumns> {
+        if let Some(column) = self.init.iter().position(|init| !init) {
+            return Err(StoreError::MissingColumn(column));
+        }
+        let this = ManuallyDrop::new(self);
+        // SAFETY: this is not used after being read
+        let schema = unsafe { (&raw const this.schema).read() };
+        // SAFETY: this is not used after being read
+        drop(unsafe { (&raw const this.init).read() });
+        Ok(DynColumns { schema, data: this.data })
+    }
+}
+impl Drop for DynColumnsBuilder {
+    fn drop(&mut self) {
+        for (i, column) in self.schema.columns().iter().enumerate() {
+            if self.init[i] {
+                // SAFETY: column is initialized
+                unsafe { (column.drop)(self.schema.field(self.data, i)) };
+            }
+        }
+        // SAFETY: data was allocated by this schema
+        unsafe { self.schema.deallocate_row(self.data) };
+    }
+}
+impl Debug for DynColumnsBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynColumnsBuilder")
+            .field("schema", &self.schema)
+            .field("init", &self.init)
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct DynRef<'a, I> {
+    schema:  &'a DynSchema,
+    rows:    RawRows<'a>,
+    _marker: PhantomData<fn(I)>,
+}
+pub struct DynMut<'a, I> {
+    schema:  &'a DynSchema,
+    rows:    RawRows<'a>,
+    _marker: PhantomData<fn(I)>,
+}
+impl<'a, I: IntoIndex> DynRef<'a, I> {
+    pub fn schema(&self) -> &'a DynSchema {
+        self.schema
+    }
+    pub fn get<T: 'static>(&self, column: usize, index: I) -> SResult<&'a T> {
+        self.schema.check::<T>(column)?;
+        // SAFETY: rows were registered by the schema and the column holds a T
+        Ok(unsafe {
+            self.schema.value(&self.rows, column, index.into_index())?.cast::<T>().as_ref()
+        })
+    }
+    /// Pointer to the value of `column` at `index`, which may only be read.
+    pub fn get_raw(&self, column: usize, index: I) -> SResult<NonNull<u8>> {
+        // SAFETY: rows were registered by the schema
+        unsafe { self.schema.value(&self.rows, column, index.into_index()) }
+    }
+    pub fn clone_row(&self, index: I) -> SResult<DynColumns> {
+        // SAFETY: rows were registered by the schema
+        unsafe { clone_row(self.schema, &self.rows, index.into_index()) }
+    }
+}
+impl<'a, I: IntoIndex> DynMut<'a, I> {
+    pub fn schema(&self) -> &'a DynSchema {
+        self.schema
+    }
+    pub fn get<T: 'static>(&self, column: usize, index: I) -> SResult<&T> {
+        self.schema.check::<T>(column)?;
+        // SAFETY: rows were registered by the schema and the column holds a T
+        Ok(unsafe {
+            self.schema.value(&self.rows, column, index.into_index())?.cast::<T>().as_ref()
+        })
+    }
+    pub fn get_mut<T: 'static>(&mut self, column: usize, index: I) -> SResult<&mut T> {
+        self.schema.check::<T>(column)?;
+        // SAFETY: the column holds a T
+        Ok(unsafe { self.get_raw_mut(column, index)?.cast::<T>().as_mut() })
+    }
+    pub fn into_get_mut<T: 'static>(mut self, column: usize, index: I) -> SResult<&'a mut T> {
+        self.schema.check::<T>(column)?;
+        // SAFETY: the column holds a T
+        Ok(unsafe { self.get_raw_mut(column, index)?.cast::<T>().as_mut() })
+    }
+    /// Pointer to the value of `column` at `index`, which may only be read.
+    pub fn get_raw(&self, column: usize, index: I) -> SResult<NonNull<u8>> {
+        // SAFETY: rows were registered by the schema
+        unsafe { self.schema.value(&self.rows, column, index.into_index()) }
+    }
+    /// Pointer to the value of `column` at `index`, which may be written.
+    pub fn get_raw_mut(&mut self, column: usize, index: I) -> SResult<NonNull<u8>> {
+        let index = index.into_index();
+        // SAFETY: rows were registered by the schema
+        let value = unsafe { self.schema.value(&self.rows, column, index)? };
+        // SAFETY: index was validated above
+        unsafe { self.rows.mark(column, index) };
+        Ok(value)
+    }
+    pub fn clone_row(&self, index: I) -> SResult<DynColumns> {
+        // SAFETY: rows were registered by the schema
+        unsafe { clone_row(self.schema, &self.rows, index.into_index()) }
+    }
+}
+/// # Safety
+/// `rows` have to be registered by `schema`.
+unsafe fn clone_row(schema: &DynSchema, rows: &RawRows<'_>, index: Index) -> SResult<DynColumns> {
+    // SAFETY: rows hold a valid pointer to an occupation table
+    unsafe { validate_row_index(rows.occupation(), index)? };
+    let mut builder = DynColumns::builder(schema);
+    for (i, column) in schema.columns().iter().enumerate() {
+        // SAFETY: the row is occupied and the builder has space for every column
+        unsafe {
+            (column.clone)(
+                rows.element(i, index, schema.0.strides[i]),
+                schema.field(builder.data, i),
+            )
+        };
+        builder.init[i] = true;
+    }
+    builder.build()
+}
+
+// SAFETY: columns are moved bytewise with the layouts registered by the schema
+unsafe impl Columns for DynColumns {
+    type Schema = DynSchema;
+
+    type Ref<'a, I>
+        = DynRef<'a, I>
+    where
+        I: IntoIndex + 'a,
+        Self: 'a;
+    type Mut<'a, I>
+        = DynMut<'a, I>
+    where
+        I: IntoIndex + 'a,
+        Self: 'a;
+
+    fn count(schema: &DynSchema) -> usize {
+        schema.columns().len()
+    }
+    fn register_layout(
+        schema: &DynSchema,
+        rows: Length,
+        register: &mut impl FnMut(Layout),
+    ) -> Result<(), LayoutError> {
+        for (i, column) in schema.columns().iter().enumerate() {
+            register(Layout::from_size_align(
+                rows as usize * schema.0.strides[i],
+                DynSchema::align(column, i),
+            )?);
+        }
+        Ok(())
+    }
+    fn move_into(self, schema: &DynSchema, index: Index, columns: &[NonNull<u8>]) {
+        assert_eq!(self.schema, *schema, "row was built for a different schema");
+        let this = ManuallyDrop::new(self);
+        for (i, column) in schema.columns().iter().enumerate() {
+            // SAFETY: the slot was registered with space for the value, which is moved
+            unsafe {
+                schema
+                    .slot(columns, i, index)
+                    .copy_from_nonoverlapping(schema.field(this.data, i), column.layout.size())
+            };
+        }
+        // SAFETY: all values were moved out
+        unsafe { schema.deallocate_row(this.data) };
+        // SAFETY: this is not used after being read
+        drop(unsafe { (&raw const this.schema).read() });
+    }
+    fn take(schema: &DynSchema, index: Index, columns: &[NonNull<u8>]) -> Self {
+        let data = schema.allocate_row();
+        for (i, column) in schema.columns().iter().enumerate() {
+            // SAFETY: the slot holds a value, which is moved
+            unsafe {
+                schema
+                    .field(data, i)
+                    .copy_from_nonoverlapping(schema.slot(columns, i, index), column.layout.size())
+            };
+        }
+        Self { schema: schema.clone(), data }
+    }
+    fn as_freelist_entry<'a>(
+        schema: &DynSchema,
+        index: Index,
+        columns: &'a [NonNull<u8>],
+    ) -> &'a mut Option<Index> {
+        // SAFETY: slots of column 0 were registered with space for a freelist entry
+        unsafe { schema.slot(columns, 0, index).cast::<Option<Index>>().as_mut() }
+    }
+    unsafe fn make_ref<'a, I>(schema: &'a DynSchema, rows: RawRows<'a>) -> Self::Ref<'a, I>
+    where
+        I: IntoIndex + 'a,
+        Self: 'a,
+    {
+        DynRef { schema, rows, _marker: PhantomData }
+    }
+    unsafe fn make_mut<'a, I>(schema: &'a DynSchema, rows: RawRows<'a>) -> Self::Mut<'a, I>
+    where
+        I: IntoIndex + 'a,
+        Self: 'a,
+    {
+        DynMut { schema, rows, _marker: PhantomData }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use generativity::make_guard;
+
+    use super::*;
+    use crate::alloc::manager::{Manager, SoA, Versioned};
+
+    #[test]
+    fn mixes_static_and_dynamic_columns() {
+        let counter = Rc::new(());
+        let schema = DynSchema::new([DynColumn::of::<u8>(), DynColumn::of::<Rc<()>>()]);
+        let row = |i: u8| {
+            DynColumns::builder(&schema).set(0, i).unwrap().set(1, counter.clone()).unwrap().build()
+        };
+        assert_eq!(
+            Err(StoreError::ColumnType(0)),
+            DynColumns::builder(&schema).set(0, 1u32).map(drop)
+        );
+        assert_eq!(
+            Err(StoreError::ColumnIndex(2, 2)),
+            DynColumns::builder(&schema).set(2, 1u8).map(drop)
+        );
+        assert_eq!(
+            Err(StoreError::MissingColumn(1)),
+            DynColumns::builder(&schema).set(0, 1u8).unwrap().build().map(drop)
+        );
+
+        let mut store =
+            SoAFreelistStore::<Join<((u64,), DynColumns)>>::with_schema(((), schema.clone()), 2);
+        let a = store.insert_within_capacity(Join(((10,), row(1).unwrap()))).unwrap();
+        let b = store.insert_within_capacity(Join(((20,), row(2).unwrap()))).unwrap();
+        store.widen(8).unwrap();
+        let mut view: JoinMut<'_, ((u64,), DynColumns), Index> = store.view_mut();
+        *view.part1_mut().get_mut::<u8>(0, b).unwrap() += 1;
+        assert_eq!(Err(StoreError::ColumnType(0)), view.part1().get::<u64>(0, a).map(drop));
+        assert_eq!(
+            Err(StoreError::ColumnIndex(2, 2)),
+            view.part1_mut().get_mut::<u8>(2, a).map(drop)
+        );
+        assert_eq!(Err(StoreError::ColumnIndex(3, 2)), view.part1().get_raw(3, a));
+        let view: JoinRef<'_, ((u64,), DynColumns), Index> = store.view();
+        assert_eq!(Ok(&20), view.part0().col0(b));
+        assert_eq!(Ok(&3), view.part1().get::<u8>(0, b));
+        assert_eq!(3, Rc::strong_count(&counter));
+
+        let cloned = view.part1().clone_row(a).unwrap();
+        assert_eq!(4, Rc::strong_count(&counter));
+        let removed = store.remove(a).unwrap();
+        assert_eq!(Ok(&1), removed.0.1.get::<u8>(0));
+        drop((cloned, removed));
+        assert_eq!(2, Rc::strong_count(&counter));
+
+        make_guard!(guard);
+        let mut manager = Manager::<SoA<DynColumns>, Versioned<true>>::with_store(
+            guard,
+            SoAFreelistStore::with_schema(((), schema.clone()), 4),
+        );
+        let handle = manager.insert_within_capacity(row(5).unwrap()).unwrap();
+        assert_eq!(Ok(&5), manager.view().get::<u8>(0, handle));
+        assert_eq!(
+            Err(StoreError::ColumnIndex(7, 2)),
+            manager.view().get::<u8>(7, handle).map(drop)
+        );
+        assert_eq!(Ok(5), manager.remove(handle).unwrap().get::<u8>(0).copied());
+    }
+}
@@ -0,0 +1,204 @@
+use super::*;
+
+/// Enums stored as a column of variant indices followed by one column per field position,
+/// which is shared by all variants that have a field at this position.
+///
+/// Implement this with `#[derive(Columns)]` (requires the `derive` feature).
+/// # Safety
+/// Column 0 has to be registered with [`variant_layout`],
+/// all other columns with [`union_layout`] of the fields at that position.
+pub unsafe trait EnumColumns: Columns<Schema = ()> {
+    /// Borrowed view of a variant.
+    type VariantRef<'a>
+    where
+        Self: 'a;
+    /// Mutably borrowed view of a variant.
+    type VariantMut<'a>
+    where
+        Self: 'a;
+
+    /// # Safety
+    /// `index` has to be occupied.
+    unsafe fn variant_ref<'a>(rows: RawRows<'a>, index: Index) -> Self::VariantRef<'a>;
+    /// Every column that is borrowed has to be reported with [`RawRows::mark`].
+    /// # Safety
+    /// `index` has to be occupied and not borrowed elsewhere.
+    unsafe fn variant_mut<'a>(rows: RawRows<'a>, index: Index) -> Self::VariantMut<'a>;
+}
+/// Layout of one slot of the variant column, which doubles as freelist.
+pub const fn variant_layout() -> Layout {
+    Layout::new::<FreelistEntry<u32>>()
+}
+/// Layout of one slot that can hold any of `layouts`.
+pub const fn union_layout(layouts: &[Layout]) -> Layout {
+    let (mut size, mut align) = (0, 1);
+    let mut i = 0;
+    while i < layouts.len() {
+        if layouts[i].size() > size {
+            size = layouts[i].size();
+        }
+        if layouts[i].align() > align {
+            align = layouts[i].align();
+        }
+        i += 1;
+    }
+    match Layout::from_size_align(size.next_multiple_of(align), align) {
+        Ok(layout) => layout,
+        Err(_) => panic!("union is too large"),
+    }
+}
+/// Layout of a column with `rows` slots of `slot`.
+pub fn column_layout(slot: Layout, rows: Length) -> Result<Layout, LayoutError> {
+    Layout::from_size_align(slot.size() * rows as usize, slot.align())
+}
+/// # Safety
+/// `column` has to be registered with [`column_layout`] of `slot`
+/// and `index` has to be within capacity.
+pub unsafe fn slot(column: NonNull<u8>, slot: Layout, index: Index) -> NonNull<u8> {
+    // SAFETY: guarantied by caller
+    unsafe { column.add(index.get() as usize * slot.size()) }
+}
+/// # Safety
+/// `columns` have to be registered by an [`EnumColumns`] and `index` has to be occupied.
+pub unsafe fn read_variant(columns: &[NonNull<u8>], index: Index) -> u32 {
+    // SAFETY: guarantied by caller
+    unsafe { slot(columns[0], variant_layout(), index).cast::<u32>().read() }
+}
+/// # Safety
+/// `rows` have to be registered by an [`EnumColumns`] and `index` has to be occupied.
+pub unsafe fn read_row_variant(rows: &RawRows<'_>, index: Index) -> u32 {
+    // SAFETY: guarantied by caller
+    unsafe { rows.element(0, index, variant_layout().size()).cast::<u32>().read() }
+}
+/// # Safety
+/// `columns` have to be registered by an [`EnumColumns`] and `index` has to be within capacity.
+pub unsafe fn write_variant(columns: &[NonNull<u8>], index: Index, variant: u32) {
+    // SAFETY: guarantied by caller
+    unsafe { slot(columns[0], variant_layout(), index).cast::<u32>().write(variant) };
+}
+/// # Safety
+/// `columns` have to be registered by an [`EnumColumns`] and `index` has to be within capacity.
+#[expect(clippy::mut_from_ref, reason = "caller is responsible for this")]
+pub unsafe fn variant_freelist_entry(columns: &[NonNull<u8>], index: Index) -> &mut Option<Index> {
+    // SAFETY: guarantied by caller
+    unsafe { slot(columns[0], variant_layout(), index).cast::<Option<Index>>().as_mut() }
+}
+
+pub struct EnumRef<'a, E, I>(RawRows<'a>, PhantomData<fn(I) -> &'a E>);
+pub struct EnumMut<'a, E, I>(RawRows<'a>, PhantomData<fn(I) -> &'a mut E>);
+impl<'a, E, I> EnumRef<'a, E, I>
+where
+    E: EnumColumns + 'a,
+    I: IntoIndex,
+{
+    /// # Safety
+    /// `rows` have to be registered by the schema of `E` and must not be written to for `'a`.
+    pub unsafe fn new(rows: RawRows<'a>) -> Self {
+        Self(rows, PhantomData)
+    }
+    /// Position of the variant stored at `index`, in declaration order.
+    pub fn variant(&self, index: I) -> SResult<u32> {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation(), index)? };
+        // SAFETY: index is occupied
+        Ok(unsafe { read_row_variant(&self.0, index) })
+    }
+    pub fn get(&self, index: I) -> SResult<E::VariantRef<'a>> {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation(), index)? };
+        // SAFETY: index is occupied
+        Ok(unsafe { E::variant_ref(self.0, index) })
+    }
+}
+impl<'a, E, I> EnumMut<'a, E, I>
+where
+    E: EnumColumns + 'a,
+    I: IntoIndex,
+{
+    /// # Safety
+    /// `rows` have to be registered by the schema of `E` and must not be accessed otherwise for `'a`.
+    pub unsafe fn new(rows: RawRows<'a>) -> Self {
+        Self(rows, PhantomData)
+    }
+    /// Position of the variant stored at `index`, in declaration order.
+    pub fn variant(&self, index: I) -> SResult<u32> {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation(), index)? };
+        // SAFETY: index is occupied
+        Ok(unsafe { read_row_variant(&self.0, index) })
+    }
+    pub fn get(&self, index: I) -> SResult<E::VariantRef<'_>> {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation(), index)? };
+        // SAFETY: index is occupied
+        Ok(unsafe { E::variant_ref(self.0, index) })
+    }
+    pub fn get_mut(&mut self, index: I) -> SResult<E::VariantMut<'_>> {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation(), index)? };
+        // SAFETY: index is occupied and self is borrowed mutably
+        Ok(unsafe { E::variant_mut(self.0, index) })
+    }
+    pub fn into_get(self, index: I) -> SResult<E::VariantRef<'a>> {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation(), index)? };
+        // SAFETY: index is occupied
+        Ok(unsafe { E::variant_ref(self.0, index) })
+    }
+    pub fn into_get_mut(self, index: I) -> SResult<E::VariantMut<'a>> {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation(), index)? };
+        // SAFETY: index is occupied and self is consumed
+        Ok(unsafe { E::variant_mut(self.0, index) })
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+    use crate::alloc::manager::{Manager, SoA, Versioned};
+
+    #[derive(Debug, Clone, PartialEq, Columns)]
+    enum Event {
+        Click { x: f32, y: f32 },
+        Key(u8),
+        Text(String),
+        Quit,
+    }
+
+    #[test]
+    fn splits_variants_into_columns() {
+        assert_eq!(3, Event::count(&()));
+        let mut store = SoAFreelistStore::<Event>::with_capacity(2);
+        let click = store.insert_within_capacity(Event::Click { x: 1.0, y: 2.0 }).unwrap();
+        let text = store.insert_within_capacity(Event::Text("hi".into())).unwrap();
+        store.widen(4).unwrap();
+        let quit = store.insert_within_capacity(Event::Quit).unwrap();
+        let mut view: EnumMut<'_, Event, Index> = store.view_mut();
+        if let EventMut::Click { x, .. } = view.get_mut(click).unwrap() {
+            *x += 1.0;
+        }
+        assert_eq!(Ok(2), view.variant(text));
+        assert!(matches!(view.get(text), Ok(EventRef::Text(text)) if text == "hi"));
+        assert!(matches!(view.get(quit), Ok(EventRef::Quit)));
+        let Ok(Event::Click { x, y }) = store.remove(click) else { panic!("wrong variant") };
+        assert_eq!((2.0, 2.0), (x, y));
+        let key = store.insert_within_capacity(Event::Key(7)).unwrap();
+        assert_eq!(click, key, "slot should be reused");
+        assert_eq!(Ok(Event::Text("hi".into())), store.remove(text));
+
+        make_guard!(guard);
+        let mut manager = Manager::<SoA<Event>, Versioned<true>>::new(guard);
+        let handle = manager.insert_within_capacity(Event::Key(3)).unwrap();
+        assert!(matches!(manager.view().get(handle), Ok(EventRef::Key(3))));
+    }
+}
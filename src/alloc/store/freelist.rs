@@ -1,5 +1,8 @@
 use std::mem::replace;
 
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
 use super::*;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -155,6 +158,60 @@ impl<T> Remove<Single<T>> for FreelistStore<T> {
     }
 }
 impl<T> ReusableStore<T> for FreelistStore<T> {}
+impl<T> Restore<T> for FreelistStore<T> {
+    fn restore(&mut self, index: Index, data: T) -> Result<(), (T, StoreError)> {
+        let len = self.data.len() as Length;
+        let Some(entry) = self.data.get_mut(index.get() as usize) else {
+            return Err((data, StoreError::OutOfBounds(index, len)));
+        };
+        let Entry::Free(next) = *entry else {
+            return Err((data, StoreError::Occupied(index)));
+        };
+        *entry = Entry::Occupied(data);
+        // NOTE: restoring the most recently freed slot is the common case
+        if self.head == Some(index) {
+            self.head = next;
+            return Ok(());
+        }
+        let mut current = self.head;
+        while let Some(i) = current {
+            let Entry::Free(link) = &mut self.data[i.get() as usize] else {
+                unreachable!("freelist should only point to free entries")
+            };
+            if *link == Some(index) {
+                *link = next;
+                break;
+            }
+            current = *link;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T> ParIter<T> for FreelistStore<T> {
+    fn par_iter<'a>(&'a self) -> impl ParallelIterator<Item = (Index, &'a T)>
+    where
+        T: Sync + 'a,
+    {
+        self.data.par_iter().enumerate().filter_map(|(i, entry)| match entry {
+            // SAFETY: all elements are within capacity
+            Entry::Occupied(x) => Some((unsafe { Index::new_unchecked(i as u32) }, x)),
+            Entry::Free(_) => None,
+        })
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl ParallelIterator<Item = (Index, &'a mut T)>
+    where
+        T: Send + 'a,
+    {
+        self.data.par_iter_mut().enumerate().filter_map(|(i, entry)| match entry {
+            // SAFETY: all elements are within capacity
+            Entry::Occupied(x) => Some((unsafe { Index::new_unchecked(i as u32) }, x)),
+            Entry::Free(_) => None,
+        })
+    }
+}
 
 #[cfg(test)]
 mod test {
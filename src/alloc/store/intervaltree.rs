@@ -1,71 +1,187 @@
-// - use a tree that stores allocated memory as Range<Index>
-// - order by data.begin
-// - also store as cumulant:
-//   - total range
-//     leaf = data
-//     branch = left.total.begin..right.total.end
-//   - biggest gap inside of total range
-//     leaf = 0
-//     branch = max[left.gap, data.begin - left.total.end, right.total.begin - data.end, right.gap]
-// - find leftmost fitting gap by traversing tree:
-//   - left.gap is big enough => go left
-//   - data.begin - left.total.end is big enough => found gap
-//   - right.total.begin - data.end is big enough => found gap
-//   - right.gap is big enough => go right
-//   - else no fitting gap exists (can only happen on root)
-// TODO: if the leftmost element does not start at 0 the space cannot be reclaimed
-//   => check first.data.begin >= gap manually before searching
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    slice,
+};
 
 use super::*;
 
+/// Store of ranges with different sizes, which are reused after being removed.
+///
+/// Free ranges are kept by start and by size,
+/// so an insert takes the smallest fitting gap and a removal merges its range with neighbouring gaps.
+/// Unused slots hold `T::default()`, so reads only have to check the capacity
+/// and never look at the bookkeeping, which is changed by inserts of exclusive arenas
+/// while other ranges are read.
+#[derive(Debug)]
 pub struct IntervaltreeStore<T> {
-    // HACK: temporary get rid of compiler error
-    x: T,
+    data: Vec<T>,
+    /// Ends of the used ranges, by their start.
+    used: BTreeMap<Length, Length>,
+    /// Ends of the free ranges, by their start.
+    free: BTreeMap<Length, Length>,
+    /// Sizes and starts of the free ranges.
+    gaps: BTreeSet<(Length, Length)>,
+}
+impl<T: Copy + Default> IntervaltreeStore<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_capacity(capacity: Length) -> Self {
+        if capacity > Index::MAX.get() {
+            panic!("capacity exceeds largest possible index!")
+        }
+        let mut store = Self::default();
+        store.data.resize(capacity as usize, T::default());
+        store.add_gap(0, capacity);
+        store
+    }
+}
+impl<T> Default for IntervaltreeStore<T> {
+    fn default() -> Self {
+        Self {
+            data: Vec::new(),
+            used: BTreeMap::new(),
+            free: BTreeMap::new(),
+            gaps: BTreeSet::new(),
+        }
+    }
+}
+impl<T> IntervaltreeStore<T> {
+    fn add_gap(&mut self, start: Length, end: Length) {
+        if start < end {
+            self.free.insert(start, end);
+            self.gaps.insert((end - start, start));
+        }
+    }
+    fn take_gap(&mut self, start: Length) -> Option<Length> {
+        let end = self.free.remove(&start)?;
+        self.gaps.remove(&(end - start, start));
+        Some(end)
+    }
+    /// Frees `start..end` together with the gaps right before and after it.
+    fn release(&mut self, mut start: Length, mut end: Length) {
+        let before = self.free.range(..start).next_back().map(|(&before, &until)| (before, until));
+        if let Some((before, _)) = before.filter(|&(_, until)| until == start) {
+            self.take_gap(before);
+            start = before;
+        }
+        if let Some(after) = self.take_gap(end) {
+            end = after;
+        }
+        self.add_gap(start, end);
+    }
+    fn check(&self, range: &Range<Index>) -> SResult<(usize, usize)> {
+        let (start, end) = (range.start.get(), range.end.get());
+        if start > end || end as usize > self.data.len() {
+            return Err(StoreError::OutOfBounds(range.start, self.data.len() as Length));
+        }
+        Ok((start as usize, (end - start) as usize))
+    }
 }
-
 impl<T> Get<Multi<T>> for IntervaltreeStore<T> {
-    fn get(&self, index: <Multi<T> as Element>::Index) -> SResult<<Multi<T> as Element>::Ref<'_>> {
-        todo!()
+    fn get(&self, index: Range<Index>) -> SResult<&[T]> {
+        let (start, len) = self.check(&index)?;
+        // SAFETY: the range is within capacity, only its own slots are borrowed
+        Ok(unsafe { slice::from_raw_parts(self.data.as_ptr().add(start), len) })
     }
 
-    fn get_mut(
+    fn get_mut(&mut self, index: Range<Index>) -> SResult<&mut [T]> {
+        let (start, len) = self.check(&index)?;
+        // SAFETY: the range is within capacity, only its own slots are borrowed
+        Ok(unsafe { slice::from_raw_parts_mut(self.data.as_mut_ptr().add(start), len) })
+    }
+}
+impl<T> GetDisjointMut<Multi<T>> for IntervaltreeStore<T> {
+    fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        indices: [Range<Index>; N],
+    ) -> SResult<[&mut [T]; N]> {
+        for (i, range) in indices.iter().enumerate() {
+            self.check(range)?;
+            if indices[..i].iter().any(|other| range.start < other.end && other.start < range.end) {
+                return Err(GetDisjointMutError::OverlappingIndices.into());
+            }
+        }
+        // SAFETY: ranges are within capacity and don't overlap
+        Ok(unsafe { self.get_disjoint_unchecked_mut(indices) })
+    }
+
+    unsafe fn get_disjoint_unchecked_mut<const N: usize>(
         &mut self,
-        index: <Multi<T> as Element>::Index,
-    ) -> SResult<<Multi<T> as Element>::Mut<'_>> {
-        todo!()
+        indices: [Range<Index>; N],
+    ) -> [&mut [T]; N] {
+        let data = self.data.as_mut_ptr();
+        indices.map(|range| {
+            let len = (range.end.get() - range.start.get()) as usize;
+            // SAFETY: assumptions guarantied by caller
+            unsafe { slice::from_raw_parts_mut(data.add(range.start.get() as usize), len) }
+        })
     }
 }
 impl<T> InsertIndirect<Multi<T>> for IntervaltreeStore<T> {
     type Guard<'a>
-        = InsertIndirectGuard<'a, T>
+        = &'a mut [MaybeUninit<T>]
     where
         Self: 'a;
 
     fn insert_indirect_within_capacity(
         &mut self,
-        args: Length,
-    ) -> Option<(<Multi<T> as Element>::Index, Self::Guard<'_>)> {
-        todo!()
+        size: Length,
+    ) -> Option<(Range<Index>, Self::Guard<'_>)> {
+        let start = if size == 0 {
+            0
+        } else {
+            let &(_, start) = self.gaps.range((size, 0)..).next()?;
+            let end = self.take_gap(start).expect("gaps and free ranges should match");
+            self.add_gap(start + size, end);
+            self.used.insert(start, start + size);
+            start
+        };
+        // SAFETY: ranges end within capacity, which is never larger than `Index::MAX`
+        let range = unsafe { Index::new_unchecked(start)..Index::new_unchecked(start + size) };
+        // SAFETY: the range is within capacity and not used by anything else
+        let data = unsafe {
+            slice::from_raw_parts_mut(
+                self.data.as_mut_ptr().add(start as usize).cast(),
+                size as usize,
+            )
+        };
+        Some((range, data))
     }
 }
-impl<T> Resizable for IntervaltreeStore<T> {
+impl<T: Copy + Default> Resizable for IntervaltreeStore<T> {
     fn capacity(&self) -> Length {
-        todo!()
+        self.data.len() as Length
     }
 
     fn widen(&mut self, new_capacity: Length) -> SResult<()> {
-        todo!()
+        let capacity = self.capacity();
+        let target = new_capacity.max(capacity.saturating_mul(2)).min(Index::MAX.get());
+        if target < new_capacity {
+            return Err(StoreError::OutofMemory(capacity, new_capacity));
+        }
+        if target > capacity {
+            self.data.resize(target as usize, T::default());
+            self.release(capacity, target);
+        }
+        Ok(())
     }
+    /// Removed ranges are not dropped
     fn clear(&mut self) {
-        todo!()
+        self.used.clear();
+        self.free.clear();
+        self.gaps.clear();
+        self.add_gap(0, self.capacity());
     }
 }
-impl<T> MultiStore<T> for IntervaltreeStore<T> {}
+impl<T: Copy + Default> MultiStore<T> for IntervaltreeStore<T> {}
 
-pub struct IntervaltreeRemoveGuard<'a, T>(PhantomData<&'a T>);
+/// Data of a removed range, which stays valid until the store is changed again.
+#[derive(Debug)]
+pub struct IntervaltreeRemoveGuard<'a, T>(&'a [T]);
 impl<'a, T> AsRef<&'a [T]> for IntervaltreeRemoveGuard<'a, T> {
     fn as_ref(&self) -> &&'a [T] {
-        todo!()
+        &self.0
     }
 }
 impl<T> RemoveIndirect<Multi<T>> for IntervaltreeStore<T> {
@@ -74,8 +190,60 @@ impl<T> RemoveIndirect<Multi<T>> for IntervaltreeStore<T> {
     where
         Self: 'a;
 
-    fn remove_indirect(&mut self, index: <Multi<T> as Element>::Index) -> SResult<Self::Guard<'_>> {
-        todo!()
+    /// `index` has to be a whole range that was inserted before.
+    fn remove_indirect(&mut self, index: Range<Index>) -> SResult<Self::Guard<'_>> {
+        let (start, len) = self.check(&index)?;
+        if len > 0 {
+            match self.used.get(&index.start.get()) {
+                Some(&end) if end == index.end.get() => {},
+                Some(&end) => return Err(StoreError::OutOfBounds(index.end, end)),
+                None => return Err(StoreError::DoubleFree(index.start)),
+            }
+            self.used.remove(&index.start.get());
+            self.release(index.start.get(), index.end.get());
+        }
+        // SAFETY: the range is within capacity, only its own slots are borrowed
+        Ok(IntervaltreeRemoveGuard(unsafe {
+            slice::from_raw_parts(self.data.as_ptr().add(start), len)
+        }))
+    }
+}
+impl<T: Copy + Default> ReusableMultiStore<T> for IntervaltreeStore<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn insert(store: &mut IntervaltreeStore<u32>, size: Length) -> Range<Index> {
+        let (range, data) = store.insert_indirect_within_capacity(size).unwrap();
+        data.fill(MaybeUninit::new(size));
+        range
+    }
+
+    #[test]
+    fn reuses_removed_ranges() {
+        let mut store = IntervaltreeStore::<u32>::with_capacity(10);
+        let a = insert(&mut store, 3);
+        let b = insert(&mut store, 2);
+        let c = insert(&mut store, 4);
+        assert!(store.insert_indirect_within_capacity(2).is_none());
+        assert_eq!(Ok(&[2, 2][..]), store.remove_indirect(b.clone()).map(|data| *data.as_ref()));
+        assert_eq!(
+            Err(StoreError::DoubleFree(b.start)),
+            store.remove_indirect(b.clone()).map(drop)
+        );
+        assert_eq!(9, insert(&mut store, 1).start.get(), "smallest fitting gap should be used");
+        let d = insert(&mut store, 2);
+        assert_eq!(b, d);
+        store.remove_indirect(a.clone()).unwrap();
+        store.remove_indirect(d).unwrap();
+        assert_eq!(a.start, insert(&mut store, 5).start, "freed neighbours should be merged");
+        assert_eq!(Ok(&[4, 4, 4, 4][..]), store.get(c));
+
+        store.widen(11).unwrap();
+        assert_eq!(20, store.capacity());
+        assert_eq!(10, insert(&mut store, 10).start.get(), "tail gap should grow");
+        store.clear();
+        assert_eq!(0, insert(&mut store, 20).start.get());
     }
 }
-impl<T> ReusableMultiStore<T> for IntervaltreeStore<T> {}
@@ -0,0 +1,324 @@
+use std::fmt;
+
+use super::*;
+
+/// Column of variable-length lists, the list of a row is freed when the row is removed.
+///
+/// Every row keeps its own allocation in the store,
+/// so ports of a versioned arena can grow the lists of their rows at the same time
+/// without moving the lists that other ports are reading.
+/// As part of a [`Join`], e.g. `Join<((u32, f32), Jagged<Tag>)>`,
+/// the lists are accessed through [`JaggedRef`] and [`JaggedMut`].
+/// As an element of a tuple, e.g. `(u32, Jagged<Tag>)`,
+/// they are accessed through [`TupleRef::get`] and [`TupleMut::push`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Jagged<T>(pub Vec<T>);
+impl<T> From<Vec<T>> for Jagged<T> {
+    fn from(value: Vec<T>) -> Self {
+        Self(value)
+    }
+}
+
+/// # Safety
+/// `rows` have to be registered by [`Jagged`] and `index` has to be validated.
+unsafe fn row_list<T>(rows: &RawRows<'_>, index: Index) -> NonNull<Vec<T>> {
+    // SAFETY: guarantied by caller
+    unsafe { rows.slot::<FreelistEntry<Vec<T>>>(0, index).cast::<Vec<T>>() }
+}
+
+pub struct JaggedRef<'a, T, I> {
+    rows:    RawRows<'a>,
+    _marker: PhantomData<fn(I) -> &'a T>,
+}
+pub struct JaggedMut<'a, T, I> {
+    rows:    RawRows<'a>,
+    _marker: PhantomData<fn(I) -> &'a mut T>,
+}
+impl<T, I> fmt::Debug for JaggedRef<'_, T, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JaggedRef").finish_non_exhaustive()
+    }
+}
+impl<T, I> fmt::Debug for JaggedMut<'_, T, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JaggedMut").finish_non_exhaustive()
+    }
+}
+impl<'a, T, I: IntoIndex> JaggedRef<'a, T, I> {
+    pub fn get(&self, index: I) -> SResult<&'a [T]> {
+        let index = index.into_index();
+        // SAFETY: rows hold a valid pointer to an occupation table
+        unsafe { validate_row_index(self.rows.occupation(), index)? };
+        // SAFETY: the row is occupied, so it holds a list
+        Ok(unsafe { row_list::<T>(&self.rows, index).as_ref() })
+    }
+}
+impl<'a, T, I: IntoIndex> JaggedMut<'a, T, I> {
+    /// Validates `index` and returns its list, marking the row as changed when `mark` is set.
+    fn list(&self, index: I, mark: bool) -> SResult<NonNull<Vec<T>>> {
+        let index = index.into_index();
+        // SAFETY: rows hold a valid pointer to an occupation table
+        unsafe { validate_row_index(self.rows.occupation(), index)? };
+        if mark {
+            // SAFETY: index was validated above
+            unsafe { self.rows.mark(0, index) };
+        }
+        // SAFETY: index was validated above
+        Ok(unsafe { row_list(&self.rows, index) })
+    }
+    pub fn get(&self, index: I) -> SResult<&[T]> {
+        // SAFETY: the row is occupied
+        Ok(unsafe { self.list(index, false)?.as_ref() })
+    }
+    pub fn get_mut(&mut self, index: I) -> SResult<&mut [T]> {
+        // SAFETY: the row is occupied and self is borrowed mutably
+        Ok(unsafe { self.list(index, true)?.as_mut() })
+    }
+    pub fn into_get_mut(self, index: I) -> SResult<&'a mut [T]> {
+        // SAFETY: the row is occupied and self is consumed
+        Ok(unsafe { self.list(index, true)?.as_mut() })
+    }
+    /// Appends `value` to the list at `index`, which only moves the list of this row.
+    pub fn push(&mut self, index: I, value: T) -> SResult<()> {
+        // SAFETY: the row is occupied and self is borrowed mutably
+        unsafe { self.list(index, true)?.as_mut().push(value) };
+        Ok(())
+    }
+    pub fn pop(&mut self, index: I) -> SResult<Option<T>> {
+        // SAFETY: the row is occupied and self is borrowed mutably
+        Ok(unsafe { self.list(index, true)?.as_mut().pop() })
+    }
+}
+
+/// # Safety
+/// Column `N` of `rows` has to hold `C::Slot`s and `index` has to be validated.
+unsafe fn list_of<const N: usize, C, T>(rows: &RawRows<'_>, index: Index) -> NonNull<Vec<T>>
+where
+    C: Column<N, Item = Jagged<T>>,
+{
+    // SAFETY: guarantied by caller, the item is stored at offset 0 of its slot
+    unsafe { rows.element(N, index, size_of::<C::Slot>()).cast::<Jagged<T>>().cast() }
+}
+impl<'a, C, I: IntoIndex> TupleRef<'a, C, I> {
+    /// List in the [`Jagged`] column `N` of the row at `index`.
+    pub fn get<const N: usize, T>(&self, index: I) -> SResult<&'a [T]>
+    where
+        C: Column<N, Item = Jagged<T>>,
+    {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation, index)? };
+        // SAFETY: index was validated above
+        Ok(unsafe { list_of::<N, C, T>(&self.0, index).as_ref() })
+    }
+}
+impl<C, I: IntoIndex> TupleMut<'_, C, I> {
+    /// Validates `index` and returns the list in column `N`, marking it as changed when `mark` is set.
+    fn list<const N: usize, T>(&self, index: I, mark: bool) -> SResult<NonNull<Vec<T>>>
+    where
+        C: Column<N, Item = Jagged<T>>,
+    {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation, index)? };
+        if mark {
+            // SAFETY: index was validated above
+            unsafe { self.0.mark(N, index) };
+        }
+        // SAFETY: index was validated above
+        Ok(unsafe { list_of::<N, C, T>(&self.0, index) })
+    }
+    /// List in the [`Jagged`] column `N` of the row at `index`.
+    pub fn get<const N: usize, T>(&self, index: I) -> SResult<&[T]>
+    where
+        C: Column<N, Item = Jagged<T>>,
+    {
+        // SAFETY: the row is occupied
+        Ok(unsafe { self.list::<N, T>(index, false)?.as_ref() })
+    }
+    pub fn get_mut<const N: usize, T>(&mut self, index: I) -> SResult<&mut [T]>
+    where
+        C: Column<N, Item = Jagged<T>>,
+    {
+        // SAFETY: the row is occupied and self is borrowed mutably
+        Ok(unsafe { self.list::<N, T>(index, true)?.as_mut() })
+    }
+    /// Appends `value` to the list in the [`Jagged`] column `N` of the row at `index`.
+    pub fn push<const N: usize, T>(&mut self, index: I, value: T) -> SResult<()>
+    where
+        C: Column<N, Item = Jagged<T>>,
+    {
+        // SAFETY: the row is occupied and self is borrowed mutably
+        unsafe { self.list::<N, T>(index, true)?.as_mut().push(value) };
+        Ok(())
+    }
+    pub fn pop<const N: usize, T>(&mut self, index: I) -> SResult<Option<T>>
+    where
+        C: Column<N, Item = Jagged<T>>,
+    {
+        // SAFETY: the row is occupied and self is borrowed mutably
+        Ok(unsafe { self.list::<N, T>(index, true)?.as_mut().pop() })
+    }
+}
+
+// SAFETY: the only column is registered as `FreelistEntry<Vec<T>>` and holds the list of each row
+unsafe impl<T> Columns for Jagged<T> {
+    type Schema = ();
+
+    type Ref<'a, I>
+        = JaggedRef<'a, T, I>
+    where
+        I: IntoIndex + 'a,
+        Self: 'a;
+    type Mut<'a, I>
+        = JaggedMut<'a, T, I>
+    where
+        I: IntoIndex + 'a,
+        Self: 'a;
+
+    fn count(_: &()) -> usize {
+        1
+    }
+    fn register_layout(
+        _: &(),
+        rows: Length,
+        register: &mut impl FnMut(Layout),
+    ) -> Result<(), LayoutError> {
+        register(Layout::array::<FreelistEntry<Vec<T>>>(rows as usize)?);
+        Ok(())
+    }
+    fn move_into(self, _: &(), index: Index, columns: &[NonNull<u8>]) {
+        // SAFETY: column 0 was registered to be of type FreelistEntry<Vec<T>>
+        unsafe {
+            columns[0]
+                .cast::<FreelistEntry<Vec<T>>>()
+                .add(index.get() as usize)
+                .cast::<Vec<T>>()
+                .write(self.0)
+        };
+    }
+    fn take(_: &(), index: Index, columns: &[NonNull<u8>]) -> Self {
+        // SAFETY: column 0 was registered to be of type FreelistEntry<Vec<T>> and holds a list
+        Self(unsafe {
+            columns[0]
+                .cast::<FreelistEntry<Vec<T>>>()
+                .add(index.get() as usize)
+                .cast::<Vec<T>>()
+                .read()
+        })
+    }
+    fn as_freelist_entry<'a>(
+        _: &(),
+        index: Index,
+        columns: &'a [NonNull<u8>],
+    ) -> &'a mut Option<Index> {
+        // SAFETY: column 0 was registered to be of type FreelistEntry<Vec<T>>
+        unsafe {
+            columns[0]
+                .cast::<FreelistEntry<Vec<T>>>()
+                .add(index.get() as usize)
+                .cast::<Option<Index>>()
+                .as_mut()
+        }
+    }
+    unsafe fn make_ref<'a, I>(_: &'a (), rows: RawRows<'a>) -> Self::Ref<'a, I>
+    where
+        I: IntoIndex + 'a,
+        Self: 'a,
+    {
+        JaggedRef { rows, _marker: PhantomData }
+    }
+    unsafe fn make_mut<'a, I>(_: &'a (), rows: RawRows<'a>) -> Self::Mut<'a, I>
+    where
+        I: IntoIndex + 'a,
+        Self: 'a,
+    {
+        JaggedMut { rows, _marker: PhantomData }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+    use crate::alloc::{
+        arena::{Arena, Headless},
+        manager::{Manager, SoA, Versioned},
+    };
+
+    #[test]
+    fn grows_and_frees_lists() {
+        make_guard!(guard);
+        let mut manager = Manager::<SoA<Join<((u32,), Jagged<&str>)>>, Versioned<true>>::new(guard);
+        manager.reserve(4).unwrap();
+        let a = manager.insert_within_capacity(Join(((1,), vec!["a"].into()))).unwrap();
+        let b = manager.insert_within_capacity(Join(((2,), Jagged::default()))).unwrap();
+        {
+            let mut view = manager.view_mut();
+            let mut tags = view.part1_mut();
+            for tag in ["x", "y", "z"] {
+                tags.push(b, tag).unwrap();
+            }
+            tags.push(a, "b").unwrap();
+            tags.get_mut(a).unwrap()[0] = "A";
+            assert_eq!(Ok(Some("z")), tags.pop(b));
+        }
+        let view = manager.view();
+        assert_eq!(Ok(&["A", "b"][..]), view.part1().get(a));
+        assert_eq!(Ok(&["x", "y"][..]), view.part1().get(b));
+
+        let Join(((id,), tags)) = manager.remove(b).unwrap();
+        assert_eq!((2, vec!["x", "y"]), (id, tags.0));
+        assert!(manager.view().part1().get(b).is_err());
+        let c = manager.insert_within_capacity(Join(((3,), vec!["p", "q", "r"].into()))).unwrap();
+        assert_eq!(Ok(&["p", "q", "r"][..]), manager.view().part1().get(c));
+        assert_eq!(Ok(&["A", "b"][..]), manager.view().part1().get(a));
+    }
+
+    #[test]
+    fn ports_grow_lists_without_moving_others() {
+        make_guard!(a);
+        make_guard!(b);
+        make_guard!(man);
+        type Kind = SoA<Join<((u32,), Jagged<u64>)>>;
+        let mut a = Arena::<Kind, Versioned<true>>::new(a, man, Headless::default());
+        let mut b = a.split(b);
+        let first = a.alloc().insert(Join(((0,), vec![0xAAAA_AAAA].into()))).unwrap();
+        let second = b.alloc().insert(Join(((1,), Jagged::default()))).unwrap();
+        let read = a.read();
+        let list = read.view().part1().get(first).unwrap();
+        {
+            let mut write = b.write();
+            let mut view = write.view_mut();
+            let mut lists = view.part1_mut();
+            for value in 0..100_000 {
+                lists.push(second, value).unwrap();
+            }
+        }
+        assert_eq!(&[0xAAAA_AAAA], list);
+        drop(read);
+        assert_eq!(Ok(100_000), b.read().view().part1().get(second).map(<[_]>::len));
+    }
+
+    #[test]
+    fn accesses_lists_in_tuples() {
+        make_guard!(guard);
+        let mut manager = Manager::<SoA<(u32, Jagged<&str>)>, Versioned<true>>::new(guard);
+        manager.reserve(2).unwrap();
+        let a = manager.insert_within_capacity((1, Jagged::default())).unwrap();
+        let b = manager.insert_within_capacity((2, vec!["x"].into())).unwrap();
+        {
+            let mut view = manager.view_mut();
+            view.push::<1, _>(a, "a").unwrap();
+            view.push::<1, _>(a, "b").unwrap();
+            view.get_mut::<1, _>(a).unwrap()[0] = "A";
+            assert_eq!(Ok(Some("x")), view.pop::<1, _>(b));
+        }
+        assert_eq!(Ok(&["A", "b"][..]), manager.view().get::<1, _>(a));
+        assert_eq!(Ok(&[][..]), manager.view().get::<1, _>(b));
+        let (id, tags) = manager.remove(a).unwrap();
+        assert_eq!((1, vec!["A", "b"]), (id, tags.0));
+        assert!(manager.view().get::<1, _>(a).is_err());
+    }
+}
@@ -22,9 +22,39 @@ pub use freelist::*;
 mod soa;
 pub use soa::*;
 
+mod aosoa;
+pub use aosoa::*;
+
 mod intervaltree;
 pub use intervaltree::*;
 
+mod column;
+pub use column::*;
+
+mod dynamic;
+pub use dynamic::*;
+
+mod enums;
+pub use enums::*;
+#[cfg(feature = "derive")]
+pub use niche_collections_derive::Columns;
+
+mod jagged;
+pub use jagged::*;
+
+mod opt;
+pub use opt::*;
+
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "rayon")]
+pub use par::*;
+
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "arrow")]
+pub use arrow::*;
+
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
 pub enum StoreError {
     #[error("Tried to access data at index {0} when length was {1}.")]
@@ -33,6 +63,8 @@ pub enum StoreError {
     AccessAfterFree(Index),
     #[error("Tried to free already freed data at index {0}.")]
     DoubleFree(Index),
+    #[error("Tried to restore data at occupied index {0}.")]
+    Occupied(Index),
     #[error("Cannot resize from {0} to {1}, collection too large.")]
     OutofMemory(Length, Length),
     #[error("Disjoint Error: {0}")]
@@ -41,6 +73,12 @@ pub enum StoreError {
     Narrow(Length, Length),
     #[error("Invalid columns layout: {0}")]
     InvalidLayout(&'static str),
+    #[error("Column {0} holds a different type.")]
+    ColumnType(usize),
+    #[error("Tried to access column {0} when there were {1} columns.")]
+    ColumnIndex(usize, usize),
+    #[error("Column {0} was not initialized.")]
+    MissingColumn(usize),
 }
 pub type SResult<T> = Result<T, StoreError>;
 
@@ -151,6 +189,21 @@ pub trait Resizable {
     fn widen(&mut self, new_capacity: Length) -> SResult<()>;
     fn clear(&mut self);
 }
+/// Stores that can put removed items back into their original slot.
+pub trait Restore<T> {
+    /// Returns `data` if the slot is not free.
+    fn restore(&mut self, index: Index, data: T) -> Result<(), (T, StoreError)>;
+}
+/// Append-only stores that can drop everything after a given length.
+pub trait Truncate {
+    fn len(&self) -> Length;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops all items after the first `len` items.
+    fn truncate(&mut self, len: Length);
+}
 
 // TODO: these marker traits should be automatically implemented for all applicable types
 // - convert to trait alias, or
@@ -159,8 +212,16 @@ pub trait Store<T>: Get<Single<T>> + Insert<Single<T>> + Resizable {}
 pub trait ReusableStore<T>: Store<T> + Remove<Single<T>> {}
 pub trait MultiStore<T>: Get<Multi<T>> + InsertIndirect<Multi<T>> + Resizable {}
 pub trait ReusableMultiStore<T>: MultiStore<T> + RemoveIndirect<Multi<T>> {}
+/// Stores that give access to their columns, to create accessors with additional state.
+pub trait RawView<C: Columns> {
+    fn schema(&self) -> &C::Schema;
+    /// # Safety
+    /// The returned rows may only be used for reading.
+    unsafe fn raw_rows(&self) -> RawRows<'_>;
+    fn raw_rows_mut(&mut self) -> (&C::Schema, RawRows<'_>);
+}
 pub trait SoAStore<C: Columns, IRef: IntoIndex, IMut: IntoIndex = IRef>:
-    View<Rows<C, IRef, IMut>> + Insert<Single<C>> + Resizable
+    View<Rows<C, IRef, IMut>> + Insert<Single<C>> + Resizable + RawView<C>
 {
 }
 pub trait ReusableSoAStore<C: Columns, IRef: IntoIndex, IMut: IntoIndex = IRef>:
@@ -199,7 +260,11 @@ impl IntoIndex for Index {
 /// using only raw pointers, this is inherently unsafe.
 // TODO: change columns slice to COUNT sized array once stable
 pub unsafe trait Columns: Sized {
-    const COUNT: usize;
+    /// Runtime description of the columns, `()` if they are known at compile time.
+    ///
+    /// Ports of a versioned arena create accessors of the same store at the same time,
+    /// so the schema must not hold storage that is changed through rows, see [`Jagged`].
+    type Schema;
 
     type Ref<'a, I>
     where
@@ -210,32 +275,244 @@ pub unsafe trait Columns: Sized {
         I: IntoIndex + 'a,
         Self: 'a;
 
+    /// Number of columns described by `schema`.
+    fn count(schema: &Self::Schema) -> usize;
     /// Registers each column with the store.
-    /// `register` will be called exactly `COUNT` times.
-    fn register_layout(rows: Length, register: &mut impl FnMut(Layout)) -> Result<(), LayoutError>;
+    /// `register` will be called exactly `count` times.
+    /// Memory of rows that were never written to is zeroed.
+    fn register_layout(
+        schema: &Self::Schema,
+        rows: Length,
+        register: &mut impl FnMut(Layout),
+    ) -> Result<(), LayoutError>;
     /// Moves itself to memory addresses provided by `next_column`.
-    /// `next_column` will be called exactly `COUNT` times.
-    fn move_into(self, index: Index, columns: &[NonNull<u8>]);
+    /// `next_column` will be called exactly `count` times.
+    fn move_into(self, schema: &Self::Schema, index: Index, columns: &[NonNull<u8>]);
     /// Loads itself from memory addresses provided by `next_column`.
-    /// `next_column` will be called exactly `COUNT` times.
-    fn take(index: Index, columns: &[NonNull<u8>]) -> Self;
+    /// `next_column` will be called exactly `count` times.
+    fn take(schema: &Self::Schema, index: Index, columns: &[NonNull<u8>]) -> Self;
     /// Return reference to n-th row, as a freelist entry.
-    /// `get_column` will only be called with values `0..COUNT`.
+    /// `get_column` will only be called with values `0..count`.
     #[expect(clippy::mut_from_ref, reason = "trait user is responsible for this")]
-    fn as_freelist_entry(index: Index, columns: &[NonNull<u8>]) -> &mut Option<Index>;
+    fn as_freelist_entry<'a>(
+        schema: &Self::Schema,
+        index: Index,
+        columns: &'a [NonNull<u8>],
+    ) -> &'a mut Option<Index>;
     /// Create Ref object to act as an accessor.
     /// This will be called inside of Deref, so this should be cheap.
-    fn make_ref<'a, I>(columns: &'a [NonNull<u8>], occupation_ptr: NonNull<u8>) -> Self::Ref<'a, I>
+    /// # Safety
+    /// `rows` have to be registered by `schema` and must not be written to for `'a`.
+    unsafe fn make_ref<'a, I>(schema: &'a Self::Schema, rows: RawRows<'a>) -> Self::Ref<'a, I>
     where
         I: IntoIndex,
         Self: 'a;
     /// Create Mut object to act as a mutable accessor.
     /// This will be called inside of DerefMut, so this should be cheap.
-    fn make_mut<'a, I>(columns: &'a [NonNull<u8>], occupation_ptr: NonNull<u8>) -> Self::Mut<'a, I>
+    /// Every mutable access to a column has to be reported with [`RawRows::mark`].
+    /// # Safety
+    /// `rows` have to be registered by `schema` and must not be accessed otherwise for `'a`.
+    unsafe fn make_mut<'a, I>(schema: &'a Self::Schema, rows: RawRows<'a>) -> Self::Mut<'a, I>
     where
         I: IntoIndex,
         Self: 'a;
 }
+/// Raw pointers to the columns of a store, used to create accessors.
+#[derive(Debug, Clone, Copy)]
+pub struct RawRows<'a> {
+    columns:    RawColumns<'a>,
+    occupation: NonNull<u8>,
+    len:        Length,
+    ticks:      Option<TickSink>,
+}
+#[derive(Debug, Clone, Copy)]
+enum RawColumns<'a> {
+    /// One array per column.
+    Flat(&'a [NonNull<u8>]),
+    /// Chunks of `rows` rows that hold `stride` columns each,
+    /// `columns` holds the column pointers chunk by chunk.
+    Chunked {
+        columns: &'a [NonNull<u8>],
+        stride:  usize,
+        first:   usize,
+        count:   usize,
+        rows:    usize,
+    },
+}
+impl<'a> RawRows<'a> {
+    /// # Safety
+    /// Every column has to hold `len` slots and `occupation` has to hold `len` bits,
+    /// all of them have to stay valid for `'a`.
+    pub unsafe fn new(columns: &'a [NonNull<u8>], occupation: NonNull<u8>, len: Length) -> Self {
+        Self { columns: RawColumns::Flat(columns), occupation, len, ticks: None }
+    }
+    /// Rows split into chunks of `rows` rows, where `columns` holds the pointers
+    /// to `count` columns for every chunk and `len` is a multiple of `rows`.
+    /// # Safety
+    /// Every column pointer has to hold `rows` slots and `occupation` has to hold `len` bits,
+    /// all of them have to stay valid for `'a`.
+    pub unsafe fn chunked(
+        columns: &'a [NonNull<u8>],
+        count: usize,
+        rows: usize,
+        occupation: NonNull<u8>,
+        len: Length,
+    ) -> Self {
+        debug_assert_eq!(columns.len() * rows, count * len as usize);
+        let columns = RawColumns::Chunked { columns, stride: count, first: 0, count, rows };
+        Self { columns, occupation, len, ticks: None }
+    }
+    pub fn with_ticks(self, ticks: TickSink) -> Self {
+        Self { ticks: Some(ticks), ..self }
+    }
+    /// Pointers to each column, if every column is a single array.
+    pub fn columns(&self) -> Option<&'a [NonNull<u8>]> {
+        match self.columns {
+            RawColumns::Flat(columns) => Some(columns),
+            RawColumns::Chunked { .. } => None,
+        }
+    }
+    pub fn column_count(&self) -> usize {
+        match self.columns {
+            RawColumns::Flat(columns) => columns.len(),
+            RawColumns::Chunked { count, .. } => count,
+        }
+    }
+    /// Address of the slot at `index` in `column`, where every slot is `size` bytes large.
+    /// # Safety
+    /// `index` has to be within capacity and `column` has to be a valid column.
+    pub unsafe fn element(&self, column: usize, index: Index, size: usize) -> NonNull<u8> {
+        debug_assert!(column < self.column_count());
+        let index = index.get() as usize;
+        // SAFETY: guarantied by caller
+        unsafe {
+            match self.columns {
+                RawColumns::Flat(columns) => columns.get_unchecked(column).add(index * size),
+                RawColumns::Chunked { columns, stride, first, rows, .. } => columns
+                    .get_unchecked(index / rows * stride + first + column)
+                    .add(index % rows * size),
+            }
+        }
+    }
+    /// Address of the slot at `index` in `column`, which holds slots of type `T`.
+    /// # Safety
+    /// `index` has to be within capacity and `column` has to hold slots of type `T`.
+    pub unsafe fn slot<T>(&self, column: usize, index: Index) -> NonNull<T> {
+        // SAFETY: guarantied by caller
+        unsafe { self.element(column, index, size_of::<T>()).cast() }
+    }
+    /// Address of the byte holding the bit of `index` in `column`, together with the mask of the bit.
+    /// The column has to be a bitmap, registered with one bit per row.
+    /// # Safety
+    /// `index` has to be within capacity and `column` has to be a valid column.
+    pub unsafe fn bit(&self, column: usize, index: Index) -> (NonNull<u8>, u8) {
+        debug_assert!(column < self.column_count());
+        let index = index.get() as usize;
+        // SAFETY: guarantied by caller
+        unsafe {
+            match self.columns {
+                RawColumns::Flat(columns) =>
+                    (columns.get_unchecked(column).add(index / 8), 1 << (index % 8)),
+                RawColumns::Chunked { columns, stride, first, rows, .. } => {
+                    let local = index % rows;
+                    let chunk = columns.get_unchecked(index / rows * stride + first + column);
+                    (chunk.add(local / 8), 1 << (local % 8))
+                },
+            }
+        }
+    }
+    pub fn occupation(&self) -> NonNull<u8> {
+        self.occupation
+    }
+    pub fn len(&self) -> Length {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn bitmap(&self) -> Bitmap<'a> {
+        // SAFETY: the occupation table has a bit for every row
+        unsafe { Bitmap::new(self.occupation, self.len) }
+    }
+    /// Restricts to the columns in `range`.
+    pub fn slice(self, range: Range<usize>) -> Self {
+        let columns = match self.columns {
+            RawColumns::Flat(columns) => RawColumns::Flat(&columns[range.clone()]),
+            RawColumns::Chunked { columns, stride, first, count, rows } => {
+                assert!(range.start <= range.end && range.end <= count, "columns out of bounds");
+                RawColumns::Chunked {
+                    columns,
+                    stride,
+                    first: first + range.start,
+                    count: range.end - range.start,
+                    rows,
+                }
+            },
+        };
+        Self {
+            // SAFETY: ticks are available for every column
+            ticks: self.ticks.map(|ticks| unsafe { ticks.offset(range.start) }),
+            columns,
+            ..self
+        }
+    }
+    /// Records a write to `column` at `index`.
+    /// # Safety
+    /// `index` has to be validated using [`validate_row_index`].
+    pub unsafe fn mark(&self, column: usize, index: Index) {
+        debug_assert!(column < self.column_count());
+        if let Some(ticks) = self.ticks {
+            // SAFETY: guarantied by caller
+            unsafe { ticks.mark(column, index) };
+        }
+    }
+    /// Records a write to all columns at `index`.
+    /// # Safety
+    /// `index` has to be validated using [`validate_row_index`].
+    pub unsafe fn mark_all(&self, index: Index) {
+        for column in 0..self.column_count() {
+            // SAFETY: guarantied by caller
+            unsafe { self.mark(column, index) };
+        }
+    }
+    /// Records a write to `column` for every occupied row.
+    pub fn mark_column(&self, column: usize) {
+        if self.ticks.is_some() {
+            for index in self.bitmap().ones() {
+                // SAFETY: index is occupied
+                unsafe { self.mark(column, index) };
+            }
+        }
+    }
+}
+/// Destination for the ticks recorded by [`RawRows::mark`].
+#[derive(Debug, Clone, Copy)]
+pub struct TickSink {
+    /// Ticks are stored row by row, with `stride` ticks per row.
+    base:   NonNull<Tick>,
+    stride: usize,
+    tick:   Tick,
+}
+impl TickSink {
+    /// # Safety
+    /// `base` has to point to `stride` ticks for every row within capacity of the store,
+    /// `stride` has to be at least the number of columns.
+    pub unsafe fn new(base: NonNull<Tick>, stride: usize, tick: Tick) -> Self {
+        Self { base, stride, tick }
+    }
+    /// # Safety
+    /// `columns` has to be at most the number of columns.
+    unsafe fn offset(self, columns: usize) -> Self {
+        // SAFETY: guarantied by caller
+        Self { base: unsafe { self.base.add(columns) }, ..self }
+    }
+    /// # Safety
+    /// `index` has to be within capacity and `column` has to be a valid column.
+    unsafe fn mark(self, column: usize, index: Index) {
+        // SAFETY: guarantied by caller
+        unsafe { self.base.add(index.get() as usize * self.stride + column).write(self.tick) };
+    }
+}
 pub union FreelistEntry<T> {
     _data: ManuallyDrop<T>,
     _next: Option<Index>,
@@ -252,8 +529,8 @@ pub const unsafe fn validate_row_index(occupation_ptr: NonNull<u8>, index: Index
         Ok(())
     }
 }
-pub struct TupleRef<'a, T, I>(&'a [NonNull<u8>], NonNull<u8>, PhantomData<fn(I) -> &'a T>);
-pub struct TupleMut<'a, T, I>(&'a [NonNull<u8>], NonNull<u8>, PhantomData<fn(I) -> &'a mut T>);
+pub struct TupleRef<'a, T, I>(RawRows<'a>, PhantomData<fn(I) -> &'a T>);
+pub struct TupleMut<'a, T, I>(RawRows<'a>, PhantomData<fn(I) -> &'a mut T>);
 macro_rules! impl_columns {
     ((0, $T0:ident, $t0:ident) $(, ($i:tt, $T:ident, $t:ident))*) => { paste! {
         impl<'a, $T0, $($T,)* I> TupleRef<'a, ($T0, $($T,)*), I>
@@ -262,31 +539,31 @@ macro_rules! impl_columns {
         {
             pub fn col0(&self, index: I) -> SResult<&'a $T0> {
                 let index = index.into_index();
-                // SAFETY: self.1 is a valid pointer ot an occupation table
-                unsafe { validate_row_index(self.1, index)? };
+                // SAFETY: self.0 holds a valid pointer to an occupation table
+                unsafe { validate_row_index(self.0.occupation, index)? };
                 Ok(unsafe {
-                    self.0[0].cast::<FreelistEntry<$T0>>().add(index.get() as usize)
+                    self.0.slot::<FreelistEntry<$T0>>(0, index)
                         .cast::<$T0>().as_ref()
                 })
             }
             $(
                 pub fn [<col $i>](&self, index: I) -> SResult<&'a $T> {
                     let index = index.into_index();
-                    // SAFETY: self.1 is a valid pointer ot an occupation table
-                    unsafe { validate_row_index(self.1, index)? };
+                    // SAFETY: self.0 holds a valid pointer to an occupation table
+                    unsafe { validate_row_index(self.0.occupation, index)? };
                     Ok(unsafe {
-                        self.0[$i].cast::<$T>().add(index.get() as usize).as_ref()
+                        self.0.slot::<$T>($i, index).as_ref()
                     })
                 }
             )*
             pub fn cols(&self, index: I) -> SResult<(&'a $T0, $(&'a $T,)*)> {
                 let index = index.into_index();
-                // SAFETY: self.1 is a valid pointer ot an occupation table
-                unsafe { validate_row_index(self.1, index)? };
+                // SAFETY: self.0 holds a valid pointer to an occupation table
+                unsafe { validate_row_index(self.0.occupation, index)? };
                 Ok(unsafe {(
-                    self.0[0].cast::<FreelistEntry<$T0>>().add(index.get() as usize)
+                    self.0.slot::<FreelistEntry<$T0>>(0, index)
                         .cast::<$T0>().as_ref(),
-                    $(self.0[$i].cast::<$T>().add(index.get() as usize).as_ref(),)*
+                    $(self.0.slot::<$T>($i, index).as_ref(),)*
                 )})
             }
         }
@@ -296,121 +573,133 @@ macro_rules! impl_columns {
         {
             pub fn col0(&self, index: I) -> SResult<&$T0> {
                 let index = index.into_index();
-                // SAFETY: self.1 is a valid pointer ot an occupation table
-                unsafe { validate_row_index(self.1, index)? };
+                // SAFETY: self.0 holds a valid pointer to an occupation table
+                unsafe { validate_row_index(self.0.occupation, index)? };
                 Ok(unsafe {
-                    self.0[0].cast::<FreelistEntry<$T0>>().add(index.get() as usize)
+                    self.0.slot::<FreelistEntry<$T0>>(0, index)
                         .cast::<$T0>().as_ref()
                 })
             }
             pub fn col0_mut(&mut self, index: I) -> SResult<&mut $T0> {
                 let index = index.into_index();
-                // SAFETY: self.1 is a valid pointer ot an occupation table
-                unsafe { validate_row_index(self.1, index)? };
+                // SAFETY: self.0 holds a valid pointer to an occupation table
+                unsafe { validate_row_index(self.0.occupation, index)? };
+                // SAFETY: index was validated above
+                unsafe { self.0.mark(0, index) };
                 Ok(unsafe {
-                    self.0[0].cast::<FreelistEntry<$T0>>().add(index.get() as usize)
+                    self.0.slot::<FreelistEntry<$T0>>(0, index)
                         .cast::<$T0>().as_mut()
                 })
             }
             pub fn into_col0(self, index: I) -> SResult<&'a $T0> {
                 let index = index.into_index();
-                // SAFETY: self.1 is a valid pointer ot an occupation table
-                unsafe { validate_row_index(self.1, index)? };
+                // SAFETY: self.0 holds a valid pointer to an occupation table
+                unsafe { validate_row_index(self.0.occupation, index)? };
                 Ok(unsafe {
-                    self.0[0].cast::<FreelistEntry<$T0>>().add(index.get() as usize)
+                    self.0.slot::<FreelistEntry<$T0>>(0, index)
                         .cast::<$T0>().as_ref()
                 })
             }
             pub fn into_col0_mut(self, index: I) -> SResult<&'a mut $T0> {
                 let index = index.into_index();
-                // SAFETY: self.1 is a valid pointer ot an occupation table
-                unsafe { validate_row_index(self.1, index)? };
+                // SAFETY: self.0 holds a valid pointer to an occupation table
+                unsafe { validate_row_index(self.0.occupation, index)? };
+                // SAFETY: index was validated above
+                unsafe { self.0.mark(0, index) };
                 Ok(unsafe {
-                    self.0[0].cast::<FreelistEntry<$T0>>().add(index.get() as usize)
+                    self.0.slot::<FreelistEntry<$T0>>(0, index)
                         .cast::<$T0>().as_mut()
                 })
             }
             $(
                 pub fn [<col $i>](&self, index: I) -> SResult<&$T> {
                     let index = index.into_index();
-                    // SAFETY: self.1 is a valid pointer ot an occupation table
-                    unsafe { validate_row_index(self.1, index)? };
+                    // SAFETY: self.0 holds a valid pointer to an occupation table
+                    unsafe { validate_row_index(self.0.occupation, index)? };
                     Ok(unsafe {
-                        self.0[$i].cast::<$T>().add(index.get() as usize).as_ref()
+                        self.0.slot::<$T>($i, index).as_ref()
                     })
                 }
                 pub fn [<col $i _mut>](&mut self, index: I) -> SResult<&mut $T> {
                     let index = index.into_index();
-                    // SAFETY: self.1 is a valid pointer ot an occupation table
-                    unsafe { validate_row_index(self.1, index)? };
+                    // SAFETY: self.0 holds a valid pointer to an occupation table
+                    unsafe { validate_row_index(self.0.occupation, index)? };
+                    // SAFETY: index was validated above
+                    unsafe { self.0.mark($i, index) };
                     Ok(unsafe {
-                        self.0[$i].cast::<$T>().add(index.get() as usize).as_mut()
+                        self.0.slot::<$T>($i, index).as_mut()
                     })
                 }
                 pub fn [<into_col $i>](self, index: I) -> SResult<&'a $T> {
                     let index = index.into_index();
-                    // SAFETY: self.1 is a valid pointer ot an occupation table
-                    unsafe { validate_row_index(self.1, index)? };
+                    // SAFETY: self.0 holds a valid pointer to an occupation table
+                    unsafe { validate_row_index(self.0.occupation, index)? };
                     Ok(unsafe {
-                        self.0[$i].cast::<$T>().add(index.get() as usize).as_ref()
+                        self.0.slot::<$T>($i, index).as_ref()
                     })
                 }
                 pub fn [<into_col $i _mut>](self, index: I) -> SResult<&'a mut $T> {
                     let index = index.into_index();
-                    // SAFETY: self.1 is a valid pointer ot an occupation table
-                    unsafe { validate_row_index(self.1, index)? };
+                    // SAFETY: self.0 holds a valid pointer to an occupation table
+                    unsafe { validate_row_index(self.0.occupation, index)? };
+                    // SAFETY: index was validated above
+                    unsafe { self.0.mark($i, index) };
                     Ok(unsafe {
-                        self.0[$i].cast::<$T>().add(index.get() as usize).as_mut()
+                        self.0.slot::<$T>($i, index).as_mut()
                     })
                 }
             )*
             pub fn cols(&self, index: I) -> Result<(&'_ $T0, $(&'_ $T,)*), StoreError> {
                 let index = index.into_index();
-                // SAFETY: self.1 is a valid pointer ot an occupation table
-                unsafe { validate_row_index(self.1, index)? };
+                // SAFETY: self.0 holds a valid pointer to an occupation table
+                unsafe { validate_row_index(self.0.occupation, index)? };
                 Ok(unsafe {(
-                    self.0[0].cast::<FreelistEntry<$T0>>().add(index.get() as usize)
+                    self.0.slot::<FreelistEntry<$T0>>(0, index)
                         .cast::<$T0>().as_ref(),
-                    $(self.0[$i].cast::<$T>().add(index.get() as usize).as_ref(),)*
+                    $(self.0.slot::<$T>($i, index).as_ref(),)*
                 )})
             }
             pub fn cols_mut(&mut self, index: I) -> Result<(&'_ mut $T0, $(&'_ mut $T,)*), StoreError> {
                 let index = index.into_index();
-                // SAFETY: self.1 is a valid pointer ot an occupation table
-                unsafe { validate_row_index(self.1, index)? };
+                // SAFETY: self.0 holds a valid pointer to an occupation table
+                unsafe { validate_row_index(self.0.occupation, index)? };
+                // SAFETY: index was validated above
+                unsafe { self.0.mark_all(index) };
                 Ok(unsafe {(
-                    self.0[0].cast::<FreelistEntry<$T0>>().add(index.get() as usize)
+                    self.0.slot::<FreelistEntry<$T0>>(0, index)
                         .cast::<$T0>().as_mut(),
-                    $(self.0[$i].cast::<$T>().add(index.get() as usize).as_mut(),)*
+                    $(self.0.slot::<$T>($i, index).as_mut(),)*
                 )})
             }
             pub fn into_cols(self, index: I) -> Result<(&'a $T0, $(&'a $T,)*), (Self, StoreError)> {
                 let index = index.into_index();
-                // SAFETY: self.1 is a valid pointer ot an occupation table
-                if let Err(err) = unsafe { validate_row_index(self.1, index) } {
+                // SAFETY: self.0 holds a valid pointer to an occupation table
+                if let Err(err) = unsafe { validate_row_index(self.0.occupation, index) } {
                     return Err((self, err));
                 }
                 Ok(unsafe {(
-                    self.0[0].cast::<FreelistEntry<$T0>>().add(index.get() as usize)
+                    self.0.slot::<FreelistEntry<$T0>>(0, index)
                         .cast::<$T0>().as_ref(),
-                    $(self.0[$i].cast::<$T>().add(index.get() as usize).as_ref(),)*
+                    $(self.0.slot::<$T>($i, index).as_ref(),)*
                 )})
             }
             pub fn into_cols_mut(self, index: I) -> Result<(&'a mut $T0, $(&'a mut $T,)*), (Self, StoreError)> {
                 let index = index.into_index();
-                // SAFETY: self.1 is a valid pointer ot an occupation table
-                if let Err(err) = unsafe { validate_row_index(self.1, index) } {
+                // SAFETY: self.0 holds a valid pointer to an occupation table
+                if let Err(err) = unsafe { validate_row_index(self.0.occupation, index) } {
                     return Err((self, err));
                 }
+                // SAFETY: index was validated above
+                unsafe { self.0.mark_all(index) };
                 Ok(unsafe {(
-                    self.0[0].cast::<FreelistEntry<$T0>>().add(index.get() as usize)
+                    self.0.slot::<FreelistEntry<$T0>>(0, index)
                         .cast::<$T0>().as_mut(),
-                    $(self.0[$i].cast::<$T>().add(index.get() as usize).as_mut(),)*
+                    $(self.0.slot::<$T>($i, index).as_mut(),)*
                 )})
             }
         }
         unsafe impl<$T0, $($T),*> Columns for ($T0, $($T,)*) {
-            const COUNT: usize = 1 $(+{$i;1})*;
+            type Schema = ();
 
             type Ref<'a, I> = TupleRef<'a, ($T0, $($T,)*), I>
             where
@@ -421,7 +710,11 @@ macro_rules! impl_columns {
                 I: IntoIndex + 'a,
                 Self: 'a;
 
+            fn count(_: &()) -> usize {
+                1 $(+{$i;1})*
+            }
             fn register_layout(
+                _: &(),
                 rows: Length,
                 register: &mut impl FnMut(Layout),
             ) -> Result<(), LayoutError> {
@@ -435,7 +728,7 @@ macro_rules! impl_columns {
                 )?);)*
                 Ok(())
             }
-            fn move_into(self, index: Index, columns: &[NonNull<u8>]) {
+            fn move_into(self, _: &(), index: Index, columns: &[NonNull<u8>]) {
                 let ($t0, $($t,)*) = self;
                 {
                     // SAFETY: column 0 was registered to be of type FreelistEntry<$T0>
@@ -449,7 +742,7 @@ macro_rules! impl_columns {
                     unsafe { columns[$i].cast::<$T>().add(index.get() as usize).write($t) };
                 })*
             }
-            fn take(index: Index, columns: &[NonNull<u8>]) -> Self {
+            fn take(_: &(), index: Index, columns: &[NonNull<u8>]) -> Self {
                 (
                     {
                         // SAFETY: column 0 was registered to be of type FreelistEntry<$T0> and holds a $T0
@@ -464,26 +757,30 @@ macro_rules! impl_columns {
                     },)*
                 )
             }
-            fn as_freelist_entry(index: Index, columns: &[NonNull<u8>]) -> &mut Option<Index> {
+            fn as_freelist_entry<'a>(
+                _: &(),
+                index: Index,
+                columns: &'a [NonNull<u8>],
+            ) -> &'a mut Option<Index> {
                 // SAFETY: column 0 was registered to be of type FreelistEntry<$T0> and holds a Option<Index>
                 unsafe {
                     columns[0].cast::<FreelistEntry<$T0>>()
                         .add(index.get() as usize).cast::<Option<Index>>().as_mut()
                 }
             }
-            fn make_ref<'a, I>(columns: &'a [NonNull<u8>], occupation_ptr: NonNull<u8>) -> Self::Ref<'a, I>
+            unsafe fn make_ref<'a, I>(_: &'a (), rows: RawRows<'a>) -> Self::Ref<'a, I>
             where
                 I: IntoIndex + 'a,
                 Self: 'a,
             {
-                TupleRef(columns, occupation_ptr, PhantomData)
+                TupleRef(rows, PhantomData)
             }
-            fn make_mut<'a, I>(columns: &'a [NonNull<u8>], occupation_ptr: NonNull<u8>) -> Self::Mut<'a, I>
+            unsafe fn make_mut<'a, I>(_: &'a (), rows: RawRows<'a>) -> Self::Mut<'a, I>
             where
                 I: IntoIndex + 'a,
                 Self: 'a,
             {
-                TupleMut(columns, occupation_ptr, PhantomData)
+                TupleMut(rows, PhantomData)
             }
         }
     } };
@@ -500,32 +797,38 @@ impl<T, C> Prefix<T, C> {
         self.0.1
     }
 }
-pub struct JoinRef<'a, T, I>(&'a [NonNull<u8>], NonNull<u8>, PhantomData<fn(I) -> &'a T>);
-pub struct JoinMut<'a, T, I>(&'a [NonNull<u8>], NonNull<u8>, PhantomData<fn(I) -> &'a mut T>);
+/// Tuples of [`Columns`] that can be joined, gives access to the schemas of all parts.
+pub trait JoinParts {
+    type Schema;
+}
+pub struct JoinRef<'a, T: JoinParts, I>(&'a T::Schema, RawRows<'a>, PhantomData<fn(I) -> &'a T>);
+pub struct JoinMut<'a, T: JoinParts, I>(
+    &'a T::Schema,
+    RawRows<'a>,
+    PhantomData<fn(I) -> &'a mut T>,
+);
 macro_rules! impl_join {
     ((0, $T0:ident) $(,($i:tt, $T:ident))*) => { paste! {
+        impl<$T0: Columns, $($T: Columns,)*> JoinParts for ($T0, $($T,)*) {
+            type Schema = ($T0::Schema, $($T::Schema,)*);
+        }
         impl<'a, $T0: Columns, $($T: Columns,)* I> JoinRef<'a, ($T0, $($T,)*), I>
         where
             I: IntoIndex,
         {
-            const COUNTS: [usize; 1 $(+{$i;1})*] = [
-                $T0::COUNT,
-                $($T::COUNT),*
-            ];
-            const fn offset(mut i: usize) -> usize {
-                let mut result = 0;
-                while i > 0 {
-                    i -= 1;
-                    result  += Self::COUNTS[i];
-                }
-                result
+            fn offset(&self, i: usize) -> usize {
+                let counts = [$T0::count(&self.0.0), $($T::count(&self.0.$i)),*];
+                counts[..i].iter().sum()
             }
             pub fn part0(&self) -> $T0::Ref<'a, I> {
-                $T0::make_ref(&self.0[0..$T0::COUNT], self.1)
+                // SAFETY: the columns of each part were registered by its schema
+                unsafe { $T0::make_ref(&self.0.0, self.1.slice(0..self.offset(1))) }
             }
             $(
                 pub fn [<part $i>](&self) -> $T::Ref<'a, I> {
-                    $T::make_ref(&self.0[Self::offset($i)..Self::offset($i + 1)], self.1)
+                    let rows = self.1.slice(self.offset($i)..self.offset($i + 1));
+                    // SAFETY: the columns of each part were registered by its schema
+                    unsafe { $T::make_ref(&self.0.$i, rows) }
                 }
             )*
             pub fn parts(&self) -> ($T0::Ref<'a, I>, $($T::Ref<'a, I>,)*) {
@@ -536,73 +839,88 @@ macro_rules! impl_join {
         where
             I: IntoIndex,
         {
-            const COUNTS: [usize; 1 $(+{$i;1})*] = [
-                $T0::COUNT,
-                $($T::COUNT),*
-            ];
-            const fn offset(mut i: usize) -> usize {
-                let mut result = 0;
-                while i > 0 {
-                    i -= 1;
-                    result  += Self::COUNTS[i];
-                }
-                result
+            fn offset(&self, i: usize) -> usize {
+                let counts = [$T0::count(&self.0.0), $($T::count(&self.0.$i)),*];
+                counts[..i].iter().sum()
             }
             pub fn part0(&self) -> $T0::Ref<'_, I> {
-                $T0::make_ref(&self.0[0..$T0::COUNT], self.1)
+                // SAFETY: the columns of each part were registered by its schema, self is borrowed
+                unsafe { $T0::make_ref(&self.0.0, self.1.slice(0..self.offset(1))) }
             }
             pub fn part0_mut(&mut self) -> $T0::Mut<'_, I> {
-                $T0::make_mut(&self.0[0..$T0::COUNT], self.1)
+                // SAFETY: the columns of each part were registered by its schema,
+                // self is borrowed mutably
+                unsafe { $T0::make_mut(&self.0.0, self.1.slice(0..self.offset(1))) }
             }
             pub fn into_part0(self) -> $T0::Ref<'a, I> {
-                $T0::make_ref(&self.0[0..$T0::COUNT], self.1)
+                // SAFETY: the columns of each part were registered by its schema, self is consumed
+                unsafe { $T0::make_ref(&self.0.0, self.1.slice(0..self.offset(1))) }
             }
             pub fn into_part0_mut(self) -> $T0::Mut<'a, I> {
-                $T0::make_mut(&self.0[0..$T0::COUNT], self.1)
+                // SAFETY: the columns of each part were registered by its schema, self is consumed
+                unsafe { $T0::make_mut(&self.0.0, self.1.slice(0..self.offset(1))) }
             }
             $(
                 pub fn [<part $i>](&self) -> $T::Ref<'_, I> {
-                    $T::make_ref(&self.0[Self::offset($i)..Self::offset($i + 1)], self.1)
+                    let rows = self.1.slice(self.offset($i)..self.offset($i + 1));
+                    // SAFETY: the columns of each part were registered by its schema,
+                    // self is borrowed
+                    unsafe { $T::make_ref(&self.0.$i, rows) }
                 }
                 pub fn [<part $i _mut>](&mut self) -> $T::Mut<'_, I> {
-                    $T::make_mut(&self.0[Self::offset($i)..Self::offset($i + 1)], self.1)
+                    let rows = self.1.slice(self.offset($i)..self.offset($i + 1));
+                    // SAFETY: the columns of each part were registered by its schema,
+                    // self is borrowed mutably
+                    unsafe { $T::make_mut(&self.0.$i, rows) }
                 }
                 pub fn [<into_part $i>](self) -> $T::Ref<'a, I> {
-                    $T::make_ref(&self.0[Self::offset($i)..Self::offset($i + 1)], self.1)
+                    let rows = self.1.slice(self.offset($i)..self.offset($i + 1));
+                    // SAFETY: the columns of each part were registered by its schema,
+                    // self is consumed
+                    unsafe { $T::make_ref(&self.0.$i, rows) }
                 }
                 pub fn [<into_part $i _mut>](self) -> $T::Mut<'a, I> {
-                    $T::make_mut(&self.0[Self::offset($i)..Self::offset($i + 1)], self.1)
+                    let rows = self.1.slice(self.offset($i)..self.offset($i + 1));
+                    // SAFETY: the columns of each part were registered by its schema,
+                    // self is consumed
+                    unsafe { $T::make_mut(&self.0.$i, rows) }
                 }
             )*
             pub fn parts(&self) -> ($T0::Ref<'_, I>, $($T::Ref<'_, I>,)*) {
-                (
-                    $T0::make_ref(&self.0[0..$T0::COUNT], self.1),
-                    $($T::make_ref(&self.0[Self::offset($i)..Self::offset($i + 1)], self.1),)*
-                )
+                // SAFETY: the columns of each part were registered by its schema, self is borrowed
+                unsafe { (
+                    $T0::make_ref(&self.0.0, self.1.slice(0..self.offset(1))),
+                    $($T::make_ref(&self.0.$i, self.1.slice(self.offset($i)..self.offset($i + 1))),)*
+                ) }
             }
             pub fn parts_mut(&mut self) -> ($T0::Mut<'_, I>, $($T::Mut<'_, I>,)*) {
-                (
-                    $T0::make_mut(&self.0[0..$T0::COUNT], self.1),
-                    $($T::make_mut(&self.0[Self::offset($i)..Self::offset($i + 1)], self.1),)*
-                )
+                // SAFETY: the parts have disjoint columns registered by their schemas,
+                // self is borrowed mutably
+                unsafe { (
+                    $T0::make_mut(&self.0.0, self.1.slice(0..self.offset(1))),
+                    $($T::make_mut(&self.0.$i, self.1.slice(self.offset($i)..self.offset($i + 1))),)*
+                ) }
             }
             pub fn into_parts(self) -> ($T0::Ref<'a, I>, $($T::Ref<'a, I>,)*) {
-                (
-                    $T0::make_ref(&self.0[0..$T0::COUNT], self.1),
-                    $($T::make_ref(&self.0[Self::offset($i)..Self::offset($i + 1)], self.1),)*
-                )
+                // SAFETY: the columns of each part were registered by its schema, self is consumed
+                unsafe { (
+                    $T0::make_ref(&self.0.0, self.1.slice(0..self.offset(1))),
+                    $($T::make_ref(&self.0.$i, self.1.slice(self.offset($i)..self.offset($i + 1))),)*
+                ) }
             }
             pub fn into_parts_mut(self) -> ($T0::Mut<'a, I>, $($T::Mut<'a, I>,)*) {
-                (
-                    $T0::make_mut(&self.0[0..$T0::COUNT], self.1),
-                    $($T::make_mut(&self.0[Self::offset($i)..Self::offset($i + 1)], self.1),)*
-                )
+                // SAFETY: the parts have disjoint columns registered by their schemas,
+                // self is consumed
+                unsafe { (
+                    $T0::make_mut(&self.0.0, self.1.slice(0..self.offset(1))),
+                    $($T::make_mut(&self.0.$i, self.1.slice(self.offset($i)..self.offset($i + 1))),)*
+                ) }
             }
         }
         unsafe impl<$T0: Columns, $($T: Columns),*> Columns for Join<($T0, $($T,)*)>
         {
             #![allow(unused_assignments)]
-            const COUNT: usize = $T0::COUNT $(+ $T::COUNT)*;
+            type Schema = ($T0::Schema, $($T::Schema,)*);
 
             type Ref<'a, I> = JoinRef<'a, ($T0, $($T,)*), I>
             where
@@ -613,53 +931,60 @@ macro_rules! impl_join {
                 I: IntoIndex + 'a,
                 Self: 'a;
 
+            fn count(schema: &Self::Schema) -> usize {
+                $T0::count(&schema.0) $(+ $T::count(&schema.$i))*
+            }
             fn register_layout(
+                schema: &Self::Schema,
                 count: Length,
                 register: &mut impl FnMut(Layout),
             ) -> Result<(), LayoutError> {
-                $T0::register_layout(count, register)?;
-                $($T::register_layout(count, register)?;)*
+                $T0::register_layout(&schema.0, count, register)?;
+                $($T::register_layout(&schema.$i, count, register)?;)*
                 Ok(())
             }
             fn move_into(
                 self,
+                schema: &Self::Schema,
                 index: Index,
                 columns: &[NonNull<u8>],
             ) {
-                self.0.0.move_into(index, &columns[0..$T0::COUNT]);
-                let mut i0 = $T0::COUNT;
+                let mut i0 = $T0::count(&schema.0);
+                self.0.0.move_into(&schema.0, index, &columns[0..i0]);
                 $({
-                    self.0.$i.move_into(index, &columns[i0..i0+$T::COUNT]);
-                    i0 += $T::COUNT;
+                    let i1 = i0 + $T::count(&schema.$i);
+                    self.0.$i.move_into(&schema.$i, index, &columns[i0..i1]);
+                    i0 = i1;
                 })*
             }
-            fn take(index: Index, columns: &[NonNull<u8>]) -> Self {
-                let mut offsets = [$T0::COUNT; 1 $(+{$i;1})*];
-                $(offsets[$i] = offsets[$i - 1] + $T::COUNT;)*
+            fn take(schema: &Self::Schema, index: Index, columns: &[NonNull<u8>]) -> Self {
+                let mut offsets = [$T0::count(&schema.0); 1 $(+{$i;1})*];
+                $(offsets[$i] = offsets[$i - 1] + $T::count(&schema.$i);)*
                 Self((
-                    $T0::take(index, &columns[0..$T0::COUNT]),
-                    $($T::take(index, &columns[offsets[$i - 1]..offsets[$i]]),)*
+                    $T0::take(&schema.0, index, &columns[0..offsets[0]]),
+                    $($T::take(&schema.$i, index, &columns[offsets[$i - 1]..offsets[$i]]),)*
                 ))
             }
-            fn as_freelist_entry(
+            fn as_freelist_entry<'a>(
+                schema: &Self::Schema,
                 index: Index,
-                columns: &[NonNull<u8>],
-            ) -> &mut Option<Index> {
-                $T0::as_freelist_entry(index, &columns[0..$T0::COUNT])
+                columns: &'a [NonNull<u8>],
+            ) -> &'a mut Option<Index> {
+                $T0::as_freelist_entry(&schema.0, index, &columns[0..$T0::count(&schema.0)])
             }
-            fn make_ref<'a, I>(columns: &'a [NonNull<u8>], occupation_ptr: NonNull<u8>) -> Self::Ref<'a, I>
+            unsafe fn make_ref<'a, I>(schema: &'a Self::Schema, rows: RawRows<'a>) -> Self::Ref<'a, I>
             where
                 I: IntoIndex + 'a,
                 Self: 'a,
             {
-                JoinRef(columns, occupation_ptr, PhantomData)
+                JoinRef(schema, rows, PhantomData)
             }
-            fn make_mut<'a, I>(columns: &'a [NonNull<u8>], occupation_ptr: NonNull<u8>) -> Self::Mut<'a, I>
+            unsafe fn make_mut<'a, I>(schema: &'a Self::Schema, rows: RawRows<'a>) -> Self::Mut<'a, I>
             where
                 I: IntoIndex + 'a,
                 Self: 'a,
             {
-                JoinMut(columns, occupation_ptr, PhantomData)
+                JoinMut(schema, rows, PhantomData)
             }
         }
     }};
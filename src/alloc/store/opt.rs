@@ -0,0 +1,262 @@
+use super::*;
+
+/// Optional column, which keeps a presence bitmap instead of a discriminant per row.
+///
+/// Use it as part of a [`Join`], e.g. `Join<((u32,), Opt<Name>)>`.
+/// Column 0 holds the values and column 1 the bitmap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Opt<T>(pub Option<T>);
+impl<T> Default for Opt<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+impl<T> From<Option<T>> for Opt<T> {
+    fn from(value: Option<T>) -> Self {
+        Self(value)
+    }
+}
+
+/// # Safety
+/// `index` has to be within capacity of `columns`, which were registered by [`Opt`].
+unsafe fn presence(columns: &[NonNull<u8>], index: Index) -> (NonNull<u8>, u8) {
+    let index = index.get() as usize;
+    // SAFETY: guarantied by caller
+    (unsafe { columns[1].add(index / 8) }, 1 << (index % 8))
+}
+/// # Safety
+/// `index` has to be within capacity of `columns`, which were registered by [`Opt<T>`].
+unsafe fn value<T>(columns: &[NonNull<u8>], index: Index) -> NonNull<T> {
+    // SAFETY: guarantied by caller
+    unsafe { columns[0].cast::<FreelistEntry<T>>().add(index.get() as usize).cast() }
+}
+
+/// # Safety
+/// `rows` have to be registered by [`Opt`] and `index` has to be validated.
+unsafe fn is_present(rows: &RawRows<'_>, index: Index) -> bool {
+    // SAFETY: guarantied by caller
+    let (byte, mask) = unsafe { rows.bit(1, index) };
+    // SAFETY: the bitmap was zeroed and written for every row since
+    unsafe { byte.read() & mask != 0 }
+}
+/// # Safety
+/// `rows` have to be registered by [`Opt`], `index` has to be validated
+/// and the store has to be borrowed mutably.
+unsafe fn set_present(rows: &RawRows<'_>, index: Index, present: bool) {
+    // SAFETY: guarantied by caller
+    unsafe {
+        let (byte, mask) = rows.bit(1, index);
+        byte.write(if present { byte.read() | mask } else { byte.read() & !mask });
+    }
+}
+/// # Safety
+/// `rows` have to be registered by [`Opt<T>`] and `index` has to be validated.
+unsafe fn present<T>(rows: &RawRows<'_>, index: Index) -> Option<NonNull<T>> {
+    // SAFETY: guarantied by caller
+    unsafe { is_present(rows, index).then(|| rows.slot::<FreelistEntry<T>>(0, index).cast()) }
+}
+
+pub struct OptRef<'a, T, I>(RawRows<'a>, PhantomData<fn(I) -> &'a T>);
+pub struct OptMut<'a, T, I>(RawRows<'a>, PhantomData<fn(I) -> &'a mut T>);
+impl<'a, T, I: IntoIndex> OptRef<'a, T, I> {
+    pub fn get(&self, index: I) -> SResult<Option<&'a T>> {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation(), index)? };
+        // SAFETY: index was validated above
+        Ok(unsafe { present::<T>(&self.0, index).map(|value| value.as_ref()) })
+    }
+    pub fn contains(&self, index: I) -> SResult<bool> {
+        Ok(self.get(index)?.is_some())
+    }
+    /// Iterates over the rows that hold a value.
+    pub fn iter(&self) -> impl Iterator<Item = (Index, &'a T)> + use<'a, T, I> {
+        let rows = self.0;
+        // SAFETY: index is occupied
+        rows.bitmap().ones().filter_map(move |index| unsafe {
+            present::<T>(&rows, index).map(|value| (index, value.as_ref()))
+        })
+    }
+}
+impl<'a, T, I: IntoIndex> OptMut<'a, T, I> {
+    pub fn get(&self, index: I) -> SResult<Option<&T>> {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation(), index)? };
+        // SAFETY: index was validated above
+        Ok(unsafe { present::<T>(&self.0, index).map(|value| value.as_ref()) })
+    }
+    pub fn get_mut(&mut self, index: I) -> SResult<Option<&mut T>> {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation(), index)? };
+        // SAFETY: index was validated above and self is borrowed mutably
+        Ok(unsafe {
+            present::<T>(&self.0, index).map(|mut value| {
+                self.0.mark(0, index);
+                value.as_mut()
+            })
+        })
+    }
+    pub fn contains(&self, index: I) -> SResult<bool> {
+        Ok(self.get(index)?.is_some())
+    }
+    /// Stores `value` at `index`, returning the previous value.
+    pub fn set(&mut self, index: I, value: T) -> SResult<Option<T>> {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation(), index)? };
+        // SAFETY: index was validated above and self is borrowed mutably
+        unsafe {
+            let previous = present::<T>(&self.0, index).map(|value| value.read());
+            self.0.slot::<FreelistEntry<T>>(0, index).cast::<T>().write(value);
+            set_present(&self.0, index, true);
+            self.0.mark_all(index);
+            Ok(previous)
+        }
+    }
+    /// Removes the value at `index`, keeping the row.
+    pub fn unset(&mut self, index: I) -> SResult<Option<T>> {
+        let index = index.into_index();
+        // SAFETY: self.0 holds a valid pointer to an occupation table
+        unsafe { validate_row_index(self.0.occupation(), index)? };
+        // SAFETY: index was validated above and self is borrowed mutably
+        unsafe {
+            let Some(value) = present::<T>(&self.0, index) else { return Ok(None) };
+            set_present(&self.0, index, false);
+            self.0.mark_all(index);
+            Ok(Some(value.read()))
+        }
+    }
+    /// Iterates over the rows that hold a value.
+    pub fn iter(&self) -> impl Iterator<Item = (Index, &T)> {
+        let rows = self.0;
+        // SAFETY: index is occupied
+        rows.bitmap().ones().filter_map(move |index| unsafe {
+            present::<T>(&rows, index).map(|value| (index, value.as_ref()))
+        })
+    }
+    /// Iterates mutably over the rows that hold a value.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Index, &mut T)> {
+        let rows = self.0;
+        // SAFETY: index is occupied, every row is visited once and self is borrowed mutably
+        rows.bitmap().ones().filter_map(move |index| unsafe {
+            present::<T>(&rows, index).map(|mut value| {
+                rows.mark(0, index);
+                (index, value.as_mut())
+            })
+        })
+    }
+}
+
+// SAFETY: column 0 is registered as `FreelistEntry<T>` and column 1 as a bitmap with a bit per row
+unsafe impl<T> Columns for Opt<T> {
+    type Schema = ();
+
+    type Ref<'a, I>
+        = OptRef<'a, T, I>
+    where
+        I: IntoIndex + 'a,
+        Self: 'a;
+    type Mut<'a, I>
+        = OptMut<'a, T, I>
+    where
+        I: IntoIndex + 'a,
+        Self: 'a;
+
+    fn count(_: &()) -> usize {
+        2
+    }
+    fn register_layout(
+        _: &(),
+        rows: Length,
+        register: &mut impl FnMut(Layout),
+    ) -> Result<(), LayoutError> {
+        register(Layout::array::<FreelistEntry<T>>(rows as usize)?);
+        register(Layout::array::<u8>(rows.div_ceil(8) as usize)?);
+        Ok(())
+    }
+    fn move_into(self, _: &(), index: Index, columns: &[NonNull<u8>]) {
+        // SAFETY: the columns were registered by this type
+        unsafe {
+            let (byte, mask) = presence(columns, index);
+            match self.0 {
+                Some(item) => {
+                    value::<T>(columns, index).write(item);
+                    byte.write(byte.read() | mask);
+                },
+                None => byte.write(byte.read() & !mask),
+            }
+        }
+    }
+    fn take(_: &(), index: Index, columns: &[NonNull<u8>]) -> Self {
+        // SAFETY: the columns were registered by this type and the bit tells whether a value is held
+        unsafe {
+            let (byte, mask) = presence(columns, index);
+            Self((byte.read() & mask != 0).then(|| value::<T>(columns, index).read()))
+        }
+    }
+    fn as_freelist_entry<'a>(
+        _: &(),
+        index: Index,
+        columns: &'a [NonNull<u8>],
+    ) -> &'a mut Option<Index> {
+        // SAFETY: column 0 was registered to be of type FreelistEntry<T>
+        unsafe { value::<Option<Index>>(columns, index).as_mut() }
+    }
+    unsafe fn make_ref<'a, I>(_: &'a (), rows: RawRows<'a>) -> Self::Ref<'a, I>
+    where
+        I: IntoIndex + 'a,
+        Self: 'a,
+    {
+        OptRef(rows, PhantomData)
+    }
+    unsafe fn make_mut<'a, I>(_: &'a (), rows: RawRows<'a>) -> Self::Mut<'a, I>
+    where
+        I: IntoIndex + 'a,
+        Self: 'a,
+    {
+        OptMut(rows, PhantomData)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+    use crate::alloc::manager::{Chunked, Manager, SoA, Versioned};
+
+    #[test]
+    fn sets_and_iterates_present_values() {
+        make_guard!(guard);
+        let mut manager =
+            Manager::<SoA<Join<((u32,), Opt<String>)>, Chunked<4>>, Versioned<true>>::new(guard);
+        manager.reserve(8).unwrap();
+        let handles: Vec<_> = (0..10)
+            .map(|i| {
+                let name = (i % 3 == 0).then(|| i.to_string());
+                manager.insert_within_capacity(Join(((i,), name.into()))).unwrap()
+            })
+            .collect();
+        {
+            let mut view = manager.view_mut();
+            let mut names = view.part1_mut();
+            assert_eq!(Ok(None), names.set(handles[5], "five".into()));
+            assert_eq!(Ok(Some("3".into())), names.unset(handles[3]));
+            assert_eq!(Ok(None), names.unset(handles[4]));
+            names.get_mut(handles[9]).unwrap().unwrap().push('!');
+        }
+        let view = manager.view();
+        let names = view.part1();
+        assert_eq!(Ok(Some(&"9!".to_string())), names.get(handles[9]));
+        assert_eq!(Ok(false), names.contains(handles[3]));
+        let present: Vec<_> = names.iter().map(|(_, name)| name.as_str()).collect();
+        assert_eq!(vec!["0", "five", "6", "9!"], present);
+
+        let Join(((id,), name)) = manager.remove(handles[5]).unwrap();
+        assert_eq!((5, Some("five".into())), (id, name.0));
+        let handle = manager.insert_within_capacity(Join(((10,), Opt(None)))).unwrap();
+        assert_eq!(Ok(None), manager.view().part1().get(handle));
+    }
+}
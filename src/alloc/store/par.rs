@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use rayon::prelude::*;
+
+use super::*;
+
+/// Stores that can visit all of their elements from a thread pool.
+pub trait ParIter<T> {
+    fn par_iter<'a>(&'a self) -> impl ParallelIterator<Item = (Index, &'a T)>
+    where
+        T: Sync + 'a;
+    fn par_iter_mut<'a>(&'a mut self) -> impl ParallelIterator<Item = (Index, &'a mut T)>
+    where
+        T: Send + 'a;
+}
+/// Walks the occupied rows of all columns in `access` from a thread pool,
+/// each task handles the rows of one word of the occupation table.
+/// # Panics
+/// All columns have to share the same occupation table.
+pub fn par_zip_rows<'a, A>(access: A) -> impl ParallelIterator<Item = (Index, A::Item)>
+where
+    A: ColumnAccess<'a> + Send + Sync,
+    A::Item: Send,
+{
+    let occupation = access.occupation();
+    let words = occupation.len().div_ceil(u64::BITS) as usize;
+    let access = Arc::new(access);
+    (0..words).into_par_iter().flat_map_iter(move |word| {
+        let access = access.clone();
+        // SAFETY: every occupied index is visited once
+        occupation.word_ones(word).map(move |index| (index, unsafe { access.fetch(index) }))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use generativity::make_guard;
+
+    use super::*;
+    use crate::alloc::{arena::*, manager::*};
+
+    #[test]
+    fn visits_occupied_rows_in_parallel() {
+        let mut store = SoAFreelistStore::<(u32, u64)>::with_capacity(1000);
+        let indices: Vec<_> =
+            (0..1000).map(|i| store.insert_within_capacity((i, 0)).unwrap()).collect();
+        for index in indices.iter().filter(|index| index.get() % 2 == 0) {
+            store.remove(*index).unwrap();
+        }
+        let mut view: TupleMut<'_, (u32, u64), Index> = store.view_mut();
+        let (ids, sums) = view.columns_mut().unwrap();
+        par_zip_rows((ids.as_slice(), sums)).for_each(|(_, (id, sum))| *sum = *id as u64 * 2);
+        let sums = view.column_slice::<1>().unwrap();
+        assert_eq!(500, sums.iter().count());
+        assert!(sums.iter().all(|(index, sum)| *sum == index.get() as u64 * 2));
+
+        let mut store = FreelistStore::<u32>::new();
+        store.widen(100).unwrap();
+        for i in 0..100 {
+            store.insert_within_capacity(i).unwrap();
+        }
+        store.par_iter_mut().for_each(|(_, x)| *x += 1);
+        assert_eq!(5050, store.par_iter().map(|(_, x)| *x).sum::<u32>());
+    }
+
+    #[test]
+    fn visits_arenas_in_parallel() {
+        make_guard!(guard);
+        let mut arena = Arena::<Typed<u32>, Exclusive<true>>::new(guard);
+        let handles: Vec<_> = (0..100).map(|i| arena.insert(i).unwrap()).collect();
+        arena.par_iter_mut().for_each(|x| *x *= 2);
+        assert_eq!(9900, arena.par_iter().sum::<u32>());
+        assert_eq!(Ok(&198), arena.get(&handles[99]));
+
+        make_guard!(guard);
+        make_guard!(manager_guard);
+        let mut arena =
+            Arena::<Typed<u32>, Versioned<true>>::new(guard, manager_guard, Headless::default());
+        let handles: Vec<_> = {
+            let mut alloc = arena.alloc();
+            (0..100).map(|i| alloc.insert(i).unwrap()).collect()
+        };
+        let read = arena.read();
+        let values: Vec<_> = read.par_get(&handles).map(Result::unwrap).copied().collect();
+        assert_eq!((0..100).collect::<Vec<_>>(), values);
+        drop(read);
+
+        make_guard!(other_guard);
+        let mut other = arena.split(other_guard);
+        other.alloc().insert(100).unwrap();
+        let read = arena.read_exclusive();
+        assert_eq!(5050, read.par_iter().sum::<u32>(), "entries of other ports should be visited");
+        assert!(other.try_read().is_none(), "other ports should be blocked");
+    }
+}
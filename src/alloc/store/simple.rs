@@ -1,3 +1,6 @@
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
 use super::*;
 
 #[derive(Debug)]
@@ -77,6 +80,15 @@ impl<T> Resizable for SimpleStore<T> {
         self.data.clear();
     }
 }
+impl<T> Truncate for SimpleStore<T> {
+    fn len(&self) -> Length {
+        self.data.len() as Length
+    }
+
+    fn truncate(&mut self, len: Length) {
+        self.data.truncate(len as usize);
+    }
+}
 impl<T> Store<T> for SimpleStore<T> {}
 
 impl<T> Get<Multi<T>> for SimpleStore<T> {
@@ -140,3 +152,27 @@ impl<T> InsertIndirect<Multi<T>> for SimpleStore<T> {
     }
 }
 impl<T> MultiStore<T> for SimpleStore<T> {}
+#[cfg(feature = "rayon")]
+impl<T> ParIter<T> for SimpleStore<T> {
+    fn par_iter<'a>(&'a self) -> impl ParallelIterator<Item = (Index, &'a T)>
+    where
+        T: Sync + 'a,
+    {
+        // SAFETY: all elements are within capacity
+        self.data
+            .par_iter()
+            .enumerate()
+            .map(|(i, x)| (unsafe { Index::new_unchecked(i as u32) }, x))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl ParallelIterator<Item = (Index, &'a mut T)>
+    where
+        T: Send + 'a,
+    {
+        self.data
+            .par_iter_mut()
+            .enumerate()
+            // SAFETY: all elements are within capacity
+            .map(|(i, x)| (unsafe { Index::new_unchecked(i as u32) }, x))
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    alloc::{Layout, alloc, dealloc, handle_alloc_error},
+    alloc::{Layout, alloc_zeroed, dealloc, handle_alloc_error},
     ptr::NonNull,
     slice,
 };
@@ -9,23 +9,25 @@ use super::*;
 // TODO: use this kinda structure for the other stores as well?
 // TODO: use this type as only freelist store (support both Typed and Mixed/Slices by using custom headers and specialized impls)
 #[derive(Debug)]
-pub struct SoAFreelistStore<C> {
+pub struct SoAFreelistStore<C: Columns> {
     /// # Memory layout
-    /// - column pointers: `0`: `[NonNull<u8>; C::COUNT]`
-    /// - layouts: `size_of(NonNull<u8>) * C::COUNT`: `[Layout; C::COUNT]`
-    /// - occupation table: `(size_of(NonNull<u8>) + size_of(Layout)) * C::COUNT`: `[u8; cap.div_ceil(8)]`
+    /// - column pointers: `0`: `[NonNull<u8>; count]`
+    /// - layouts: `size_of(NonNull<u8>) * count`: `[Layout; count]`
+    /// - occupation table: `(size_of(NonNull<u8>) + size_of(Layout)) * count`: `[u8; cap.div_ceil(8)]`
     /// - for each columns:
     ///   - `+ size_of(last column).next_mul(align_of(this column))`: `size of(this column)`
     buffer:    NonNull<u8>,
     cap:       Length,
     next_free: Index,
     head:      Option<Index>,
+    /// Number of columns, cached from the schema.
+    count:     usize,
+    schema:    C::Schema,
     _marker:   PhantomData<C>,
 }
-impl<C> SoAFreelistStore<C> {}
 impl<C> Default for SoAFreelistStore<C>
 where
-    C: Columns,
+    C: Columns<Schema: Default>,
 {
     fn default() -> Self {
         Self::new()
@@ -51,68 +53,72 @@ where
         align_of::<(NonNull<u8>, Layout)>()
     }
     // TODO: replace with C::COUNT sized array once stable
-    const fn columns_ptr(&self) -> NonNull<NonNull<u8>> {
+    fn columns_ptr(&self) -> NonNull<NonNull<u8>> {
         // SAFETY: buffer holds the columns array at this point
         self.buffer.cast::<NonNull<u8>>()
     }
     /// # Safety
     /// This will give mutable access to the columns header wihout checks.
     #[expect(clippy::mut_from_ref, reason = "needed to access in parallel with layout")]
-    const unsafe fn columns(&self) -> &mut [NonNull<u8>] {
+    unsafe fn columns(&self) -> &mut [NonNull<u8>] {
         // SAFETY: buffer holds the columns array at this point
-        unsafe { slice::from_raw_parts_mut(self.columns_ptr().as_mut(), C::COUNT) }
+        unsafe { slice::from_raw_parts_mut(self.columns_ptr().as_mut(), self.count) }
     }
-    const fn columns_size() -> usize {
-        C::COUNT * size_of::<NonNull<u8>>()
+    const fn columns_size(count: usize) -> usize {
+        count * size_of::<NonNull<u8>>()
     }
-    const fn layout_ptr(&self) -> NonNull<Layout> {
+    fn layout_ptr(&self) -> NonNull<Layout> {
         // SAFETY: buffer holds the layout array at this point
-        unsafe { self.buffer.add(Self::columns_size()).cast::<Layout>() }
+        unsafe { self.buffer.add(Self::columns_size(self.count)).cast::<Layout>() }
     }
     /// # Safety
     /// This will give mutable access to the columns header wihout checks.
     #[expect(clippy::mut_from_ref, reason = "needed to access in parallel with columns")]
-    const unsafe fn layout(&self) -> &mut [Layout] {
+    unsafe fn layout(&self) -> &mut [Layout] {
         // SAFETY: layout_ptr is a valid layout array
-        unsafe { slice::from_raw_parts_mut(self.layout_ptr().as_mut(), C::COUNT) }
+        unsafe { slice::from_raw_parts_mut(self.layout_ptr().as_mut(), self.count) }
     }
-    const fn layout_size() -> usize {
-        C::COUNT * size_of::<Layout>()
+    const fn layout_size(count: usize) -> usize {
+        count * size_of::<Layout>()
     }
-    const fn occupation_ptr(&self) -> NonNull<u8> {
+    fn occupation_ptr(&self) -> NonNull<u8> {
         // SAFETY: buffer holds the occupation table at this point
-        unsafe { self.buffer.add(Self::columns_size() + Self::layout_size()) }
+        unsafe { self.buffer.add(Self::columns_size(self.count) + Self::layout_size(self.count)) }
     }
     const fn occupation_size(capacity: Length) -> usize {
         capacity.div_ceil(8) as usize
     }
-    const fn header_size(capacity: Length) -> usize {
-        Self::columns_size() + Self::layout_size() + Self::occupation_size(capacity)
+    const fn header_size(count: usize, capacity: Length) -> usize {
+        Self::columns_size(count) + Self::layout_size(count) + Self::occupation_size(capacity)
     }
 
-    const fn is_occupied(&self, index: Index) -> bool {
+    fn is_occupied(&self, index: Index) -> bool {
         // SAFETY: if index is in capacity, then chunk is a valid part of the header
         let chunk = unsafe { self.occupation_ptr().add(index.get() as usize / 8).read() };
         chunk >> (index.get() % 8) & 1 == 1
     }
-    const fn set_occupied(&mut self, index: Index) {
+    fn set_occupied(&mut self, index: Index) {
         // SAFETY: if index is in capacity, then chunk is a valid part of the header
         let chunk = unsafe { self.occupation_ptr().add(index.get() as usize / 8).as_mut() };
         *chunk |= 1 << (index.get() % 8);
     }
-    const fn clear_occupied(&mut self, index: Index) {
+    fn clear_occupied(&mut self, index: Index) {
         // SAFETY: if index is in capacity, then chunk is a valid part of the header
         let chunk = unsafe { self.occupation_ptr().add(index.get() as usize / 8).as_mut() };
         *chunk &= !(1 << (index.get() % 8));
     }
 
-    fn register_columns(capacity: Length) -> Result<(Vec<Header>, Layout), LayoutError> {
-        let mut columns = Vec::with_capacity(C::COUNT);
-        C::register_layout(capacity, &mut |layout: Layout| {
+    fn register_columns(
+        schema: &C::Schema,
+        capacity: Length,
+    ) -> Result<(Vec<Header>, Layout), LayoutError> {
+        let count = C::count(schema);
+        let mut columns = Vec::with_capacity(count);
+        C::register_layout(schema, capacity, &mut |layout: Layout| {
             columns.push(Header::new(layout));
         })?;
-        debug_assert_eq!(columns.len(), C::COUNT);
-        let mut offset = Self::header_size(capacity);
+        debug_assert_eq!(columns.len(), count);
+        let mut offset = Self::header_size(count, capacity);
         let mut align = Self::header_align();
         for column in &mut columns {
             offset = offset.next_multiple_of(column.layout.align());
@@ -126,14 +132,14 @@ where
         Ok((columns, layout))
     }
     fn update_columns(&mut self, new_capacity: Length) -> Result<(Layout, Layout), LayoutError> {
-        let mut offset = Self::header_size(new_capacity);
-        let mut old_size = Self::header_size(self.cap);
+        let mut offset = Self::header_size(self.count, new_capacity);
+        let mut old_size = Self::header_size(self.count, self.cap);
         let mut align = Self::header_align();
         // SAFETY: self can be mutable here
         let mut columns = unsafe { self.columns().iter_mut() };
         // SAFETY: self can be mutable here
         let mut layouts = unsafe { self.layout().iter_mut() };
-        C::register_layout(new_capacity, &mut |new_layout: Layout| {
+        C::register_layout(&self.schema, new_capacity, &mut |new_layout: Layout| {
             // SAFETY: number of columns can't change
             let column = unsafe { columns.next().unwrap_unchecked() };
             // SAFETY: number of columns can't change
@@ -151,34 +157,51 @@ where
         })?;
         Ok((Layout::from_size_align(old_size, align)?, Layout::from_size_align(offset, align)?))
     }
-    fn allocate_initialized(capacity: Length) -> SResult<NonNull<u8>> {
-        let (headers, layout) = Self::register_columns(capacity)
+    fn allocate_initialized(schema: &C::Schema, capacity: Length) -> SResult<NonNull<u8>> {
+        let count = C::count(schema);
+        let (headers, layout) = Self::register_columns(schema, capacity)
             .map_err(|_| StoreError::InvalidLayout("invalid column layout"))?;
         // SAFETY: size is not zero
-        let buffer = unsafe { alloc(layout) };
+        let buffer = unsafe { alloc_zeroed(layout) };
         let Some(buffer) = NonNull::new(buffer) else { handle_alloc_error(layout) };
         for (i, header) in headers.into_iter().enumerate() {
             // SAFETY: buffer is big enough to hold header
             unsafe {
                 buffer.cast::<NonNull<u8>>().add(i).write(buffer.add(header.offset));
-                buffer.add(Self::columns_size()).cast::<Layout>().add(i).write(header.layout);
+                buffer.add(Self::columns_size(count)).cast::<Layout>().add(i).write(header.layout);
             }
         }
-        // SAFETY: buffer is big enough to hold occupation table
-        unsafe {
-            buffer
-                .add(Self::columns_size() + Self::layout_size())
-                .write_bytes(0, capacity.div_ceil(8) as usize)
-        };
         Ok(buffer)
     }
 
-    pub fn new() -> Self {
+    pub fn new() -> Self
+    where
+        C::Schema: Default,
+    {
         Self::with_capacity(Self::DEFAULT_CAPACITY)
     }
-    pub fn with_capacity(capacity: Length) -> Self {
-        let buffer = Self::allocate_initialized(capacity).expect("failed to allocate memory");
-        Self { buffer, cap: capacity, next_free: Index::ZERO, head: None, _marker: PhantomData }
+    pub fn with_capacity(capacity: Length) -> Self
+    where
+        C::Schema: Default,
+    {
+        Self::with_schema(C::Schema::default(), capacity)
+    }
+    /// Creates a store for columns only described at runtime.
+    pub fn with_schema(schema: C::Schema, capacity: Length) -> Self {
+        let buffer =
+            Self::allocate_initialized(&schema, capacity).expect("failed to allocate memory");
+        Self {
+            buffer,
+            cap: capacity,
+            next_free: Index::ZERO,
+            head: None,
+            count: C::count(&schema),
+            schema,
+            _marker: PhantomData,
+        }
+    }
+    pub fn schema(&self) -> &C::Schema {
+        &self.schema
     }
 }
 
@@ -198,21 +221,19 @@ where
             .update_columns(new_capacity)
             .map_err(|_| StoreError::InvalidLayout("invalid layout"))?;
         // SAFETY: layout is valid here
-        let buffer = unsafe { alloc(new_layout) };
+        let buffer = unsafe { alloc_zeroed(new_layout) };
         let Some(buffer) = NonNull::new(buffer) else {
             handle_alloc_error(new_layout);
         };
-        let old_header_size = Self::header_size(self.cap);
-        let new_header_size = Self::header_size(new_capacity);
+        let old_header_size = Self::header_size(self.count, self.cap);
+        let new_header_size = Self::header_size(self.count, new_capacity);
         debug_assert!(new_header_size >= old_header_size);
         // SAFETY: new header is larger than old header
         unsafe { buffer.copy_from_nonoverlapping(self.buffer, old_header_size) };
-        // SAFETY: buffer is big enough to hold the header
-        unsafe { buffer.add(old_header_size).write_bytes(0, new_header_size - old_header_size) };
         let mut new_columns = buffer.cast::<NonNull<u8>>();
         let mut old_offset = old_header_size;
         // NOTE: the old layout has to be re-calculated because the header was overritten in update_columns
-        C::register_layout(self.cap, &mut |old_layout: Layout| {
+        C::register_layout(&self.schema, self.cap, &mut |old_layout: Layout| {
             old_offset = old_offset.next_multiple_of(old_layout.align());
             // SAFETY: new_columns holds the offset from the base pointer of the new columns (set in update_columns)
             let new_column = unsafe { buffer.add(new_columns.read().as_ptr() as usize) };
@@ -229,14 +250,13 @@ where
         // SAFETY: pointer and layout match at this point
         unsafe { dealloc(self.buffer.as_ptr(), old_layout) };
         self.buffer = buffer;
+        self.cap = new_capacity;
         Ok(())
     }
 
     fn clear(&mut self) {
         // SAFETY: buffer contains a valid occupied table at this point
-        unsafe {
-            self.buffer.add(Self::columns_size()).write_bytes(0, self.cap.div_ceil(8) as usize)
-        };
+        unsafe { self.occupation_ptr().write_bytes(0, Self::occupation_size(self.cap)) };
         self.next_free = Index::ZERO;
         self.head = None;
     }
@@ -248,7 +268,7 @@ where
     fn insert_within_capacity(&mut self, element: C) -> Result<Index, C> {
         let index = if let Some(head) = self.head {
             // SAFETY: self can be mutable here
-            self.head = *C::as_freelist_entry(head, unsafe { self.columns() });
+            self.head = *C::as_freelist_entry(&self.schema, head, unsafe { self.columns() });
             head
         } else if self.next_free.get() < self.cap {
             let next_free = self.next_free;
@@ -260,7 +280,7 @@ where
         };
         debug_assert!(!self.is_occupied(index));
         // SAFETY: self can be mutable here
-        element.move_into(index, unsafe { self.columns() });
+        element.move_into(&self.schema, index, unsafe { self.columns() });
         self.set_occupied(index);
         Ok(index)
     }
@@ -271,13 +291,34 @@ where
     C: Columns,
 {
     fn view(&self) -> <Rows<C, I> as Element>::Ref<'_> {
-        // SAFETY: columns is restricted to read-only access here
-        C::make_ref(unsafe { self.columns() }, self.occupation_ptr())
+        // SAFETY: the rows were registered by the schema and are only read through &self
+        unsafe { C::make_ref(&self.schema, self.raw_rows()) }
     }
 
     fn view_mut(&mut self) -> <Rows<C, I> as Element>::Mut<'_> {
-        // SAFETY: mutable access is valid here
-        C::make_mut(unsafe { self.columns() }, self.occupation_ptr())
+        let (schema, rows) = self.raw_rows_mut();
+        // SAFETY: the rows were registered by the schema and self is borrowed mutably
+        unsafe { C::make_mut(schema, rows) }
+    }
+}
+impl<C> RawView<C> for SoAFreelistStore<C>
+where
+    C: Columns,
+{
+    fn schema(&self) -> &C::Schema {
+        &self.schema
+    }
+
+    unsafe fn raw_rows(&self) -> RawRows<'_> {
+        // SAFETY: rows are restricted to read-only access by the trait contract,
+        // every column and the occupation table are allocated for `cap` rows
+        unsafe { RawRows::new(self.columns(), self.occupation_ptr(), self.cap) }
+    }
+
+    fn raw_rows_mut(&mut self) -> (&C::Schema, RawRows<'_>) {
+        // SAFETY: mutable access is valid here,
+        // every column and the occupation table are allocated for `cap` rows
+        (&self.schema, unsafe { RawRows::new(self.columns(), self.occupation_ptr(), self.cap) })
     }
 }
 impl<C, I> SoAStore<C, I> for SoAFreelistStore<C>
@@ -299,14 +340,71 @@ where
             return Err(StoreError::DoubleFree(index));
         }
         // SAFETY: self can be mutable here
-        let element = C::take(index, unsafe { self.columns() });
+        let columns = unsafe { self.columns() };
+        let element = C::take(&self.schema, index, columns);
+        *C::as_freelist_entry(&self.schema, index, columns) = self.head;
+        self.head = Some(index);
         self.clear_occupied(index);
         Ok(element)
     }
 }
+impl<C> Restore<C> for SoAFreelistStore<C>
+where
+    C: Columns,
+{
+    fn restore(&mut self, index: Index, element: C) -> Result<(), (C, StoreError)> {
+        if index >= self.next_free {
+            return Err((element, StoreError::OutOfBounds(index, self.next_free.get())));
+        }
+        if self.is_occupied(index) {
+            return Err((element, StoreError::Occupied(index)));
+        }
+        // SAFETY: self can be mutable here
+        let columns = unsafe { self.columns() };
+        let mut head = self.head;
+        if head == Some(index) {
+            head = *C::as_freelist_entry(&self.schema, index, columns);
+        } else {
+            let mut current = head;
+            while let Some(i) = current {
+                let link = C::as_freelist_entry(&self.schema, i, columns);
+                if *link == Some(index) {
+                    *link = *C::as_freelist_entry(&self.schema, index, columns);
+                    break;
+                }
+                current = *link;
+            }
+        }
+        element.move_into(&self.schema, index, columns);
+        self.head = head;
+        self.set_occupied(index);
+        Ok(())
+    }
+}
 impl<C, I> ReusableSoAStore<C, I> for SoAFreelistStore<C>
 where
     C: Columns,
     I: IntoIndex,
 {
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reuses_rows_after_widening() {
+        let mut store = SoAFreelistStore::<(u32, u64)>::new();
+        let count = SoAFreelistStore::<(u32, u64)>::DEFAULT_CAPACITY + 1;
+        assert_eq!(Ok(()), store.widen(count));
+        assert_eq!(count, store.capacity(), "widening should update the capacity");
+        let indices: Vec<_> = (0..count)
+            .map(|i| store.insert_within_capacity((i, i as u64)).expect("rows should fit"))
+            .collect();
+        assert_eq!(Ok((3, 3)), store.remove(indices[3]));
+        let index = store
+            .insert_within_capacity((42, 42))
+            .expect("removed row should be reused without allocation");
+        assert_eq!(indices[3], index, "index should be reused");
+    }
+}
@@ -1,3 +1,6 @@
+// generated code refers to this crate by name
+extern crate self as niche_collections;
+
 pub mod alloc;
 
 pub(crate) mod internal {